@@ -0,0 +1,29 @@
+//! Detects targets without compare-and-swap atomics (e.g., `thumbv6m-none-eabi`).
+//!
+//! `cfg(target_has_atomic)` can't be used directly, since it's only stable from Rust 1.60.
+
+use std::env;
+
+fn main() {
+    println!("cargo:rerun-if-changed=build.rs");
+    println!("cargo:rustc-check-cfg=cfg(loggery_no_atomic_cas)");
+
+    if !has_atomic_cas() {
+        println!("cargo:rustc-cfg=loggery_no_atomic_cas");
+    }
+}
+
+/// Returns `true` if the target has atomic read-modify-write operations on pointer-sized values.
+fn has_atomic_cas() -> bool {
+    // Only set by compilers where `target_has_atomic` is stable
+    if let Ok(widths) = env::var("CARGO_CFG_TARGET_HAS_ATOMIC") {
+        return widths.split(',').any(|width| width == "ptr");
+    }
+
+    let target = env::var("TARGET").unwrap_or_default();
+
+    !(target.starts_with("thumbv6m-")
+        || target.starts_with("riscv32i-")
+        || target.starts_with("riscv32imc-")
+        || target.starts_with("msp430-"))
+}
@@ -0,0 +1,62 @@
+use crate::Level;
+
+/// Level aliases storage, filled in order.
+static LEVEL_ALIASES: crate::spin::Mutex<[Option<(&'static str, Level)>; MAX_LEVEL_ALIASES]> =
+    crate::spin::Mutex::new([None; MAX_LEVEL_ALIASES]);
+
+/// Maximum number of aliases that can be registered with [`register_level_alias`]. (`std`
+/// feature)
+pub const MAX_LEVEL_ALIASES: usize = 8;
+
+/// Registers an extra name for a level, accepted when parsing a [`Level`] (e.g., from a config
+/// file). (`std` feature)
+///
+/// Like the built-in names, aliases are matched ignoring ASCII case and surrounding whitespace.
+/// The built-in names (`trace`, `debug`, `info`, `warn`, `warning`, `error` and `off`) take
+/// precedence, so an alias can't change their meaning. Registering an alias again replaces its
+/// level.
+///
+/// Returns `false` if the alias couldn't be registered because [`MAX_LEVEL_ALIASES`] aliases are
+/// already registered.
+///
+/// # Example
+///
+/// ```
+/// use loggery::Level;
+///
+/// assert!("critical".parse::<Level>().is_err());
+///
+/// assert!(loggery::register_level_alias("critical", Level::Error));
+/// assert!(loggery::register_level_alias("verbose", Level::Trace));
+///
+/// assert_eq!("critical".parse::<Level>(), Ok(Level::Error));
+/// assert_eq!(" Verbose ".parse::<Level>(), Ok(Level::Trace));
+/// ```
+pub fn register_level_alias(alias: &'static str, level: Level) -> bool {
+    let alias = alias.trim();
+    let mut aliases = LEVEL_ALIASES.lock();
+
+    let slot = aliases.iter_mut().find(|slot| match slot {
+        Some((name, _)) => name.eq_ignore_ascii_case(alias),
+        None => true,
+    });
+
+    match slot {
+        Some(slot) => {
+            *slot = Some((alias, level));
+
+            true
+        }
+        None => false,
+    }
+}
+
+/// Returns the level of a registered alias, `s` being already trimmed.
+pub(crate) fn find(s: &str) -> Option<Level> {
+    LEVEL_ALIASES
+        .lock()
+        .iter()
+        .flatten()
+        .find(|(name, _)| name.eq_ignore_ascii_case(s))
+        .map(|(_, level)| *level)
+}
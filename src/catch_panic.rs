@@ -0,0 +1,26 @@
+extern crate std;
+
+use crate::Payload;
+
+use std::{io::Write as _, panic::AssertUnwindSafe};
+
+/// Calls `logger` with `payload`, catching a panic of the logger so it doesn't unwind into the
+/// code that logged.
+///
+/// A caught panic is reported to stderr with a note naming the level of the lost log. The message
+/// isn't rendered again, since its arguments may be what panicked.
+#[inline(always)]
+pub(crate) fn call_logger(payload: Payload, logger: impl FnOnce(Payload)) {
+    let level = payload.level;
+
+    if std::panic::catch_unwind(AssertUnwindSafe(|| logger(payload))).is_err() {
+        let stderr = std::io::stderr();
+        let mut stderr = stderr.lock();
+
+        let _ = writeln!(
+            stderr,
+            "loggery: the logger panicked, a log at the {} level was lost",
+            level.name()
+        );
+    }
+}
@@ -0,0 +1,60 @@
+/// Returns the CPU core the current thread is running on, as stored in
+/// [`Metadata::cpu`](crate::Metadata::cpu). (`cpu_meta` feature)
+///
+/// This is best-effort:
+///
+/// - It's only supported on Linux (with `sched_getcpu`), it always returns `None` on other
+///   targets, or if the call fails.
+/// - The scheduler can migrate the thread to another core at any time, so the core may already
+///   be stale when the log is written, even when it's captured at the log call. Pin the thread
+///   (e.g., with `sched_setaffinity`) for the core to be reliable.
+///
+/// # Example
+///
+/// ```
+/// use loggery::{info, Payload};
+/// use std::cell::Cell;
+///
+/// thread_local! {
+///     static LAST_CPU: Cell<Option<u32>> = Cell::new(None);
+/// }
+///
+/// fn my_logger(payload: Payload) {
+///     LAST_CPU.with(|cpu| cpu.set(payload.meta.cpu));
+/// }
+///
+/// fn main() {
+/// #   #[cfg(not(feature = "static"))]
+///     loggery::set_logger(my_logger);
+///
+///     info!("Where am I?");
+///
+///     // A plausible core, Linux supports at most 8192 of them
+/// #   #[cfg(all(target_os = "linux", not(feature = "static"), not(feature = "min_level_off")))]
+///     assert!(LAST_CPU.with(|cpu| cpu.get()).unwrap() < 8192);
+///
+/// #   #[cfg(target_os = "linux")]
+///     assert!(loggery::current_cpu().is_some());
+/// #   #[cfg(not(target_os = "linux"))]
+/// #   assert!(loggery::current_cpu().is_none());
+/// }
+/// ```
+#[inline]
+pub fn current_cpu() -> Option<u32> {
+    #[cfg(target_os = "linux")]
+    {
+        // SAFETY: `sched_getcpu` has no preconditions, it returns `-1` on failure.
+        let cpu = unsafe { libc::sched_getcpu() };
+
+        if cpu >= 0 {
+            Some(cpu as u32)
+        } else {
+            None
+        }
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    {
+        None
+    }
+}
@@ -0,0 +1,328 @@
+/// A logging misconfiguration found by [`diagnose_config`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum ConfigIssue {
+    /// The `min_level_off` feature is enabled, so every log is removed at compile time and
+    /// nothing (including [`set_min_level`](crate::set_min_level)) can bring them back.
+    AllLogsDisabledAtCompileTime = 0,
+    /// [`set_min_level`](crate::set_min_level) was called with a level below the compile-time
+    /// level (`min_level_*` features), so the levels in between are still filtered.
+    RuntimeLevelBelowCompileTime = 1,
+    /// No logger is set and there's no default one (the `std` feature is disabled), so logs are
+    /// silently dropped until [`set_logger`](crate::set_logger) is called.
+    NoLogger = 2,
+}
+
+impl ConfigIssue {
+    const ALL: [ConfigIssue; 3] = [
+        ConfigIssue::AllLogsDisabledAtCompileTime,
+        ConfigIssue::RuntimeLevelBelowCompileTime,
+        ConfigIssue::NoLogger,
+    ];
+}
+
+impl core::fmt::Display for ConfigIssue {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str(match self {
+            ConfigIssue::AllLogsDisabledAtCompileTime => {
+                "the `min_level_off` feature disables all logs at compile time"
+            }
+            ConfigIssue::RuntimeLevelBelowCompileTime => {
+                "the runtime level is below the compile-time level (`min_level_*` feature) and \
+                 can't enable the levels in between"
+            }
+            ConfigIssue::NoLogger => {
+                "no logger is set, logs are dropped until `set_logger` is called"
+            }
+        })
+    }
+}
+
+/// The misconfigurations found by [`diagnose_config`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ConfigDiagnostics {
+    issues: u8,
+}
+
+impl ConfigDiagnostics {
+    /// Returns `true` if no issue was found.
+    #[inline]
+    pub fn is_ok(&self) -> bool {
+        self.issues == 0
+    }
+
+    /// Returns `true` if `issue` was found.
+    #[inline]
+    pub fn contains(&self, issue: ConfigIssue) -> bool {
+        self.issues & (1 << issue as u8) != 0
+    }
+
+    /// Returns an iterator over the found issues.
+    #[inline]
+    pub fn iter(&self) -> impl Iterator<Item = ConfigIssue> {
+        let diagnostics = *self;
+
+        ConfigIssue::ALL
+            .iter()
+            .copied()
+            .filter(move |issue| diagnostics.contains(*issue))
+    }
+
+    #[inline]
+    fn add(&mut self, issue: ConfigIssue) {
+        self.issues |= 1 << issue as u8;
+    }
+}
+
+/// Checks the current configuration for contradictions that make logs silently disappear.
+///
+/// | Issue                                          | Reported when                              |
+/// |------------------------------------------------|--------------------------------------------|
+/// | [`ConfigIssue::AllLogsDisabledAtCompileTime`]  | The `min_level_off` feature is enabled     |
+/// | [`ConfigIssue::RuntimeLevelBelowCompileTime`]  | `set_min_level` was called with a level below the compile-time level |
+/// | [`ConfigIssue::NoLogger`]                      | No `std` feature, no `static` feature and `set_logger` wasn't called |
+///
+/// # Example
+///
+/// ```
+/// use loggery::{ConfigIssue, Level};
+///
+/// # #[cfg(feature = "runtime_level")]
+/// loggery::set_min_level(Level::Trace);
+///
+/// let diagnostics = loggery::diagnose_config();
+///
+/// for issue in diagnostics.iter() {
+///     eprintln!("logging misconfiguration: {}", issue);
+/// }
+///
+/// # #[cfg(feature = "min_level_off")]
+/// assert!(diagnostics.contains(ConfigIssue::AllLogsDisabledAtCompileTime));
+/// # #[cfg(all(feature = "std", not(any(feature = "min_level_off", feature = "min_level_debug",
+/// #     feature = "min_level_info", feature = "min_level_warn", feature = "min_level_error"))))]
+/// assert!(diagnostics.is_ok());
+/// ```
+pub fn diagnose_config() -> ConfigDiagnostics {
+    let mut diagnostics = ConfigDiagnostics { issues: 0 };
+
+    match crate::COMPILE_TIME_MIN_LEVEL {
+        None => diagnostics.add(ConfigIssue::AllLogsDisabledAtCompileTime),
+        #[cfg(feature = "runtime_level")]
+        Some(compile_time) => {
+            let is_set = crate::RUNTIME_MIN_LEVEL_SET.load(core::sync::atomic::Ordering::Relaxed);
+            let runtime = crate::RUNTIME_MIN_LEVEL.load(core::sync::atomic::Ordering::Relaxed);
+
+            if is_set && runtime < compile_time {
+                diagnostics.add(ConfigIssue::RuntimeLevelBelowCompileTime);
+            }
+        }
+        #[cfg(not(feature = "runtime_level"))]
+        Some(_) => {}
+    }
+
+    #[cfg(all(not(feature = "std"), not(feature = "static")))]
+    if crate::LOGGERS[0]
+        .load(core::sync::atomic::Ordering::Acquire)
+        .is_null()
+    {
+        diagnostics.add(ConfigIssue::NoLogger);
+    }
+
+    diagnostics
+}
+
+/// Checks the configuration with [`diagnose_config`] and prints a warning to stderr for every
+/// issue found. (`std` feature)
+///
+/// The warnings are written directly to stderr, since a misconfigured logger may not show them.
+/// Meant to be called once at startup, after the logging setup.
+///
+/// Output:
+///
+/// ```text
+/// [loggery] the `min_level_off` feature disables all logs at compile time
+/// ```
+#[cfg(feature = "std")]
+pub fn warn_config_issues() -> ConfigDiagnostics {
+    extern crate std;
+
+    let diagnostics = diagnose_config();
+
+    for issue in diagnostics.iter() {
+        std::eprintln!("[loggery] {}", issue);
+    }
+
+    diagnostics
+}
+
+/// Logs a summary of the effective logging configuration at the `info` level, through the
+/// current logger.
+///
+/// Meant to be called once at startup, after the logging setup, so the logs themselves tell how
+/// logging was configured. Like any `info` log, the banner is filtered out if the effective level
+/// is above [`Level::Info`](crate::Level::Info).
+///
+/// # Format
+///
+/// The banner is a single line of `key=value` pairs:
+///
+/// ```text
+/// loggery config: compile_time_level=TRACE runtime_level=INFO effective_level=INFO logger=default extra_loggers=0
+/// ```
+///
+/// | Key                  | Value                                                          |
+/// |----------------------|----------------------------------------------------------------|
+/// | `compile_time_level` | Level set by the `min_level_*` features (`OFF` if all are off) |
+/// | `runtime_level`      | Level set with `set_min_level` (`runtime_level` feature)       |
+/// | `effective_level`    | The stricter of the two levels above                           |
+/// | `module_levels`      | Number of module levels (`module_level` feature)               |
+/// | `level_mask`         | Levels in the mask, e.g., `TRACE\|ERROR` (`level_mask` feature) |
+/// | `logger`             | `default`, `custom`, `static` or `none`                        |
+/// | `extra_loggers`      | Number of loggers added with `add_logger` (NOT `static`)       |
+/// | `extension`          | `set`, `static` or `none` (`extension` feature)                |
+/// | `timestamp`          | Timestamp format, e.g., `Rfc3339` (`timestamp` feature)        |
+///
+/// # Example
+///
+/// ```
+/// use loggery::{Level, Payload};
+/// use std::cell::RefCell;
+///
+/// thread_local! {
+///     static BANNER: RefCell<String> = RefCell::new(String::new());
+/// }
+///
+/// fn my_logger(payload: Payload) {
+///     BANNER.with(|banner| *banner.borrow_mut() = payload.args.to_string());
+/// }
+///
+/// fn main() {
+/// #   #[cfg(not(feature = "static"))]
+///     loggery::set_logger(my_logger);
+/// #   #[cfg(feature = "runtime_level")]
+///     loggery::set_min_level(Level::Info);
+///
+///     loggery::log_startup_banner();
+///
+/// #   #[cfg(all(not(feature = "static"), not(any(feature = "min_level_warn",
+/// #       feature = "min_level_error", feature = "min_level_off"))))]
+///     BANNER.with(|banner| {
+///         let banner = banner.borrow();
+///
+///         assert!(banner.starts_with("loggery config: "));
+/// #       #[cfg(feature = "runtime_level")]
+///         assert!(banner.contains(" effective_level=INFO "));
+///         assert!(banner.contains(" logger=custom "));
+///     });
+/// }
+/// ```
+pub fn log_startup_banner() {
+    crate::log!(crate::Level::Info, "loggery config: {}", ConfigSummary);
+}
+
+/// Renders the effective configuration for [`log_startup_banner`].
+struct ConfigSummary;
+
+impl core::fmt::Display for ConfigSummary {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let level_name = |level: Option<u8>| match level.and_then(crate::Level::from_u8) {
+            Some(level) => level.name(),
+            None => "OFF",
+        };
+
+        write!(
+            f,
+            "compile_time_level={}",
+            level_name(crate::COMPILE_TIME_MIN_LEVEL)
+        )?;
+
+        #[cfg(feature = "runtime_level")]
+        write!(
+            f,
+            " runtime_level={}",
+            level_name(Some(
+                crate::RUNTIME_MIN_LEVEL.load(core::sync::atomic::Ordering::Relaxed)
+            ))
+        )?;
+
+        write!(
+            f,
+            " effective_level={}",
+            level_name(crate::get_min_level().map(|level| level as u8))
+        )?;
+
+        #[cfg(feature = "module_level")]
+        write!(
+            f,
+            " module_levels={}",
+            crate::MODULE_LEVELS
+                .lock()
+                .iter()
+                .filter(|module| module.is_some())
+                .count()
+        )?;
+
+        #[cfg(feature = "level_mask")]
+        {
+            let mask = crate::get_level_mask();
+            let mut separator = "";
+
+            f.write_str(" level_mask=")?;
+
+            for raw in 0..5 {
+                if let Some(level) =
+                    crate::Level::from_u8(raw).filter(|level| mask.contains(*level))
+                {
+                    write!(f, "{}{}", separator, level.name())?;
+                    separator = "|";
+                }
+            }
+
+            if mask.is_empty() {
+                f.write_str("none")?;
+            }
+        }
+
+        #[cfg(feature = "static")]
+        f.write_str(" logger=static")?;
+
+        #[cfg(not(feature = "static"))]
+        {
+            let first = crate::LOGGERS[0].load(core::sync::atomic::Ordering::Acquire);
+
+            #[cfg(feature = "std")]
+            let logger = if first.is_null()
+                || first == crate::stdout::default_logger as crate::LoggerFn as *mut ()
+            {
+                "default"
+            } else {
+                "custom"
+            };
+
+            #[cfg(not(feature = "std"))]
+            let logger = if first.is_null() { "none" } else { "custom" };
+
+            let extra_loggers = crate::LOGGERS[1..]
+                .iter()
+                .filter(|slot| !slot.load(core::sync::atomic::Ordering::Acquire).is_null())
+                .count();
+
+            write!(f, " logger={} extra_loggers={}", logger, extra_loggers)?;
+        }
+
+        #[cfg(all(feature = "extension", feature = "static"))]
+        f.write_str(" extension=static")?;
+
+        #[cfg(all(feature = "extension", not(feature = "static")))]
+        f.write_str(if crate::get_extension().is_some() {
+            " extension=set"
+        } else {
+            " extension=none"
+        })?;
+
+        #[cfg(feature = "timestamp")]
+        write!(f, " timestamp={:?}", crate::get_timestamp_format())?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,94 @@
+use crate::{level_enabled_at_compile_time, Level, Payload};
+
+/// Passes logs to an explicit sink, without any global state.
+///
+/// The global logger ([`set_logger`](crate::set_logger), the level macros) relies on statics
+/// holding the logger and the runtime level. A `Dispatcher` owns its sink and its minimum level
+/// instead, for code that forbids global mutable state (e.g., safety-critical firmware), or to
+/// log to different sinks from different components. Log through it with [`Dispatcher::log`] or
+/// the [`log_to!`](crate::log_to), [`trace_to!`](crate::trace_to), [`debug_to!`](crate::debug_to),
+/// [`info_to!`](crate::info_to), [`warn_to!`](crate::warn_to) and
+/// [`error_to!`](crate::error_to) macros.
+///
+/// The sink is any `Fn(Payload)`, so it can be a closure borrowing its writer instead of reaching
+/// it through a `static`. It's called directly: the global loggers, extensions, runtime level,
+/// filter and shutdown mode don't apply, only the `min_level_*` features (which remove the logs
+/// at compile time) and the dispatcher's own minimum level. Use [`emit`](crate::emit) as the sink
+/// to pass the logs on to the global loggers.
+///
+/// # Tradeoff
+///
+/// Without a global, the dispatcher has to be threaded through every function that logs (as a
+/// parameter or a field), and logs from code that doesn't have it (e.g., dependencies using the
+/// level macros) still go to the global logger. The macros still fill the payload as usual: with
+/// the `timestamp` feature, the time comes from the global clock (see `set_clock`), and with the
+/// `thread_meta` feature, the thread ID from a thread-local.
+///
+/// # Example
+///
+/// ```
+/// use loggery::{info_to, trace_to, Dispatcher, Level, Payload};
+/// use std::cell::RefCell;
+///
+/// let lines = RefCell::new(Vec::new());
+///
+/// let dispatcher = Dispatcher::new(
+///     |payload: Payload| lines.borrow_mut().push(payload.args.to_string()),
+///     Level::Debug,
+/// );
+///
+/// info_to!(dispatcher, "Sensor {} ready", 3);
+/// trace_to!(dispatcher, "Filtered by the dispatcher's level");
+///
+/// # #[cfg(not(any(feature = "min_level_warn", feature = "min_level_error",
+/// #     feature = "min_level_off")))]
+/// assert_eq!(*lines.borrow(), ["Sensor 3 ready"]);
+/// ```
+pub struct Dispatcher<F> {
+    sink: F,
+    min_level: Level,
+}
+
+impl<F> Dispatcher<F> {
+    /// Creates a dispatcher passing the logs at `min_level` and above to `sink`.
+    #[inline]
+    pub const fn new(sink: F, min_level: Level) -> Self {
+        Self { sink, min_level }
+    }
+
+    /// Returns the minimum level of the logs passed to the sink.
+    #[inline]
+    pub fn min_level(&self) -> Level {
+        self.min_level
+    }
+
+    /// Sets the minimum level of the logs passed to the sink.
+    ///
+    /// This can't enable levels removed at compile time by the `min_level_*` features.
+    #[inline]
+    pub fn set_min_level(&mut self, min_level: Level) {
+        self.min_level = min_level;
+    }
+
+    /// Returns `true` if logs at `level` are passed to the sink.
+    #[inline(always)]
+    pub fn is_enabled(&self, level: Level) -> bool {
+        level_enabled_at_compile_time(level) && level >= self.min_level
+    }
+}
+
+impl<F: Fn(Payload)> Dispatcher<F> {
+    /// Passes a log to the sink if its level is enabled, see [`Dispatcher::is_enabled`].
+    ///
+    /// Returns `true` if the log was passed to the sink, `false` if it was filtered out.
+    #[inline(always)]
+    pub fn log(&self, payload: Payload) -> bool {
+        if !self.is_enabled(payload.level) {
+            return false;
+        }
+
+        (self.sink)(payload);
+
+        true
+    }
+}
@@ -0,0 +1,500 @@
+//! Logging to a [`core::fmt::Write`] writer (e.g., a UART) without `std` or allocation.
+//!
+//! On targets without stdout, the common case is writing `[LEVEL] message` lines to a serial
+//! port implementing `core::fmt::Write`. Wrap the writer in a lock implementing [`WriterLock`]
+//! (usually a critical section), put it in a `static` and install a logger writing to it with
+//! [`writer_logger!`](crate::writer_logger):
+//!
+//! ```
+//! use core::fmt::{self, Write};
+//! use loggery::{embedded::WriterLock, info};
+//! # use std::cell::RefCell;
+//! # thread_local! {
+//! #     static SENT: RefCell<String> = RefCell::new(String::new());
+//! # }
+//!
+//! struct Uart;
+//!
+//! impl Write for Uart {
+//!     fn write_str(&mut self, s: &str) -> fmt::Result {
+//!         // Write the bytes to the UART data register
+//! #       SENT.with(|sent| sent.borrow_mut().push_str(s));
+//!         Ok(())
+//!     }
+//! }
+//!
+//! struct UartLock;
+//!
+//! impl WriterLock for UartLock {
+//!     type Writer = Uart;
+//!
+//!     fn lock_with<R>(&self, f: impl FnOnce(&mut Uart) -> R) -> R {
+//!         // E.g., `critical_section::with(|_| f(&mut Uart))`
+//!         f(&mut Uart)
+//!     }
+//! }
+//!
+//! static UART: UartLock = UartLock;
+//!
+//! fn main() {
+//! #   #[cfg(not(feature = "static"))]
+//!     loggery::set_logger(loggery::writer_logger!(UART));
+//!
+//!     info!("Booted in {} ms", 42);
+//!
+//! #   #[cfg(all(not(feature = "static"), not(feature = "min_level_off"),
+//! #       not(feature = "timestamp")))]
+//!     SENT.with(|sent| assert_eq!(*sent.borrow(), "[ INFO] Booted in 42 ms\n"));
+//! }
+//! ```
+//!
+//! With the `static` feature, call [`write_log`] from `__loggery_log_impl` instead. To send logs
+//! with DMA, [`render_into`] renders them into a byte buffer, or [`fmt_into`] if the bytes must
+//! stay valid UTF-8. To keep the last logs in RAM and only dump them after a crash, write them to
+//! a [`RingBufferLogger`].
+
+#[cfg(feature = "std")]
+extern crate std;
+
+use crate::Payload;
+
+/// A lock giving exclusive access to a writer, so logs from different threads or interrupts
+/// aren't interleaved.
+///
+/// Implement it for the mutex of your platform (e.g., a critical section). With the `std`
+/// feature, it's implemented for [`std::sync::Mutex`].
+pub trait WriterLock {
+    /// The writer logs are written to.
+    type Writer: core::fmt::Write;
+
+    /// Calls `f` with exclusive access to the writer.
+    fn lock_with<R>(&self, f: impl FnOnce(&mut Self::Writer) -> R) -> R;
+}
+
+#[cfg(feature = "std")]
+impl<W: core::fmt::Write> WriterLock for std::sync::Mutex<W> {
+    type Writer = W;
+
+    fn lock_with<R>(&self, f: impl FnOnce(&mut W) -> R) -> R {
+        f(&mut self.lock().unwrap_or_else(|e| e.into_inner()))
+    }
+}
+
+/// Writes a log entry to the writer of `lock`, in the format: `[LEVEL] message` followed by a
+/// newline, with the labels of the active [`scope!`](crate::scope)s after the level:
+/// `[LEVEL] [scope] message`.
+///
+/// With the `timestamp` feature, the time is prepended in the format set with
+/// `set_timestamp_format`: `timestamp [LEVEL] message`. With the `kv` feature, the fields are
+/// appended as ` key=value`.
+///
+/// The entry is written while holding the lock, without allocating. Errors of the writer are
+/// returned, a logger usually ignores them.
+pub fn write_log<L: WriterLock>(lock: &L, payload: &Payload) -> core::fmt::Result {
+    lock.lock_with(|writer| write_entry(writer, payload))
+}
+
+/// The error returned by [`render_into`] when the log entry doesn't fit in the buffer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Overflow;
+
+impl core::fmt::Display for Overflow {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str("the log entry doesn't fit in the buffer")
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for Overflow {}
+
+/// Renders a log entry into `buf`, in the format of [`write_log`], returning the number of bytes
+/// written.
+///
+/// Meant for the most constrained targets, e.g., to fill a DMA buffer for a UART: nothing is
+/// allocated and the writer is a concrete type, not a trait object.
+///
+/// # Overflow
+///
+/// On success, `buf[..len]` holds the whole entry, trailing newline included, and the rest of `buf`
+/// is left untouched. If the entry doesn't fit, the buffer is filled with its beginning (possibly
+/// cutting a UTF-8 character, and without the trailing newline) and [`Overflow`] is returned. Send
+/// the partial entry (all of `buf`) or drop it, whichever suits the target. An error returned by a
+/// `Display` implementation of the message is reported as an [`Overflow`] too.
+///
+/// # Example
+///
+/// ```
+/// use loggery::{embedded::{render_into, Overflow}, Level, Payload};
+///
+/// fn dma_logger(payload: Payload) {
+///     let mut buf = [0; 64];
+///
+///     match render_into(&payload, &mut buf) {
+///         Ok(len) => { /* Send `buf[..len]` */ }
+///         Err(Overflow) => { /* Send `buf` (truncated) or drop it */ }
+///     }
+/// }
+///
+/// # let payload = |level| {
+/// #     Payload::__new(
+/// #         level,
+/// #         format_args!("Booted"),
+/// #         #[cfg(feature = "metadata")]
+/// #         loggery::Metadata::__new(module_path!(), file!(), line!()),
+/// #     )
+/// # };
+/// // A `Payload` as created by the macros, e.g., `info!("Booted")`
+/// let payload = payload(Level::Info);
+///
+/// # #[cfg(all(not(feature = "timestamp"), not(feature = "kv")))]
+/// # {
+/// // Exact fit
+/// let mut buf = [0; 15];
+/// assert_eq!(render_into(&payload, &mut buf), Ok(15));
+/// assert_eq!(&buf, b"[ INFO] Booted\n");
+///
+/// // Overflow
+/// let mut buf = [0; 10];
+/// assert_eq!(render_into(&payload, &mut buf), Err(Overflow));
+/// assert_eq!(&buf, b"[ INFO] Bo");
+/// # }
+/// ```
+pub fn render_into(payload: &Payload, buf: &mut [u8]) -> Result<usize, Overflow> {
+    let mut writer = SliceWriter {
+        buf,
+        len: 0,
+        whole_chars: false,
+    };
+
+    match write_entry(&mut writer, payload) {
+        Ok(()) => Ok(writer.len),
+        Err(_) => Err(Overflow),
+    }
+}
+
+/// The error returned by [`fmt_into`] when the log entry doesn't fit in the buffer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BufferFull {
+    /// The number of bytes written before the buffer was full, always on a character boundary.
+    pub len: usize,
+}
+
+impl core::fmt::Display for BufferFull {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "the buffer is full after {} bytes of the log entry",
+            self.len
+        )
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for BufferFull {}
+
+/// Formats a log entry into `buf`, in the format of [`write_log`], returning the number of bytes
+/// written.
+///
+/// Like [`render_into`], nothing is allocated, but the written bytes are always valid UTF-8, so
+/// they can be used as a `&str` (e.g., by formatting helpers) with `core::str::from_utf8`.
+///
+/// # Overflow
+///
+/// On success, `buf[..len]` holds the whole entry, trailing newline included. If the entry doesn't
+/// fit, the entry is cut at the last character that fits whole, [`BufferFull`] is returned with
+/// the length of the written part, and:
+///
+/// - `buf[..len]` holds the beginning of the entry, without the trailing newline. A character
+///   that would be split isn't written, so `len` can be up to 3 bytes less than `buf.len()`.
+/// - Nothing after the cut is written, even if a later part of the entry would fit.
+///
+/// In both cases, the bytes of `buf` after `len` are left untouched. An error returned by a
+/// `Display` implementation of the message is reported as a [`BufferFull`] too.
+///
+/// # Example
+///
+/// ```
+/// use loggery::{embedded::{fmt_into, BufferFull}, Level, Payload};
+///
+/// # let payload = |level| {
+/// #     Payload::__new(
+/// #         level,
+/// #         format_args!("Temp: 21 °C"),
+/// #         #[cfg(feature = "metadata")]
+/// #         loggery::Metadata::__new(module_path!(), file!(), line!()),
+/// #     )
+/// # };
+/// // A `Payload` as created by the macros, e.g., `info!("Temp: 21 °C")`
+/// let payload = payload(Level::Info);
+///
+/// # #[cfg(all(not(feature = "timestamp"), not(feature = "kv")))]
+/// # {
+/// // Exact fit ("°" is 2 bytes long)
+/// let mut buf = [0; 21];
+/// assert_eq!(fmt_into(&mut buf, &payload), Ok(21));
+/// assert_eq!(&buf, "[ INFO] Temp: 21 °C\n".as_bytes());
+///
+/// // Overflow: the 18th byte would split "°", so only 17 bytes are written
+/// let mut buf = [0; 18];
+/// assert_eq!(fmt_into(&mut buf, &payload), Err(BufferFull { len: 17 }));
+/// assert_eq!(core::str::from_utf8(&buf[..17]), Ok("[ INFO] Temp: 21 "));
+/// # }
+/// ```
+pub fn fmt_into(buf: &mut [u8], payload: &Payload) -> Result<usize, BufferFull> {
+    let mut writer = SliceWriter {
+        buf,
+        len: 0,
+        whole_chars: true,
+    };
+
+    match write_entry(&mut writer, payload) {
+        Ok(()) => Ok(writer.len),
+        Err(_) => Err(BufferFull { len: writer.len }),
+    }
+}
+
+/// Writes a log entry in the format of [`write_log`].
+fn write_entry<W: core::fmt::Write>(writer: &mut W, payload: &Payload) -> core::fmt::Result {
+    #[cfg(feature = "timestamp")]
+    write!(
+        writer,
+        "{} ",
+        crate::get_timestamp_format().display(payload.timestamp)
+    )?;
+
+    write!(
+        writer,
+        "[{}]{} {}",
+        payload.level.as_str(),
+        crate::scope::Scopes,
+        payload.args
+    )?;
+
+    #[cfg(feature = "kv")]
+    write!(writer, "{}", crate::fields::KeyValues(payload.fields))?;
+
+    writer.write_char('\n')
+}
+
+/// Writes into a byte slice, filling it and failing once it's full.
+struct SliceWriter<'a> {
+    buf: &'a mut [u8],
+    len: usize,
+    /// Whether to stop at the last character that fits whole instead of filling the slice.
+    whole_chars: bool,
+}
+
+impl core::fmt::Write for SliceWriter<'_> {
+    fn write_str(&mut self, s: &str) -> core::fmt::Result {
+        let available = self.buf.len() - self.len;
+        let mut n = s.len().min(available);
+
+        while self.whole_chars && !s.is_char_boundary(n) {
+            n -= 1;
+        }
+
+        self.buf[self.len..self.len + n].copy_from_slice(&s.as_bytes()[..n]);
+        self.len += n;
+
+        if n < s.len() {
+            return Err(core::fmt::Error);
+        }
+
+        Ok(())
+    }
+}
+
+/// The maximum length of a line kept by a [`RingBufferLogger`], in bytes.
+pub const RING_LINE_LEN: usize = 128;
+
+/// Keeps the last `N` log lines in a fixed RAM buffer, to dump them after a crash.
+///
+/// It's a [`core::fmt::Write`] writer storing lines instead of sending them: wrap it in a
+/// [`WriterLock`] in a `static` and install it with [`writer_logger!`](crate::writer_logger) like
+/// any other writer, then [`dump`](RingBufferLogger::dump) the lines from the panic handler (or
+/// the hard fault handler, or when a watchdog fires). Nothing is allocated: the buffer takes about
+/// `(N + 1) * RING_LINE_LEN` bytes (the stored lines and the line being written), wherever the
+/// logger is stored.
+///
+/// # Capacity
+///
+/// - Once `N` lines are stored, every new line overwrites the oldest one.
+/// - A line is stored when its newline is written, a line being written isn't dumped.
+/// - Lines longer than [`RING_LINE_LEN`] bytes are truncated, on a character boundary, and dumped
+///   with a trailing `...`.
+///
+/// # Example
+///
+/// ```
+/// use loggery::{embedded::{RingBufferLogger, WriterLock}, error, info};
+/// use std::cell::RefCell;
+///
+/// thread_local! {
+///     static RING: RefCell<RingBufferLogger<2>> = RefCell::new(RingBufferLogger::new());
+/// }
+///
+/// struct RingLock;
+///
+/// impl WriterLock for RingLock {
+///     type Writer = RingBufferLogger<2>;
+///
+///     fn lock_with<R>(&self, f: impl FnOnce(&mut RingBufferLogger<2>) -> R) -> R {
+///         // E.g., `critical_section::with(|cs| f(&mut RING.borrow_ref_mut(cs)))`
+///         RING.with(|ring| f(&mut ring.borrow_mut()))
+///     }
+/// }
+///
+/// static RING_LOCK: RingLock = RingLock;
+///
+/// fn main() {
+/// #   #[cfg(not(feature = "static"))]
+///     loggery::set_logger(loggery::writer_logger!(RING_LOCK));
+///
+///     info!("Booted");
+///     info!("Motor started");
+///     error!("Overcurrent: {} mA", 2500);
+///
+///     // E.g., in the panic handler, to a UART
+///     let mut dump = String::new();
+///     RING_LOCK.lock_with(|ring| ring.dump(&mut dump)).unwrap();
+///
+/// #   #[cfg(all(not(feature = "static"), not(feature = "min_level_off"),
+/// #       not(feature = "timestamp"), not(feature = "kv")))]
+///     assert_eq!(dump, "[ INFO] Motor started\n[ERROR] Overcurrent: 2500 mA\n");
+/// }
+/// ```
+pub struct RingBufferLogger<const N: usize> {
+    lines: [Line; N],
+    /// The line being written, stored once its newline is written.
+    current: Line,
+    /// The slot of the next stored line, the oldest line once the buffer is full.
+    next: usize,
+    /// The number of stored lines.
+    count: usize,
+}
+
+/// A line of a [`RingBufferLogger`].
+#[derive(Clone, Copy)]
+struct Line {
+    bytes: [u8; RING_LINE_LEN],
+    len: usize,
+    truncated: bool,
+}
+
+impl Line {
+    const EMPTY: Line = Line {
+        bytes: [0; RING_LINE_LEN],
+        len: 0,
+        truncated: false,
+    };
+
+    fn as_str(&self) -> &str {
+        // Only whole characters are copied in `RingBufferLogger::write_str`.
+        core::str::from_utf8(&self.bytes[..self.len]).unwrap_or("")
+    }
+}
+
+impl<const N: usize> RingBufferLogger<N> {
+    /// Creates an empty buffer.
+    pub const fn new() -> Self {
+        Self {
+            lines: [Line::EMPTY; N],
+            current: Line::EMPTY,
+            next: 0,
+            count: 0,
+        }
+    }
+
+    /// Returns the number of stored lines, at most `N`.
+    pub fn len(&self) -> usize {
+        self.count
+    }
+
+    /// Returns `true` if no line is stored.
+    pub fn is_empty(&self) -> bool {
+        self.count == 0
+    }
+
+    /// Removes every line, including the line being written.
+    pub fn clear(&mut self) {
+        self.current = Line::EMPTY;
+        self.next = 0;
+        self.count = 0;
+    }
+
+    /// Returns the stored lines (without their newline), oldest first.
+    pub fn lines(&self) -> impl Iterator<Item = &str> + '_ {
+        self.stored().map(Line::as_str)
+    }
+
+    /// Writes the stored lines to `out`, oldest first, each followed by a newline (truncated lines
+    /// end with `...`).
+    pub fn dump(&self, out: &mut impl core::fmt::Write) -> core::fmt::Result {
+        for line in self.stored() {
+            out.write_str(line.as_str())?;
+
+            if line.truncated {
+                out.write_str("...")?;
+            }
+
+            out.write_char('\n')?;
+        }
+
+        Ok(())
+    }
+
+    /// Returns the stored lines, oldest first.
+    fn stored(&self) -> impl Iterator<Item = &Line> + '_ {
+        let first = if self.count < N { 0 } else { self.next };
+
+        (0..self.count).map(move |i| &self.lines[(first + i) % N])
+    }
+}
+
+impl<const N: usize> Default for RingBufferLogger<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const N: usize> core::fmt::Write for RingBufferLogger<N> {
+    fn write_str(&mut self, s: &str) -> core::fmt::Result {
+        for c in s.chars() {
+            if c == '\n' {
+                if N > 0 {
+                    self.lines[self.next] = self.current;
+                    self.next = (self.next + 1) % N;
+                    self.count = (self.count + 1).min(N);
+                }
+
+                self.current = Line::EMPTY;
+
+                continue;
+            }
+
+            let line = &mut self.current;
+            let end = line.len + c.len_utf8();
+
+            if end <= RING_LINE_LEN {
+                c.encode_utf8(&mut line.bytes[line.len..end]);
+                line.len = end;
+            } else {
+                line.truncated = true;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Creates a [`LoggerFn`](crate::LoggerFn) writing every log to the writer of a `static`
+/// [`WriterLock`], e.g., `loggery::set_logger(loggery::writer_logger!(UART))`. See the
+/// [`embedded`](crate::embedded) module for a complete example.
+#[macro_export]
+macro_rules! writer_logger {
+    ($lock:path) => {
+        |payload: $crate::Payload| {
+            let _ = $crate::embedded::write_log(&$lock, &payload);
+        }
+    };
+}
@@ -0,0 +1,85 @@
+extern crate std;
+
+use crate::Level;
+
+use std::time::Instant;
+
+/// Total emission time (in nanoseconds) and number of emitted logs per level storage, indexed by
+/// level.
+static EMISSION_STATS: crate::spin::Mutex<[(u64, u64); 5]> = crate::spin::Mutex::new([(0, 0); 5]);
+
+/// Returns how long the extensions and the loggers took to handle the emitted logs of each level,
+/// as `(total_ns, count)` pairs indexed by `level as usize`, from [`Level::Trace`] to
+/// [`Level::Error`]. (`emission_stats` feature)
+///
+/// Each log passing the filters is timed from right before the first extension is called until the
+/// last logger returns, with the monotonic clock of [`Instant`], so the totals aren't affected by
+/// changes of the system time. Filtered and dropped logs aren't counted, and neither is the
+/// rendering done by the macros before (the arguments are only rendered by the sinks, which are
+/// timed). Divide the total by the count for the mean latency of a level.
+///
+/// # Overhead
+///
+/// Every emitted log reads the clock twice (tens of nanoseconds on common platforms) and takes a
+/// lock to add to the totals, which contends when many threads log at once. Enable the feature for
+/// tuning the sinks, not in production builds that don't read the stats.
+///
+/// # Example
+///
+/// ```
+/// use loggery::{error, info, Level, Payload};
+///
+/// fn my_logger(_: Payload) {
+///     std::thread::sleep(std::time::Duration::from_millis(1));
+/// }
+///
+/// fn main() {
+/// #   #[cfg(not(feature = "static"))]
+///     loggery::set_logger(my_logger);
+///
+///     for _ in 0..3 {
+///         info!("Polling");
+///     }
+///
+///     error!("Timed out");
+///
+///     let stats = loggery::emission_stats();
+///     let (total_ns, count) = stats[Level::Info as usize];
+///
+/// #   #[cfg(all(not(feature = "static"), not(any(feature = "min_level_warn",
+/// #       feature = "min_level_error", feature = "min_level_off"))))]
+/// #   {
+///     assert_eq!(count, 3);
+///     assert!(total_ns >= 3_000_000);
+///     assert_eq!(stats[Level::Error as usize].1, 1);
+///     assert_eq!(stats[Level::Debug as usize], (0, 0));
+/// #   }
+/// }
+/// ```
+#[inline]
+pub fn emission_stats() -> [(u64, u64); 5] {
+    *EMISSION_STATS.lock()
+}
+
+/// Resets the totals and counts of [`emission_stats`] to zero. (`emission_stats` feature)
+#[inline]
+pub fn reset_emission_stats() {
+    *EMISSION_STATS.lock() = [(0, 0); 5];
+}
+
+/// Returns the start of the emission of a log.
+#[inline(always)]
+pub(crate) fn start() -> Instant {
+    Instant::now()
+}
+
+/// Adds the emission of a log at `level`, started at `started`, to the stats.
+#[inline(always)]
+pub(crate) fn record(level: Level, started: Instant) {
+    let elapsed = started.elapsed().as_nanos() as u64;
+
+    if let Some((total_ns, count)) = EMISSION_STATS.lock().get_mut(level as usize) {
+        *total_ns = total_ns.saturating_add(elapsed);
+        *count += 1;
+    }
+}
@@ -0,0 +1,407 @@
+extern crate std;
+
+use crate::Payload;
+
+use std::{
+    boxed::Box,
+    collections::VecDeque,
+    io::Write,
+    string::String,
+    sync::{Arc, Condvar, Mutex, MutexGuard},
+    thread::{self, JoinHandle},
+    time::{Duration, Instant},
+};
+
+/// Number of logs the queue holds by default.
+const DEFAULT_CAPACITY: usize = 1024;
+
+/// What [`AsyncLogger::log`] does when the queue is full (`std` feature).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OverflowPolicy {
+    /// Waits until the worker makes room. No log is lost, but the logging thread is slowed down to
+    /// the pace of the writer.
+    Block,
+    /// Drops the new log, keeping the queued ones.
+    DropNewest,
+    /// Drops the oldest queued log to make room for the new one, keeping the most recent logs.
+    DropOldest,
+}
+
+/// Writes logs from a background thread, so the logging thread returns without waiting for the
+/// write (`std` feature).
+///
+/// [`log`](AsyncLogger::log) renders the log on the calling thread (the payload borrows its
+/// arguments, it can't be sent as is) and pushes the line to a bounded queue. A worker thread,
+/// started with the logger, drains the queue and writes the lines to the writer, flushing it after
+/// each batch.
+///
+/// # Full queue
+///
+/// The queue holds up to `capacity` lines (1024 with [`new`](AsyncLogger::new)). When the writer
+/// can't keep up and the queue is full, the [`OverflowPolicy`] decides between blocking the
+/// logging thread ([`Block`](OverflowPolicy::Block), the default) and dropping a log
+/// ([`DropNewest`](OverflowPolicy::DropNewest) or [`DropOldest`](OverflowPolicy::DropOldest)).
+/// Dropped logs are counted as `DropReason::FullChannel` by `drop_breakdown` (`metrics` feature).
+///
+/// # Shutdown
+///
+/// [`flush`](AsyncLogger::flush) blocks until every queued line is written. Dropping the logger
+/// writes the remaining lines and stops the worker, waiting as long as the writer needs: use
+/// [`shutdown_timeout`](AsyncLogger::shutdown_timeout) first to bound that wait, e.g., when the
+/// writer may be stuck on a network. A logger that's never dropped (e.g., in a `static`, or a
+/// thread-local of the main thread) must be flushed before the program exits, e.g., from the
+/// function registered with `set_flush`, or the queued lines are lost.
+///
+/// # Format
+///
+/// Lines are written in the format of the default logger, or with the formatter set with
+/// [`set_global_formatter`](crate::set_global_formatter).
+///
+/// # Example
+///
+/// ```
+/// use loggery::extensions::{AsyncLogger, OverflowPolicy};
+/// use loggery::{info, Payload};
+///
+/// thread_local! {
+///     static LOGGER: AsyncLogger = AsyncLogger::with_policy(
+///         std::fs::File::create(path()).unwrap(),
+///         64,
+///         OverflowPolicy::Block,
+///     );
+/// }
+///
+/// fn path() -> String {
+///     let path = std::env::temp_dir().join("loggery_async_logger_doctest.log");
+///     path.to_string_lossy().into_owned()
+/// }
+///
+/// fn my_logger(payload: Payload) {
+///     LOGGER.with(|logger| logger.log(&payload));
+/// }
+///
+/// fn main() {
+/// #   #[cfg(not(feature = "static"))]
+///     loggery::set_logger(my_logger);
+///
+///     for i in 0..100 {
+///         info!("Request {} served", i);
+///     }
+///
+///     // Wait for the worker to write everything
+///     LOGGER.with(|logger| logger.flush());
+///
+/// #   #[cfg(all(not(feature = "static"), not(any(feature = "min_level_warn",
+/// #       feature = "min_level_error", feature = "min_level_off"))))]
+/// #   {
+///     let contents = std::fs::read_to_string(path()).unwrap();
+///
+///     assert_eq!(contents.lines().count(), 100);
+///     assert!(contents.ends_with("Request 99 served\n"));
+/// #   }
+/// }
+/// ```
+pub struct AsyncLogger {
+    shared: Arc<Shared>,
+    worker: Option<JoinHandle<()>>,
+}
+
+/// State shared with the worker thread.
+struct Shared {
+    state: Mutex<State>,
+    /// Signaled when lines are queued, or on shutdown.
+    queued: Condvar,
+    /// Signaled when the worker has written a batch.
+    written: Condvar,
+    capacity: usize,
+    policy: OverflowPolicy,
+}
+
+/// The queue and the progress of the worker.
+struct State {
+    queue: VecDeque<String>,
+    /// Whether the worker is writing a batch taken from the queue.
+    writing: bool,
+    /// Number of lines of the batch being written that aren't written yet.
+    unwritten: usize,
+    shutdown: bool,
+    /// Set when [`AsyncLogger::shutdown_timeout`] gave up, the worker stops writing.
+    abandoned: bool,
+}
+
+impl AsyncLogger {
+    /// Creates a logger writing to `writer` from a new worker thread, with a queue of 1024 lines
+    /// and the [`OverflowPolicy::Block`] policy.
+    pub fn new(writer: impl Write + Send + 'static) -> Self {
+        Self::with_policy(writer, DEFAULT_CAPACITY, OverflowPolicy::Block)
+    }
+
+    /// Creates a logger writing to `writer` from a new worker thread, with a queue of `capacity`
+    /// lines (at least 1) and the given `policy` when it's full.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the worker thread can't be spawned.
+    pub fn with_policy(
+        writer: impl Write + Send + 'static,
+        capacity: usize,
+        policy: OverflowPolicy,
+    ) -> Self {
+        let shared = Arc::new(Shared {
+            state: Mutex::new(State {
+                queue: VecDeque::new(),
+                writing: false,
+                unwritten: 0,
+                shutdown: false,
+                abandoned: false,
+            }),
+            queued: Condvar::new(),
+            written: Condvar::new(),
+            capacity: capacity.max(1),
+            policy,
+        });
+
+        let worker = {
+            let shared = Arc::clone(&shared);
+            let writer: Box<dyn Write + Send> = Box::new(writer);
+
+            thread::Builder::new()
+                .name("loggery-async".into())
+                .spawn(move || shared.work(writer))
+                .expect("failed to spawn the logging thread")
+        };
+
+        Self {
+            shared,
+            worker: Some(worker),
+        }
+    }
+
+    /// Formats a log entry and queues it for the worker, see [`OverflowPolicy`] for a full queue.
+    pub fn log(&self, payload: &Payload) {
+        use core::fmt::Write as _;
+
+        let mut line = String::new();
+        let _ = writeln!(line, "{}", crate::format::Line::timestamped(payload));
+
+        let shared = &*self.shared;
+        let mut state = shared.lock();
+
+        if state.shutdown {
+            // The worker is gone, or about to be
+            crate::metrics::count_drop(crate::metrics::DropReason::Shutdown);
+
+            return;
+        }
+
+        while state.queue.len() >= shared.capacity {
+            match shared.policy {
+                OverflowPolicy::Block => {
+                    state = shared
+                        .written
+                        .wait(state)
+                        .unwrap_or_else(|e| e.into_inner());
+                }
+                OverflowPolicy::DropNewest => {
+                    crate::metrics::count_drop(crate::metrics::DropReason::FullChannel);
+
+                    return;
+                }
+                OverflowPolicy::DropOldest => {
+                    crate::metrics::count_drop(crate::metrics::DropReason::FullChannel);
+
+                    state.queue.pop_front();
+                }
+            }
+        }
+
+        state.queue.push_back(line);
+        drop(state);
+
+        shared.queued.notify_one();
+    }
+
+    /// Blocks until every queued line is written and the writer is flushed.
+    ///
+    /// Returns right away once [`shutdown_timeout`](AsyncLogger::shutdown_timeout) gave up.
+    pub fn flush(&self) {
+        let shared = &*self.shared;
+        let mut state = shared.lock();
+
+        while (!state.queue.is_empty() || state.writing) && !state.abandoned {
+            state = shared
+                .written
+                .wait(state)
+                .unwrap_or_else(|e| e.into_inner());
+        }
+    }
+
+    /// Stops the worker, waiting at most `timeout` for it to write the queued lines, and returns
+    /// the number of lines that weren't written in time.
+    ///
+    /// If the worker writes everything before the deadline, the writer is flushed, the worker is
+    /// joined and `0` is returned.
+    ///
+    /// # Timeout
+    ///
+    /// Otherwise, the lines left are dropped (counted as `DropReason::Shutdown` by
+    /// `drop_breakdown`, `metrics` feature) and the worker is detached instead of joined, so a
+    /// writer stuck forever doesn't hang the program:
+    ///
+    /// - The line being written when the deadline passes is counted as dropped, but the writer
+    ///   may still finish writing it. The worker writes nothing after it, and exits as soon as the
+    ///   writer returns.
+    /// - The writer isn't flushed, and is dropped by the worker when (if ever) it exits.
+    ///
+    /// Either way, the logger drops the logs passed to [`log`](AsyncLogger::log) afterwards (also
+    /// counted as `DropReason::Shutdown`), and dropping it doesn't wait for the worker anymore.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use loggery::extensions::AsyncLogger;
+    /// use loggery::{Level, Payload};
+    /// use std::{io, thread, time::Duration};
+    ///
+    /// // A writer stuck on a slow network
+    /// struct SlowWriter;
+    ///
+    /// impl io::Write for SlowWriter {
+    ///     fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+    ///         thread::sleep(Duration::from_millis(500));
+    ///         Ok(buf.len())
+    ///     }
+    ///
+    ///     fn flush(&mut self) -> io::Result<()> {
+    ///         Ok(())
+    ///     }
+    /// }
+    ///
+    /// let mut logger = AsyncLogger::new(SlowWriter);
+    ///
+    /// # let payload = |message| {
+    /// #     Payload::__new(
+    /// #         Level::Info,
+    /// #         message,
+    /// #         #[cfg(feature = "metadata")]
+    /// #         loggery::Metadata::__new(module_path!(), file!(), line!()),
+    /// #     )
+    /// # };
+    /// for _ in 0..5 {
+    ///     // A `Payload` as created by the macros, e.g., `info!("Request served")`
+    ///     logger.log(&payload(format_args!("Request served")));
+    /// }
+    ///
+    /// // The first line takes 500 ms to write, none is written within 50 ms
+    /// assert_eq!(logger.shutdown_timeout(Duration::from_millis(50)), 5);
+    /// ```
+    pub fn shutdown_timeout(&mut self, timeout: Duration) -> usize {
+        let deadline = Instant::now() + timeout;
+        let shared = &*self.shared;
+        let mut state = shared.lock();
+
+        state.shutdown = true;
+        shared.queued.notify_one();
+
+        while !state.queue.is_empty() || state.writing {
+            let now = Instant::now();
+
+            if now >= deadline {
+                break;
+            }
+
+            state = shared
+                .written
+                .wait_timeout(state, deadline - now)
+                .map_or_else(|e| e.into_inner().0, |(state, _)| state);
+        }
+
+        let dropped = state.queue.len() + state.unwritten;
+        let done = !state.writing;
+
+        state.queue.clear();
+        state.abandoned = true;
+        drop(state);
+
+        // The worker may be waiting for lines to write, it exits now that the queue is empty
+        shared.queued.notify_one();
+
+        for _ in 0..dropped {
+            crate::metrics::count_drop(crate::metrics::DropReason::Shutdown);
+        }
+
+        match self.worker.take() {
+            Some(worker) if done => {
+                let _ = worker.join();
+            }
+            // Detached
+            _ => {}
+        }
+
+        dropped
+    }
+}
+
+impl Drop for AsyncLogger {
+    fn drop(&mut self) {
+        self.shared.lock().shutdown = true;
+        self.shared.queued.notify_one();
+
+        if let Some(worker) = self.worker.take() {
+            let _ = worker.join();
+        }
+    }
+}
+
+impl Shared {
+    /// Writes the queued lines until shutdown, then writes the remaining ones.
+    fn work(&self, mut writer: Box<dyn Write + Send>) {
+        let mut state = self.lock();
+
+        loop {
+            while state.queue.is_empty() && !state.shutdown {
+                state = self.queued.wait(state).unwrap_or_else(|e| e.into_inner());
+            }
+
+            if state.queue.is_empty() {
+                // Shut down, and everything is written
+                return;
+            }
+
+            let batch = core::mem::take(&mut state.queue);
+            state.writing = true;
+            state.unwritten = batch.len();
+            drop(state);
+
+            // Room was made, wake up the blocked loggers
+            self.written.notify_all();
+
+            for line in &batch {
+                let _ = writer.write_all(line.as_bytes());
+
+                let mut state = self.lock();
+
+                if state.abandoned {
+                    return;
+                }
+
+                state.unwritten -= 1;
+            }
+
+            let _ = writer.flush();
+
+            state = self.lock();
+            state.writing = false;
+
+            if state.abandoned {
+                return;
+            }
+
+            self.written.notify_all();
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
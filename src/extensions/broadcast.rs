@@ -0,0 +1,144 @@
+extern crate std;
+
+use crate::Payload;
+
+use std::{
+    string::String,
+    sync::{
+        mpsc::{self, Receiver, SyncSender, TrySendError},
+        Mutex,
+    },
+    vec::Vec,
+};
+
+/// Number of logs a subscriber can fall behind by default before new logs are dropped for it.
+const DEFAULT_CAPACITY: usize = 256;
+
+/// Sends every log to all current subscribers, e.g., for a live log viewer embedded in the
+/// application (`std` feature).
+///
+/// Every subscriber gets its own bounded channel, so a slow subscriber never blocks logging or the
+/// other subscribers.
+///
+/// # Example
+///
+/// ```
+/// use loggery::{extensions::BroadcastSink, info, Payload};
+///
+/// thread_local! {
+///     static SINK: BroadcastSink = BroadcastSink::new();
+/// }
+///
+/// fn my_extension(payload: &Payload) {
+///     SINK.with(|sink| sink.log(payload));
+/// }
+///
+/// fn main() {
+///     let viewer = SINK.with(|sink| sink.subscribe());
+///     let recorder = SINK.with(|sink| sink.subscribe());
+///
+/// #   #[cfg(not(feature = "static"))]
+///     loggery::set_extension(my_extension);
+///
+///     info!("Hello, subscribers!");
+///
+/// #   #[cfg(all(not(feature = "static"), not(feature = "min_level_off"),
+/// #       not(feature = "thread_meta"), not(feature = "cpu_meta")))]
+/// #   {
+///     assert_eq!(viewer.try_recv().unwrap(), "[ INFO] Hello, subscribers!");
+///     assert_eq!(recorder.try_recv().unwrap(), "[ INFO] Hello, subscribers!");
+/// #   }
+///
+///     // Unsubscribing is dropping the receiver
+///     drop(recorder);
+///
+///     info!("Only for the viewer");
+///
+/// #   #[cfg(all(not(feature = "static"), not(feature = "min_level_off")))]
+///     assert_eq!(SINK.with(|sink| sink.subscriber_count()), 1);
+/// }
+/// ```
+///
+/// # Format
+///
+/// Logs are sent in the format: `[LEVEL] message` (without a trailing newline), or with the
+/// formatter set with [`set_global_formatter`](crate::set_global_formatter).
+///
+/// # Subscribers
+///
+/// [`subscribe`](BroadcastSink::subscribe) returns the receiving end of a new channel, which only
+/// gets the logs sent after subscribing. To unsubscribe, drop the receiver: it's removed from the
+/// sink on the next log.
+///
+/// # Slow subscribers
+///
+/// A subscriber that doesn't keep up loses logs: once `capacity` logs are waiting in its channel,
+/// new logs are dropped for that subscriber (and only for it) until it catches up.
+pub struct BroadcastSink {
+    subscribers: Mutex<Vec<SyncSender<String>>>,
+    capacity: usize,
+}
+
+impl BroadcastSink {
+    /// Creates a sink without subscribers, where each subscriber can fall behind by 256 logs.
+    pub fn new() -> Self {
+        Self::with_capacity(DEFAULT_CAPACITY)
+    }
+
+    /// Creates a sink without subscribers, where each subscriber can fall behind by `capacity`
+    /// logs.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            subscribers: Mutex::new(Vec::new()),
+            capacity,
+        }
+    }
+
+    /// Adds a subscriber, returning the receiver of the logs sent from now on.
+    pub fn subscribe(&self) -> Receiver<String> {
+        let (sender, receiver) = mpsc::sync_channel(self.capacity);
+
+        self.lock().push(sender);
+
+        receiver
+    }
+
+    /// Returns the number of subscribers (including dropped ones not yet removed by a log).
+    pub fn subscriber_count(&self) -> usize {
+        self.lock().len()
+    }
+
+    /// Formats a log entry and sends it to all subscribers.
+    pub fn log(&self, payload: &Payload) {
+        use core::fmt::Write as _;
+
+        let mut subscribers = self.lock();
+
+        if subscribers.is_empty() {
+            return;
+        }
+
+        let mut entry = String::new();
+        let _ = write!(entry, "{}", crate::format::Line::plain(payload));
+
+        subscribers.retain(|sender| match sender.try_send(entry.clone()) {
+            Ok(()) => true,
+            Err(TrySendError::Full(_)) => {
+                crate::metrics::count_drop(crate::metrics::DropReason::FullChannel);
+
+                true
+            }
+            Err(TrySendError::Disconnected(_)) => false,
+        });
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Vec<SyncSender<String>>> {
+        self.subscribers.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl Default for BroadcastSink {
+    fn default() -> Self {
+        Self::new()
+    }
+}
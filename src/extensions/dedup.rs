@@ -0,0 +1,177 @@
+extern crate std;
+
+use crate::{Level, LoggerFn, Payload};
+
+#[cfg(feature = "kv")]
+use core::fmt::Write as _;
+use std::string::String;
+
+/// Wraps a logger, collapsing runs of identical consecutive logs into the first one and a
+/// `... (repeated N more times)` summary (`std` feature).
+///
+/// A log is a duplicate when it has the same level and the same rendered message (with its fields
+/// with the `kv` feature) as the previous one. Duplicates aren't passed to the wrapped logger,
+/// they're counted instead, and once a different log comes in, a summary is written before it:
+/// the repeated message followed by ` (repeated N more times)`, `N` being the number of
+/// duplicates. The summary has the level, metadata and timestamp of the first log of the run.
+///
+/// Only *consecutive* duplicates are collapsed: `A, A, B, A` is written as `A`, the summary of
+/// the second `A`, `B`, then `A` again. Logs from several threads are interleaved, so duplicates
+/// from one thread can be split by logs from another.
+///
+/// A run still going on when the program stops is only reported by
+/// [`flush`](DedupLogger::flush), e.g., from the function registered with `set_flush`.
+///
+/// # Memory and performance
+///
+/// The wrapper keeps the last message, rendered to a `String` (so as much memory as the longest
+/// message, at most), and a counter. Every log is rendered to compare it with the last one, on top
+/// of the wrapped logger's own formatting. Extensions see every log, duplicates included.
+///
+/// # Example
+///
+/// ```
+/// use loggery::{error, extensions::DedupLogger, info, Payload};
+/// use std::cell::RefCell;
+///
+/// thread_local! {
+///     static LINES: RefCell<Vec<String>> = RefCell::new(Vec::new());
+///     static DEDUP: DedupLogger = DedupLogger::new(inner_logger);
+/// }
+///
+/// fn inner_logger(payload: Payload) {
+///     LINES.with(|lines| lines.borrow_mut().push(payload.args.to_string()));
+/// }
+///
+/// fn my_logger(payload: Payload) {
+///     DEDUP.with(|dedup| dedup.log(payload));
+/// }
+///
+/// fn main() {
+/// #   #[cfg(not(feature = "static"))]
+///     loggery::set_logger(my_logger);
+///
+///     for _ in 0..4 {
+///         error!("Connection refused");
+///     }
+///
+///     info!("Connected");
+///
+/// #   #[cfg(all(not(feature = "static"), not(feature = "min_level_off")))]
+///     LINES.with(|lines| {
+///         assert_eq!(
+///             *lines.borrow(),
+///             [
+///                 "Connection refused",
+///                 "Connection refused (repeated 3 more times)",
+///                 "Connected"
+///             ]
+///         );
+///     });
+/// }
+/// ```
+pub struct DedupLogger {
+    logger: LoggerFn,
+    last: crate::spin::Mutex<Option<Last>>,
+}
+
+/// The last log passed to the wrapped logger.
+struct Last {
+    message: String,
+    level: Level,
+    #[cfg(feature = "metadata")]
+    meta: crate::Metadata,
+    #[cfg(feature = "timestamp")]
+    timestamp: u64,
+    /// Number of duplicates suppressed since.
+    repeats: usize,
+}
+
+impl DedupLogger {
+    /// Creates a wrapper around `logger`.
+    pub fn new(logger: LoggerFn) -> Self {
+        Self {
+            logger,
+            last: crate::spin::Mutex::new(None),
+        }
+    }
+
+    /// Passes a log to the wrapped logger, unless it's a duplicate of the previous one.
+    ///
+    /// If it ends a run of duplicates, their summary is passed first.
+    pub fn log(&self, payload: Payload) {
+        // Rendered before locking, since rendering can log
+        #[cfg_attr(not(feature = "kv"), allow(unused_mut))]
+        let mut message = std::format!("{}", payload.args);
+
+        #[cfg(feature = "kv")]
+        let _ = write!(message, "{}", crate::__private::KeyValues(payload.fields));
+
+        let previous = {
+            let mut last = self.last.lock();
+
+            match &mut *last {
+                Some(last) if last.level == payload.level && last.message == message => {
+                    last.repeats += 1;
+
+                    return;
+                }
+                _ => last.replace(Last {
+                    message,
+                    level: payload.level,
+                    #[cfg(feature = "metadata")]
+                    meta: payload.meta,
+                    #[cfg(feature = "timestamp")]
+                    timestamp: payload.timestamp,
+                    repeats: 0,
+                }),
+            }
+        };
+
+        if let Some(previous) = previous {
+            self.summarize(&previous);
+        }
+
+        (self.logger)(payload);
+    }
+
+    /// Passes the summary of the current run of duplicates to the wrapped logger, if any.
+    ///
+    /// The next log is compared with nothing, so it's always passed on, even if it's the same as
+    /// the last one.
+    pub fn flush(&self) {
+        let previous = self.last.lock().take();
+
+        if let Some(previous) = previous {
+            self.summarize(&previous);
+        }
+    }
+
+    /// Passes the summary of the duplicates of `last`, if there are any.
+    fn summarize(&self, last: &Last) {
+        if last.repeats > 0 {
+            self.emit(
+                last,
+                format_args!("{} (repeated {} more times)", last.message, last.repeats),
+            );
+        }
+    }
+
+    /// Passes a log with the level, metadata and timestamp of `last` to the wrapped logger.
+    fn emit(&self, last: &Last, args: core::fmt::Arguments) {
+        #[cfg_attr(not(feature = "timestamp"), allow(unused_mut))]
+        let mut payload = Payload::__new(
+            last.level,
+            args,
+            #[cfg(feature = "metadata")]
+            last.meta,
+        );
+
+        #[cfg(feature = "timestamp")]
+        {
+            payload.timestamp = last.timestamp;
+        }
+
+        (self.logger)(payload);
+    }
+}
@@ -0,0 +1,153 @@
+use crate::Payload;
+
+use super::json::JsonEscape;
+
+use core::fmt::{self, Write as _};
+
+/// The version of the Elastic Common Schema the entries follow.
+const ECS_VERSION: &str = "1.6.0";
+
+/// Writes a log entry as a single-line Elastic Common Schema (ECS) JSON object (`std` feature).
+///
+/// The entry follows the [ECS logging](https://github.com/elastic/ecs-logging) conventions for
+/// [ECS](https://www.elastic.co/guide/en/ecs/current/index.html) `1.6.0`:
+/// `@timestamp`, `log.level` and `message` come first, with dotted names, and the other fields
+/// are nested objects. The payload maps to these ECS fields:
+///
+/// | Data                         | ECS field                | Feature       |
+/// |------------------------------|--------------------------|---------------|
+/// | `Payload::timestamp`         | `@timestamp` (RFC 3339)  | `timestamp`   |
+/// | `Payload::level` (lowercase) | `log.level`              |               |
+/// | `Payload::args`              | `message`                |               |
+/// | -                            | `ecs.version` (`1.6.0`)  |               |
+/// | `Metadata::module_path`      | `log.logger`             | `metadata`    |
+/// | `Metadata::file`             | `log.origin.file.name`   | `metadata`    |
+/// | `Metadata::line`             | `log.origin.file.line`   | `metadata`    |
+/// | `Metadata::thread_id`        | `process.thread.id`      | `thread_meta` |
+/// | `Payload::fields`            | `labels.<key>` (strings) | `kv`          |
+///
+/// ```json
+/// {"@timestamp":"2023-11-14T22:13:20.123Z","log.level":"info","message":"Server started",
+///  "ecs.version":"1.6.0","log":{"logger":"my_app","origin":{"file":{"name":"src/main.rs",
+///  "line":42}}}}
+/// ```
+///
+/// (on a single line, wrapped here for readability)
+///
+/// ECS requires `@timestamp`: without the `timestamp` feature, it's left out and has to be set at
+/// ingestion (e.g., by an ingest pipeline). Field values are written as strings, as ECS expects
+/// for `labels`.
+///
+/// The signature matches [`FormatterFn`](crate::FormatterFn), so it can be passed to
+/// [`set_global_formatter`](crate::set_global_formatter) to write ECS from every sink. This is
+/// distinct from [`to_json`](super::to_json), whose flat field names don't follow ECS.
+///
+/// # Example
+///
+/// ```
+/// use loggery::{extensions::format_ecs, info, Payload};
+/// use std::cell::RefCell;
+///
+/// thread_local! {
+///     static LAST_ECS: RefCell<String> = RefCell::new(String::new());
+/// }
+///
+/// fn my_extension(payload: &Payload) {
+///     LAST_ECS.with(|ecs| {
+///         let mut ecs = ecs.borrow_mut();
+///
+///         ecs.clear();
+///         let _ = format_ecs(payload, &mut *ecs);
+///     });
+/// }
+///
+/// fn main() {
+/// #   #[cfg(not(feature = "static"))]
+///     loggery::set_extension(my_extension);
+///
+///     info!("Server started");
+///
+/// #   #[cfg(all(not(feature = "static"), not(feature = "min_level_off")))]
+///     LAST_ECS.with(|ecs| {
+///         let ecs = ecs.borrow();
+///
+///         assert!(ecs.contains(r#""log.level":"info","message":"Server started","#));
+///         assert!(ecs.contains(r#""ecs.version":"1.6.0""#));
+/// #       #[cfg(feature = "timestamp")]
+///         assert!(ecs.starts_with(r#"{"@timestamp":""#));
+/// #       #[cfg(feature = "metadata")]
+///         assert!(ecs.contains(r#""log":{"logger":"rust_out","origin":{"file":{"name":"#));
+///     });
+/// }
+/// ```
+pub fn format_ecs(payload: &Payload, out: &mut dyn fmt::Write) -> fmt::Result {
+    out.write_char('{')?;
+
+    #[cfg(feature = "timestamp")]
+    write!(
+        out,
+        "\"@timestamp\":\"{}\",",
+        crate::TimestampFormat::Rfc3339.display(payload.timestamp)
+    )?;
+
+    write!(
+        out,
+        "\"log.level\":\"{}\",\"message\":\"",
+        EcsLevel(payload.level)
+    )?;
+    write!(JsonEscape(&mut *out), "{}", payload.args)?;
+    write!(out, "\",\"ecs.version\":\"{}\"", ECS_VERSION)?;
+
+    #[cfg(feature = "metadata")]
+    {
+        out.write_str(",\"log\":{\"logger\":\"")?;
+        JsonEscape(&mut *out).write_str(payload.meta.module_path)?;
+        out.write_str("\",\"origin\":{\"file\":{\"name\":\"")?;
+        JsonEscape(&mut *out).write_str(payload.meta.file)?;
+        write!(out, "\",\"line\":{}}}}}}}", payload.meta.line)?;
+    }
+
+    #[cfg(feature = "thread_meta")]
+    write!(
+        out,
+        ",\"process\":{{\"thread\":{{\"id\":{}}}}}",
+        payload.meta.thread_id
+    )?;
+
+    #[cfg(feature = "kv")]
+    if !payload.fields.is_empty() {
+        out.write_str(",\"labels\":{")?;
+
+        for (i, (key, value)) in payload.fields.iter().enumerate() {
+            if i > 0 {
+                out.write_char(',')?;
+            }
+
+            out.write_char('"')?;
+            JsonEscape(&mut *out).write_str(key)?;
+            out.write_str("\":\"")?;
+            write!(JsonEscape(&mut *out), "{}", value)?;
+            out.write_char('"')?;
+        }
+
+        out.write_char('}')?;
+    }
+
+    out.write_char('}')
+}
+
+/// Writes a level in lowercase, as ECS expects for `log.level`.
+struct EcsLevel(crate::Level);
+
+impl fmt::Display for EcsLevel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self.0 {
+            crate::Level::Trace => "trace",
+            crate::Level::Debug => "debug",
+            crate::Level::Info => "info",
+            crate::Level::Warn => "warn",
+            crate::Level::Error => "error",
+            crate::Level::Off => "off",
+        })
+    }
+}
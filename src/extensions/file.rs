@@ -0,0 +1,267 @@
+extern crate std;
+
+use crate::{FormatterFn, Payload};
+
+use std::{
+    fs::File,
+    io::{BufWriter, Write as _},
+    string::String,
+    sync::Mutex,
+};
+
+/// Appends log entries to a file opened once (`std` feature).
+///
+/// Unlike [`save_to_file`](super::save_to_file), which opens and closes the file for every log,
+/// the file handle is reused across calls, saving two syscalls per log. It's `Send + Sync`, so it
+/// can live in a `static` (e.g., in a `OnceLock`) shared by all threads.
+///
+/// # Exit
+///
+/// Logs below [`warn!`](crate::warn) are buffered (see [Buffering](#buffering)), and only written
+/// when the buffer is full, on [`flush`](FileLogger::flush) or when the logger is dropped. A logger
+/// that's never dropped (e.g., in a `static`, or a thread-local of the main thread) must be flushed
+/// before the program exits, or its last lines are silently lost: register a function flushing it
+/// with [`set_flush`](crate::set_flush), and call [`flush`](crate::flush()) on the way out (or
+/// install `install_atexit_flush`).
+///
+/// # Example
+///
+/// ```
+/// use loggery::{extensions::FileLogger, info, Payload};
+///
+/// thread_local! {
+///     static LOGGER: FileLogger = FileLogger::open(&path()).unwrap();
+/// }
+///
+/// fn path() -> String {
+///     let path = std::env::temp_dir().join("loggery_file_logger_doctest.log");
+///     path.to_string_lossy().into_owned()
+/// }
+///
+/// fn my_extension(payload: &Payload) {
+///     LOGGER.with(|logger| {
+///         let _ = logger.log(payload);
+///     });
+/// }
+///
+/// fn flush_logger() {
+///     LOGGER.with(|logger| {
+///         let _ = logger.flush();
+///     });
+/// }
+///
+/// fn main() {
+///     let _ = std::fs::remove_file(path());
+///
+/// #   #[cfg(not(feature = "static"))]
+///     loggery::set_extension(my_extension);
+///     loggery::set_flush(flush_logger);
+///
+///     info!("First");
+///     info!("Second");
+///
+///     // Before exiting, or the buffered `info!` logs are lost
+///     loggery::flush();
+///
+/// #   #[cfg(all(not(feature = "static"), not(feature = "min_level_off"),
+/// #       not(feature = "thread_meta"), not(feature = "cpu_meta")))]
+/// #   {
+///     let contents = std::fs::read_to_string(path()).unwrap();
+///
+///     assert!(contents.contains("[ INFO] First\n"));
+///     assert!(contents.ends_with("[ INFO] Second\n"));
+/// #   }
+/// }
+/// ```
+///
+/// # Buffering
+///
+/// Logs are buffered according to their level's [`FlushPolicy`](crate::FlushPolicy): by default,
+/// [`warn!`](crate::warn)/[`error!`](crate::error) logs are flushed right away (with everything
+/// buffered before them), while lower levels are written when the buffer is full, on
+/// [`flush`](FileLogger::flush) or when the logger is dropped.
+///
+/// ```
+/// use loggery::{extensions::FileLogger, error, trace, Level, Payload};
+///
+/// thread_local! {
+///     static LOGGER: FileLogger = FileLogger::open(&path()).unwrap();
+/// }
+///
+/// fn path() -> String {
+///     let path = std::env::temp_dir().join("loggery_file_logger_flush_doctest.log");
+///     path.to_string_lossy().into_owned()
+/// }
+///
+/// fn my_extension(payload: &Payload) {
+///     LOGGER.with(|logger| {
+///         let _ = logger.log(payload);
+///     });
+/// }
+///
+/// fn main() {
+///     let _ = std::fs::remove_file(path());
+///
+/// #   #[cfg(feature = "runtime_level")]
+///     loggery::set_min_level(Level::Trace);
+/// #   #[cfg(not(feature = "static"))]
+///     loggery::set_extension(my_extension);
+///
+///     trace!("Buffered");
+///
+/// #   #[cfg(all(not(feature = "static"), not(any(feature = "min_level_debug",
+/// #       feature = "min_level_info", feature = "min_level_warn", feature = "min_level_error",
+/// #       feature = "min_level_off")), not(feature = "thread_meta"), not(feature = "cpu_meta")))]
+/// #   {
+///     // Not flushed yet, the file was only created
+///     assert_eq!(std::fs::read_to_string(path()).unwrap(), "");
+///
+///     error!("Flushed");
+///
+///     let contents = std::fs::read_to_string(path()).unwrap();
+///
+///     assert!(contents.contains("[TRACE] Buffered\n"));
+///     assert!(contents.ends_with("[ERROR] Flushed\n"));
+/// #   }
+/// }
+/// ```
+///
+/// # Format
+///
+/// Logs are written in the format: `[LEVEL] message`
+///
+/// With the `timestamp` feature, the time is prepended in the format set with
+/// `set_timestamp_format`: `timestamp [LEVEL] message`
+///
+/// A formatter set with [`set_global_formatter`](crate::set_global_formatter) replaces this format,
+/// and one set with [`with_formatter`](FileLogger::with_formatter) replaces both for this logger.
+pub struct FileLogger {
+    writer: Mutex<BufWriter<File>>,
+    formatter_fn: Option<FormatterFn>,
+}
+
+impl FileLogger {
+    /// Opens (or creates) the file at `path` in append mode.
+    pub fn open(path: &str) -> std::io::Result<Self> {
+        let file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)?;
+
+        Ok(Self {
+            writer: Mutex::new(BufWriter::new(file)),
+            formatter_fn: None,
+        })
+    }
+
+    /// Formats the entries of this logger with `formatter_fn`, instead of the default format or
+    /// the global formatter.
+    ///
+    /// Each sink renders the log again from the same [`Payload`] (it isn't cloned, and its `args`
+    /// are rendered lazily), so the default logger can write colored text to the terminal while
+    /// this logger writes JSON to a file, from the same log.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use loggery::extensions::{write_json, FileLogger};
+    /// use loggery::{info, Destination, Level, Payload, StreamRouting};
+    ///
+    /// fn path(name: &str) -> String {
+    ///     let path = std::env::temp_dir().join(name);
+    ///     path.to_string_lossy().into_owned()
+    /// }
+    ///
+    /// thread_local! {
+    ///     static JSON_LOGGER: FileLogger = FileLogger::open(&path("loggery_json_sink.log"))
+    ///         .unwrap()
+    ///         .with_formatter(write_json);
+    /// }
+    ///
+    /// fn json_extension(payload: &Payload) {
+    ///     JSON_LOGGER.with(|logger| {
+    ///         let _ = logger.log(payload);
+    ///     });
+    /// }
+    ///
+    /// fn main() {
+    ///     let _ = std::fs::remove_file(path("loggery_json_sink.log"));
+    ///
+    ///     // Colored text for the terminal (`Destination::Stdout` in an application, a file here to
+    ///     // check it)
+    ///     let console = path("loggery_colored_sink.log");
+    ///     let _ = std::fs::remove_file(&console);
+    ///     let console: &'static str = Box::leak(console.into_boxed_str());
+    ///
+    ///     loggery::set_stream_routing(StreamRouting::new().route_with_format(
+    ///         Level::Info,
+    ///         Destination::File(console),
+    ///         loggery::colored,
+    ///     ));
+    ///
+    ///     // JSON for the file
+    /// #   #[cfg(not(feature = "static"))]
+    ///     loggery::set_extension(json_extension);
+    ///
+    ///     info!("Backup done");
+    ///
+    ///     JSON_LOGGER.with(|logger| logger.flush().unwrap());
+    ///
+    /// #   #[cfg(all(any(not(feature = "static"), feature = "static_default"), not(any(
+    /// #       feature = "min_level_warn", feature = "min_level_error", feature = "min_level_off")),
+    /// #       not(feature = "timestamp"), not(feature = "thread_meta"), not(feature = "cpu_meta")))]
+    ///     assert_eq!(
+    ///         std::fs::read_to_string(console).unwrap(),
+    ///         "\x1b[32m[ INFO]\x1b[0m Backup done\n"
+    ///     );
+    ///
+    /// #   #[cfg(all(not(feature = "static"), not(any(feature = "min_level_warn",
+    /// #       feature = "min_level_error", feature = "min_level_off"))))]
+    ///     assert!(std::fs::read_to_string(path("loggery_json_sink.log"))
+    ///         .unwrap()
+    ///         .starts_with(r#"{"level":"INFO","message":"Backup done""#));
+    /// }
+    /// ```
+    pub fn with_formatter(mut self, formatter_fn: FormatterFn) -> Self {
+        self.formatter_fn = Some(formatter_fn);
+
+        self
+    }
+
+    /// Appends a log entry.
+    ///
+    /// The entry is written in a single write, so lines from different threads (or processes
+    /// appending to the same file) are never interleaved. It stays buffered unless its level has
+    /// the [`FlushPolicy::Immediate`](crate::FlushPolicy::Immediate) policy, see
+    /// [`set_flush_policy`](crate::set_flush_policy).
+    pub fn log(&self, payload: &Payload) -> std::io::Result<()> {
+        use core::fmt::Write as _;
+
+        let mut line = String::new();
+
+        let _ = match self.formatter_fn {
+            Some(formatter_fn) => {
+                writeln!(line, "{}", crate::format::Formatted(formatter_fn, payload))
+            }
+            None => writeln!(line, "{}", crate::format::Line::timestamped(payload)),
+        };
+
+        let mut writer = self.writer.lock().unwrap_or_else(|e| e.into_inner());
+
+        writer.write_all(line.as_bytes())?;
+
+        if crate::get_flush_policy(payload.level) == crate::FlushPolicy::Immediate {
+            writer.flush()?;
+        }
+
+        Ok(())
+    }
+
+    /// Writes the buffered log entries to the file.
+    pub fn flush(&self) -> std::io::Result<()> {
+        self.writer
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .flush()
+    }
+}
@@ -0,0 +1,297 @@
+extern crate std;
+
+use crate::{Level, Payload};
+
+use std::{io::Write as _, string::String, vec::Vec};
+
+/// Version of the frame layout, written in every frame.
+const FRAME_VERSION: u8 = 1;
+/// Size of the length prefix of a frame.
+const LEN_SIZE: usize = 4;
+
+/// Set in the flags when the frame has the metadata.
+const FLAG_METADATA: u8 = 1 << 0;
+/// Set in the flags when the frame has the timestamp.
+const FLAG_TIMESTAMP: u8 = 1 << 1;
+
+/// Appends a log entry to `buf` as a length-prefixed binary frame (`std` feature).
+///
+/// Frames are a compact, dependency-free wire format to send logs to another process (e.g., over
+/// a pipe), read back with [`decode_frame`] instead of parsing text. Integers are little-endian,
+/// and strings are a `u32` byte length followed by their UTF-8 bytes:
+///
+/// | Field     | Size     | Content                                                       |
+/// |-----------|----------|---------------------------------------------------------------|
+/// | Length    | 4        | `u32` length of the rest of the frame                         |
+/// | Version   | 1        | Frame layout version, currently `1`                           |
+/// | Level     | 1        | The [`Level`] discriminant (`0` = trace to `4` = error)       |
+/// | Flags     | 1        | Bit 0: the metadata follows, bit 1: the timestamp follows     |
+/// | Message   | variable | The rendered message (with the fields appended with `kv`)     |
+/// | Metadata  | variable | Target, module path and file strings, then the `u32` line     |
+/// | Timestamp | 8        | `u64` milliseconds since the UNIX epoch                       |
+///
+/// The metadata is written with the `metadata` feature, and the timestamp with the `timestamp`
+/// feature. The receiving side doesn't need the same features: the flags tell which parts are in
+/// the frame. The message is rendered straight into `buf`, without an intermediate `String`.
+///
+/// # Example
+///
+/// ```
+/// use loggery::extensions::{decode_frame, encode_frame, frame_len};
+/// use loggery::{info, Level, Payload};
+/// use std::cell::RefCell;
+///
+/// thread_local! {
+///     // Stands for the pipe to the parent process
+///     static PIPE: RefCell<Vec<u8>> = RefCell::new(Vec::new());
+/// }
+///
+/// fn my_extension(payload: &Payload) {
+///     PIPE.with(|pipe| encode_frame(payload, &mut pipe.borrow_mut()));
+/// }
+///
+/// fn main() {
+/// #   #[cfg(not(feature = "static"))]
+///     loggery::set_extension(my_extension);
+///
+///     info!("Listening on port {}", 8080);
+///
+/// #   #[cfg(all(not(feature = "static"), not(any(feature = "min_level_warn",
+/// #       feature = "min_level_error", feature = "min_level_off"))))]
+/// #   {
+///     let pipe = PIPE.with(|pipe| pipe.borrow().clone());
+///
+///     // The receiving side reads a whole frame, then decodes it
+///     let len = frame_len(&pipe).unwrap();
+///     let record = decode_frame(&pipe[..len]).unwrap();
+///
+///     assert_eq!(record.level, Level::Info);
+///     assert_eq!(record.message, "Listening on port 8080");
+/// #   #[cfg(feature = "metadata")]
+///     assert_eq!(record.meta.unwrap().module_path, module_path!());
+/// #   }
+/// }
+/// ```
+pub fn encode_frame(payload: &Payload, buf: &mut Vec<u8>) {
+    let start = buf.len();
+    buf.extend_from_slice(&[0; LEN_SIZE]);
+
+    #[cfg_attr(
+        not(any(feature = "metadata", feature = "timestamp")),
+        allow(unused_mut)
+    )]
+    let mut flags = 0;
+
+    #[cfg(feature = "metadata")]
+    {
+        flags |= FLAG_METADATA;
+    }
+
+    #[cfg(feature = "timestamp")]
+    {
+        flags |= FLAG_TIMESTAMP;
+    }
+
+    buf.extend_from_slice(&[FRAME_VERSION, payload.level as u8, flags]);
+
+    // The message length is only known once rendered
+    let message_start = buf.len();
+    buf.extend_from_slice(&[0; LEN_SIZE]);
+    let _ = write!(buf, "{}", payload.args);
+
+    #[cfg(feature = "kv")]
+    let _ = write!(buf, "{}", crate::__private::KeyValues(payload.fields));
+
+    patch_len(buf, message_start);
+
+    #[cfg(feature = "metadata")]
+    {
+        push_str(buf, payload.meta.target);
+        push_str(buf, payload.meta.module_path);
+        push_str(buf, payload.meta.file);
+        buf.extend_from_slice(&payload.meta.line.to_le_bytes());
+    }
+
+    #[cfg(feature = "timestamp")]
+    buf.extend_from_slice(&payload.timestamp.to_le_bytes());
+
+    patch_len(buf, start);
+}
+
+/// Returns the length of the frame at the start of `buf` (length prefix included), or `None` if
+/// `buf` doesn't even hold the length prefix (`std` feature).
+///
+/// Frames follow each other in a stream: read until `buf` holds [`frame_len`] bytes, pass them to
+/// [`decode_frame`], and drop them from `buf` before reading the next frame.
+pub fn frame_len(buf: &[u8]) -> Option<usize> {
+    let mut reader = Reader(buf);
+
+    reader.u32().ok().map(|len| LEN_SIZE + len as usize)
+}
+
+/// Decodes a frame written by [`encode_frame`] (`std` feature).
+///
+/// `buf` must start with the frame, bytes past its end are ignored, see [`frame_len`]. See
+/// [`encode_frame`] for an example.
+pub fn decode_frame(buf: &[u8]) -> Result<OwnedRecord, DecodeError> {
+    let len = frame_len(buf).ok_or(DecodeError::Incomplete)?;
+    let frame = buf.get(LEN_SIZE..len).ok_or(DecodeError::Incomplete)?;
+    let mut reader = Reader(frame);
+
+    let version = reader.u8()?;
+
+    if version != FRAME_VERSION {
+        return Err(DecodeError::UnsupportedVersion(version));
+    }
+
+    let level = reader.u8()?;
+    let level = match Level::from_u8(level) {
+        Some(Level::Off) | None => return Err(DecodeError::InvalidLevel(level)),
+        Some(level) => level,
+    };
+
+    let flags = reader.u8()?;
+    let message = reader.string()?;
+
+    let meta = if flags & FLAG_METADATA != 0 {
+        Some(OwnedMetadata {
+            target: reader.string()?,
+            module_path: reader.string()?,
+            file: reader.string()?,
+            line: reader.u32()?,
+        })
+    } else {
+        None
+    };
+
+    let timestamp = if flags & FLAG_TIMESTAMP != 0 {
+        Some(reader.u64()?)
+    } else {
+        None
+    };
+
+    if !reader.0.is_empty() {
+        return Err(DecodeError::Malformed);
+    }
+
+    Ok(OwnedRecord {
+        level,
+        message,
+        meta,
+        timestamp,
+    })
+}
+
+/// A log entry decoded from a frame by [`decode_frame`] (`std` feature).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OwnedRecord {
+    /// The level of the log.
+    pub level: Level,
+    /// The rendered message, with the fields appended as ` key=value` if the sender had the `kv`
+    /// feature.
+    pub message: String,
+    /// The metadata, if the sender had the `metadata` feature.
+    pub meta: Option<OwnedMetadata>,
+    /// The timestamp (milliseconds since the UNIX epoch), if the sender had the `timestamp`
+    /// feature.
+    pub timestamp: Option<u64>,
+}
+
+/// The metadata of an [`OwnedRecord`] (`std` feature).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OwnedMetadata {
+    /// The target of the log.
+    pub target: String,
+    /// The module path where the log was generated.
+    pub module_path: String,
+    /// The file where the log was generated.
+    pub file: String,
+    /// The line where the log was generated.
+    pub line: u32,
+}
+
+/// Error returned by [`decode_frame`] (`std` feature).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DecodeError {
+    /// The buffer ends before the frame does.
+    Incomplete,
+    /// The frame was written with an unknown layout version.
+    UnsupportedVersion(u8),
+    /// The level byte isn't a valid level.
+    InvalidLevel(u8),
+    /// A string of the frame isn't valid UTF-8.
+    InvalidUtf8,
+    /// The content of the frame doesn't match its length or flags.
+    Malformed,
+}
+
+impl core::fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            DecodeError::Incomplete => f.write_str("incomplete log frame"),
+            DecodeError::UnsupportedVersion(version) => {
+                write!(f, "unsupported log frame version {}", version)
+            }
+            DecodeError::InvalidLevel(level) => write!(f, "invalid log level {} in frame", level),
+            DecodeError::InvalidUtf8 => f.write_str("invalid UTF-8 in log frame"),
+            DecodeError::Malformed => f.write_str("malformed log frame"),
+        }
+    }
+}
+
+impl std::error::Error for DecodeError {}
+
+/// Writes the length of what follows the length prefix at `start` into that prefix.
+fn patch_len(buf: &mut [u8], start: usize) {
+    let len = (buf.len() - start - LEN_SIZE) as u32;
+
+    buf[start..start + LEN_SIZE].copy_from_slice(&len.to_le_bytes());
+}
+
+/// Appends a length-prefixed string.
+#[cfg(feature = "metadata")]
+fn push_str(buf: &mut Vec<u8>, value: &str) {
+    buf.extend_from_slice(&(value.len() as u32).to_le_bytes());
+    buf.extend_from_slice(value.as_bytes());
+}
+
+/// Reads the fields of a frame, front to back.
+struct Reader<'a>(&'a [u8]);
+
+impl Reader<'_> {
+    fn bytes(&mut self, len: usize) -> Result<&[u8], DecodeError> {
+        if self.0.len() < len {
+            return Err(DecodeError::Malformed);
+        }
+
+        let (bytes, rest) = self.0.split_at(len);
+        self.0 = rest;
+
+        Ok(bytes)
+    }
+
+    fn u8(&mut self) -> Result<u8, DecodeError> {
+        Ok(self.bytes(1)?[0])
+    }
+
+    fn u32(&mut self) -> Result<u32, DecodeError> {
+        let mut bytes = [0; 4];
+        bytes.copy_from_slice(self.bytes(4)?);
+
+        Ok(u32::from_le_bytes(bytes))
+    }
+
+    fn u64(&mut self) -> Result<u64, DecodeError> {
+        let mut bytes = [0; 8];
+        bytes.copy_from_slice(self.bytes(8)?);
+
+        Ok(u64::from_le_bytes(bytes))
+    }
+
+    fn string(&mut self) -> Result<String, DecodeError> {
+        let len = self.u32()? as usize;
+        let bytes = self.bytes(len)?;
+
+        String::from_utf8(bytes.to_vec()).map_err(|_| DecodeError::InvalidUtf8)
+    }
+}
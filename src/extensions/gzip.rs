@@ -0,0 +1,177 @@
+extern crate std;
+
+use crate::Payload;
+
+use flate2::{write::GzEncoder, Compression};
+use std::{fs::File, io::Write as _, sync::Mutex};
+
+/// Number of lines written between two compressor flushes by default.
+const DEFAULT_FLUSH_INTERVAL: usize = 64;
+
+/// Appends gzip-compressed log entries to a `.gz` file (`gzip` feature).
+///
+/// The file is opened once and every log line goes through the same compressor. The compressor is
+/// flushed every `flush_interval` lines (and after logs with the
+/// [`FlushPolicy::Immediate`](crate::FlushPolicy::Immediate) policy, see
+/// [`set_flush_policy`](crate::set_flush_policy)), so the data written so far is decodable even if
+/// the process dies, and the gzip stream is finished by [`close`](GzipFileSink::close) (or on drop).
+///
+/// # Example
+///
+/// ```
+/// use loggery::{extensions::GzipFileSink, info, Payload};
+/// use std::io::Read as _;
+///
+/// thread_local! {
+///     static SINK: GzipFileSink = GzipFileSink::open(&path()).unwrap();
+/// }
+///
+/// fn path() -> String {
+///     let path = std::env::temp_dir().join("loggery_gzip_doctest.log.gz");
+///     path.to_string_lossy().into_owned()
+/// }
+///
+/// fn my_extension(payload: &Payload) {
+///     SINK.with(|sink| {
+///         let _ = sink.log(payload);
+///     });
+/// }
+///
+/// fn main() {
+///     let _ = std::fs::remove_file(path());
+///
+/// #   #[cfg(not(feature = "static"))]
+///     loggery::set_extension(my_extension);
+///
+///     info!("Compressed!");
+///     info!("Compressed again!");
+///
+///     SINK.with(|sink| sink.close()).unwrap();
+///
+///     let mut text = String::new();
+///     let file = std::fs::File::open(path()).unwrap();
+///     flate2::read::MultiGzDecoder::new(file)
+///         .read_to_string(&mut text)
+///         .unwrap();
+///
+/// #   #[cfg(all(not(feature = "static"), not(any(feature = "min_level_warn",
+/// #       feature = "min_level_error", feature = "min_level_off")), not(feature = "thread_meta"),
+/// #       not(feature = "cpu_meta")))]
+///     assert_eq!(text, "[ INFO] Compressed!\n[ INFO] Compressed again!\n");
+/// }
+/// ```
+///
+/// # Format
+///
+/// Logs are written in the format: `[LEVEL] message`, or with the formatter set with
+/// [`set_global_formatter`](crate::set_global_formatter).
+///
+/// # Tradeoffs
+///
+/// Compressed logs take a fraction of the disk space, but they can't be followed with `tail -f`
+/// or grepped directly. Use `zcat`/`zgrep` (or [`flate2::read::MultiGzDecoder`]) to read them.
+/// Lines that are still sitting in the compressor (since the last flush) are lost if the process
+/// is killed before [`close`](GzipFileSink::close) is called.
+///
+/// # Rotation
+///
+/// The file is opened in append mode and every sink writes its own complete gzip member, so
+/// rotating is just closing the current sink, moving the file away and opening a new one. Both the
+/// rotated files and a file appended to across restarts are valid gzip archives.
+pub struct GzipFileSink {
+    state: Mutex<Option<State>>,
+    flush_interval: usize,
+}
+
+struct State {
+    encoder: GzEncoder<File>,
+    pending: usize,
+}
+
+impl GzipFileSink {
+    /// Opens (or creates) the file at `path` in append mode.
+    ///
+    /// The compressor is flushed every 64 lines, use
+    /// [`with_flush_interval`](GzipFileSink::with_flush_interval) to change that.
+    #[inline]
+    pub fn open(path: &str) -> std::io::Result<Self> {
+        Self::with_flush_interval(path, DEFAULT_FLUSH_INTERVAL)
+    }
+
+    /// Opens (or creates) the file at `path` in append mode, flushing the compressor every
+    /// `flush_interval` lines.
+    ///
+    /// A `flush_interval` of `0` or `1` flushes after every line, which gives the worst
+    /// compression ratio.
+    pub fn with_flush_interval(path: &str, flush_interval: usize) -> std::io::Result<Self> {
+        let file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)?;
+
+        Ok(Self {
+            state: Mutex::new(Some(State {
+                encoder: GzEncoder::new(file, Compression::default()),
+                pending: 0,
+            })),
+            flush_interval: flush_interval.max(1),
+        })
+    }
+
+    /// Compresses and appends a log entry.
+    ///
+    /// Returns an error if the sink has already been closed.
+    pub fn log(&self, payload: &Payload) -> std::io::Result<()> {
+        let mut guard = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        let state = guard.as_mut().ok_or_else(closed)?;
+
+        writeln!(state.encoder, "{}", crate::format::Line::plain(payload))?;
+
+        state.pending += 1;
+
+        let is_immediate = crate::get_flush_policy(payload.level) == crate::FlushPolicy::Immediate;
+
+        if state.pending >= self.flush_interval || is_immediate {
+            state.pending = 0;
+            state.encoder.flush()?;
+        }
+
+        Ok(())
+    }
+
+    /// Flushes the compressor, making every line logged so far decodable.
+    pub fn flush(&self) -> std::io::Result<()> {
+        let mut guard = self.state.lock().unwrap_or_else(|e| e.into_inner());
+
+        match guard.as_mut() {
+            Some(state) => {
+                state.pending = 0;
+                state.encoder.flush()
+            }
+            None => Ok(()),
+        }
+    }
+
+    /// Finishes the gzip stream and closes the file.
+    ///
+    /// Any later [`log`](GzipFileSink::log) call returns an error. Closing twice is a no-op.
+    pub fn close(&self) -> std::io::Result<()> {
+        let mut guard = self.state.lock().unwrap_or_else(|e| e.into_inner());
+
+        match guard.take() {
+            Some(state) => state.encoder.finish()?.sync_all(),
+            None => Ok(()),
+        }
+    }
+}
+
+impl Drop for GzipFileSink {
+    fn drop(&mut self) {
+        let _ = self.close();
+    }
+}
+
+#[inline]
+fn closed() -> std::io::Error {
+    std::io::Error::new(std::io::ErrorKind::Other, "gzip sink is closed")
+}
@@ -0,0 +1,170 @@
+extern crate std;
+
+use crate::Payload;
+
+use std::{fs::File, io::Write as _, string::String, sync::Mutex};
+
+/// Appends log entries to a file, chaining each line's hash with the previous one
+/// (`std` feature).
+///
+/// Every line starts with a hash of the previous line's hash and the current entry, so modifying,
+/// reordering or deleting a line breaks the chain from that line onward, which can be detected
+/// with [`HashChainSink::verify`].
+///
+/// # Example
+///
+/// ```
+/// use loggery::{extensions::HashChainSink, info, Payload};
+///
+/// thread_local! {
+///     static SINK: HashChainSink = HashChainSink::open(&path()).unwrap();
+/// }
+///
+/// fn path() -> String {
+///     let path = std::env::temp_dir().join("loggery_hash_chain_doctest.log");
+///     path.to_string_lossy().into_owned()
+/// }
+///
+/// fn my_extension(payload: &Payload) {
+///     SINK.with(|sink| {
+///         let _ = sink.log(payload);
+///     });
+/// }
+///
+/// fn main() {
+/// #   #[cfg(all(not(feature = "static"), not(feature = "min_level_off")))]
+/// #   {
+///     let _ = std::fs::remove_file(path());
+///
+///     loggery::set_extension(my_extension);
+///
+///     info!("User logged in");
+///     info!("User changed password");
+///     info!("User logged out");
+///
+///     let contents = std::fs::read_to_string(path()).unwrap();
+///
+///     assert_eq!(HashChainSink::verify(&contents), Ok(()));
+///
+///     // Tampering with the middle line is detected
+///     let tampered = contents.replace("changed password", "read the news");
+///
+///     assert_eq!(HashChainSink::verify(&tampered), Err(1));
+/// #   }
+/// }
+/// ```
+///
+/// # Format
+///
+/// Logs are written in the format: `[hash] [LEVEL] message`, where `hash` is 16 hex digits. A
+/// formatter set with [`set_global_formatter`](crate::set_global_formatter) replaces the
+/// `[LEVEL] message` part.
+///
+/// # Hash
+///
+/// The hash is the 64-bit [FNV-1a](http://www.isthe.com/chongo/tech/comp/fnv/) of the previous
+/// line's hash (little-endian bytes, `0` for the first line) followed by the `[LEVEL] message`
+/// text. FNV-1a is fast but **not** cryptographic: the chain detects accidental corruption and
+/// naive edits, but anyone able to rewrite the file can also recompute the hashes. It's an
+/// integrity check, not a security guarantee.
+///
+/// Deleting lines from the *end* of the file can't be detected, since nothing comes after them.
+///
+/// When opening an existing file, the chain continues from the hash of its last line.
+pub struct HashChainSink {
+    state: Mutex<State>,
+}
+
+struct State {
+    file: File,
+    previous_hash: u64,
+}
+
+impl HashChainSink {
+    /// Opens (or creates) the file at `path` in append mode, continuing its hash chain.
+    pub fn open(path: &str) -> std::io::Result<Self> {
+        let previous_hash = match std::fs::read_to_string(path) {
+            Ok(contents) => contents
+                .lines()
+                .last()
+                .and_then(|line| parse_line(line))
+                .map(|(hash, _)| hash)
+                .unwrap_or(0),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => 0,
+            Err(e) => return Err(e),
+        };
+
+        let file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)?;
+
+        Ok(Self {
+            state: Mutex::new(State {
+                file,
+                previous_hash,
+            }),
+        })
+    }
+
+    /// Appends a log entry, chained to the previous one.
+    pub fn log(&self, payload: &Payload) -> std::io::Result<()> {
+        use core::fmt::Write as _;
+
+        let mut entry = String::new();
+        let _ = write!(entry, "{}", crate::format::Line::plain(payload));
+
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        let hash = chain_hash(state.previous_hash, &entry);
+
+        writeln!(state.file, "[{:016x}] {}", hash, entry)?;
+
+        state.previous_hash = hash;
+
+        Ok(())
+    }
+
+    /// Verifies the hash chain of the `contents` of a file written by a [`HashChainSink`].
+    ///
+    /// Returns the index (0-based) of the first line that doesn't match the chain.
+    pub fn verify(contents: &str) -> Result<(), usize> {
+        let mut previous_hash = 0;
+
+        for (index, line) in contents.lines().enumerate() {
+            match parse_line(line) {
+                Some((hash, entry)) if hash == chain_hash(previous_hash, entry) => {
+                    previous_hash = hash;
+                }
+                _ => return Err(index),
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Splits a `[hash] [LEVEL] message` line into its hash and `[LEVEL] message` entry.
+fn parse_line(line: &str) -> Option<(u64, &str)> {
+    if !line.starts_with('[') {
+        return None;
+    }
+
+    let hex = line.get(1..17)?;
+    let entry = line.get(17..)?.strip_prefix("] ")?;
+
+    u64::from_str_radix(hex, 16).ok().map(|hash| (hash, entry))
+}
+
+/// 64-bit FNV-1a of `previous_hash` (little-endian) followed by `entry`.
+fn chain_hash(previous_hash: u64, entry: &str) -> u64 {
+    const OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+    const PRIME: u64 = 0x0000_0100_0000_01b3;
+
+    previous_hash
+        .to_le_bytes()
+        .iter()
+        .chain(entry.as_bytes())
+        .fold(OFFSET_BASIS, |hash, byte| {
+            (hash ^ u64::from(*byte)).wrapping_mul(PRIME)
+        })
+}
@@ -0,0 +1,192 @@
+extern crate std;
+
+use crate::Payload;
+
+use core::fmt::Write as _;
+use std::string::String;
+
+/// Formats a log entry as a single-line JSON object (`std` feature).
+///
+/// The object has the `level` and `message` fields, plus the `module`, `file` and `line` fields of
+/// the [`meta`](crate::Metadata) with the `metadata` feature, and the `timestamp` field
+/// (milliseconds since the UNIX epoch) with the `timestamp` feature:
+///
+/// ```json
+/// {"level":"INFO","message":"Hello, \"JSON\"!","module":"my_app","file":"src/main.rs","line":42}
+/// ```
+///
+/// With the `kv` feature, the fields of the log (if any) are in a `fields` object, with their
+/// values as strings: `"fields":{"user_id":"42"}`.
+///
+/// Quotes, backslashes and control characters in the message (and metadata) are escaped, so every
+/// entry is a valid JSON object on a single line, ready for ingestion by tools like Loki or
+/// Elasticsearch.
+///
+/// # Example
+///
+/// ```
+/// use loggery::{extensions::to_json, info, Payload};
+/// use std::cell::RefCell;
+///
+/// thread_local! {
+///     static LAST_JSON: RefCell<String> = RefCell::new(String::new());
+/// }
+///
+/// fn my_extension(payload: &Payload) {
+///     LAST_JSON.with(|json| *json.borrow_mut() = to_json(payload));
+/// }
+///
+/// fn main() {
+/// #   #[cfg(not(feature = "static"))]
+///     loggery::set_extension(my_extension);
+///
+///     info!("Said \"hi\"\n\tand left");
+///
+/// #   #[cfg(all(not(feature = "static"), not(feature = "min_level_off")))]
+///     LAST_JSON.with(|json| {
+///         assert!(json
+///             .borrow()
+///             .starts_with(r#"{"level":"INFO","message":"Said \"hi\"\n\tand left""#));
+///     });
+/// }
+/// ```
+pub fn to_json(payload: &Payload) -> String {
+    let mut json = String::new();
+
+    let _ = write_json(payload, &mut json);
+
+    json
+}
+
+/// Writes a log entry as a single-line JSON object, see [`to_json`] (`std` feature).
+///
+/// It's a [`FormatterFn`](crate::FormatterFn), so it can be the formatter of a sink, e.g., of a
+/// route of the default logger (see
+/// [`StreamRouting::route_with_format`](crate::StreamRouting::route_with_format)) or of a
+/// [`FileLogger`](super::FileLogger), without allocating the entry first.
+///
+/// # Example
+///
+/// ```
+/// use loggery::{extensions::write_json, info, Payload};
+/// use std::cell::RefCell;
+///
+/// thread_local! {
+///     static LAST_JSON: RefCell<String> = RefCell::new(String::new());
+/// }
+///
+/// fn my_logger(payload: Payload) {
+///     LAST_JSON.with(|json| {
+///         let mut json = json.borrow_mut();
+///
+///         json.clear();
+///         let _ = write_json(&payload, &mut *json);
+///     });
+/// }
+///
+/// fn main() {
+/// #   #[cfg(not(feature = "static"))]
+///     loggery::set_logger(my_logger);
+///
+///     info!("Ready");
+///
+/// #   #[cfg(all(not(feature = "static"), not(any(feature = "min_level_warn",
+/// #       feature = "min_level_error", feature = "min_level_off"))))]
+///     LAST_JSON.with(|json| {
+///         assert!(json.borrow().starts_with(r#"{"level":"INFO","message":"Ready""#));
+///     });
+/// }
+/// ```
+pub fn write_json(payload: &Payload, out: &mut dyn core::fmt::Write) -> core::fmt::Result {
+    out.write_str("{\"level\":\"")?;
+    out.write_str(payload.level.name())?;
+    out.write_str("\",\"message\":\"")?;
+    write!(JsonEscape(&mut *out), "{}", payload.args)?;
+    out.write_char('"')?;
+
+    #[cfg(feature = "kv")]
+    if !payload.fields.is_empty() {
+        out.write_str(",\"fields\":{")?;
+
+        for (i, (key, value)) in payload.fields.iter().enumerate() {
+            if i > 0 {
+                out.write_char(',')?;
+            }
+
+            out.write_char('"')?;
+            JsonEscape(&mut *out).write_str(key)?;
+            out.write_str("\":\"")?;
+            write!(JsonEscape(&mut *out), "{}", value)?;
+            out.write_char('"')?;
+        }
+
+        out.write_char('}')?;
+    }
+
+    #[cfg(feature = "metadata")]
+    {
+        out.write_str(",\"module\":\"")?;
+        JsonEscape(&mut *out).write_str(payload.meta.module_path)?;
+        out.write_str("\",\"file\":\"")?;
+        JsonEscape(&mut *out).write_str(payload.meta.file)?;
+        write!(out, "\",\"line\":{}", payload.meta.line)?;
+    }
+
+    #[cfg(feature = "timestamp")]
+    write!(out, ",\"timestamp\":{}", payload.timestamp)?;
+
+    out.write_char('}')
+}
+
+/// Appends a log entry to a file as a JSON object on its own line (`std` feature).
+///
+/// The file at the `path` is opened in append mode. See [`to_json`] for the format.
+///
+/// # Example
+///
+/// ```no_run
+/// use loggery::{Payload, debug};
+///
+/// fn my_extension(payload: &Payload) {
+///     let _ = loggery::extensions::save_json_to_file(payload, "path/to/app.jsonl");
+/// }
+///
+/// fn main() {
+/// #   #[cfg(not(feature = "static"))]
+///     loggery::set_extension(my_extension);
+///
+///     debug!("A log message that will be saved to a file as JSON too!");
+/// }
+/// ```
+#[inline]
+pub fn save_json_to_file(payload: &Payload, path: &str) -> std::io::Result<()> {
+    use std::io::Write as _;
+
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)?;
+
+    writeln!(file, "{}", to_json(payload))
+}
+
+/// Writes strings into a JSON string literal (without the surrounding quotes), escaping them.
+pub(super) struct JsonEscape<'a, W: core::fmt::Write + ?Sized>(pub(super) &'a mut W);
+
+impl<W: core::fmt::Write + ?Sized> core::fmt::Write for JsonEscape<'_, W> {
+    fn write_str(&mut self, s: &str) -> core::fmt::Result {
+        for c in s.chars() {
+            match c {
+                '"' => self.0.write_str("\\\"")?,
+                '\\' => self.0.write_str("\\\\")?,
+                '\n' => self.0.write_str("\\n")?,
+                '\r' => self.0.write_str("\\r")?,
+                '\t' => self.0.write_str("\\t")?,
+                c if c < ' ' => write!(self.0, "\\u{:04x}", c as u32)?,
+                c => self.0.write_char(c)?,
+            }
+        }
+
+        Ok(())
+    }
+}
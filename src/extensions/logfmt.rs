@@ -0,0 +1,144 @@
+extern crate std;
+
+use crate::Payload;
+
+use core::fmt::Write as _;
+use std::string::String;
+
+/// Formats a log entry as a single logfmt line of `key=value` pairs (`std` feature).
+///
+/// The line has the `level` (in lowercase) and `msg` pairs, then the fields of the log with the
+/// `kv` feature, the `module`, `file` and `line` pairs of the [`meta`](crate::Metadata) with the
+/// `metadata` feature, and the `timestamp` pair (milliseconds since the UNIX epoch) with the
+/// `timestamp` feature:
+///
+/// ```text
+/// level=info msg="Logged in" user_id=42 module=my_app file=src/main.rs line=42
+/// ```
+///
+/// # Escaping
+///
+/// - A value is written verbatim, unless it's empty or contains a space, `=`, `"`, or a control
+///   character. It's then wrapped in double quotes, with `"` and `\` escaped as `\"` and `\\`,
+///   newlines, carriage returns and tabs as `\n`, `\r` and `\t`, and other control characters as
+///   `\u00XX`.
+/// - Keys can't be quoted in logfmt: spaces, `=`, `"` and control characters in a key are
+///   replaced with `_`, and an empty key is written as `_`.
+///
+/// So every entry is on a single line, and parsers like `go-logfmt` read back the same values.
+///
+/// # Example
+///
+/// ```
+/// use loggery::{extensions::to_logfmt, info, Payload};
+/// use std::cell::RefCell;
+///
+/// thread_local! {
+///     static LAST_LINE: RefCell<String> = RefCell::new(String::new());
+/// }
+///
+/// fn my_extension(payload: &Payload) {
+///     LAST_LINE.with(|line| *line.borrow_mut() = to_logfmt(payload));
+/// }
+///
+/// fn main() {
+/// #   #[cfg(not(feature = "static"))]
+///     loggery::set_extension(my_extension);
+///
+///     info!("Said \"hi\"\n\tand left");
+///
+/// #   #[cfg(all(not(feature = "static"), not(feature = "min_level_off")))]
+///     LAST_LINE.with(|line| {
+///         assert!(line
+///             .borrow()
+///             .starts_with(r#"level=info msg="Said \"hi\"\n\tand left""#));
+///     });
+///
+/// #   #[cfg(feature = "kv")]
+/// #   {
+///     info!(user = "Ada Lovelace", id = 42; "Logged in");
+///
+/// #   #[cfg(all(not(feature = "static"), not(feature = "min_level_off")))]
+///     LAST_LINE.with(|line| {
+///         assert!(line
+///             .borrow()
+///             .starts_with(r#"level=info msg="Logged in" user="Ada Lovelace" id=42"#));
+///     });
+/// #   }
+/// }
+/// ```
+pub fn to_logfmt(payload: &Payload) -> String {
+    let mut line = String::from("level=");
+
+    for c in payload.level.name().chars() {
+        line.push(c.to_ascii_lowercase());
+    }
+
+    let mut value = String::new();
+
+    let _ = write!(value, "{}", payload.args);
+    push_pair(&mut line, "msg", &value);
+
+    #[cfg(feature = "kv")]
+    for (key, field) in payload.fields {
+        value.clear();
+        let _ = write!(value, "{}", field);
+        push_pair(&mut line, key, &value);
+    }
+
+    #[cfg(feature = "metadata")]
+    {
+        push_pair(&mut line, "module", payload.meta.module_path);
+        push_pair(&mut line, "file", payload.meta.file);
+        let _ = write!(line, " line={}", payload.meta.line);
+    }
+
+    #[cfg(feature = "timestamp")]
+    let _ = write!(line, " timestamp={}", payload.timestamp);
+
+    line
+}
+
+/// Appends a ` key=value` pair to a logfmt line, escaping both.
+fn push_pair(line: &mut String, key: &str, value: &str) {
+    line.push(' ');
+
+    if key.is_empty() {
+        line.push('_');
+    }
+
+    for c in key.chars() {
+        line.push(if needs_quotes(c) { '_' } else { c });
+    }
+
+    line.push('=');
+
+    if !value.is_empty() && !value.chars().any(needs_quotes) {
+        line.push_str(value);
+
+        return;
+    }
+
+    line.push('"');
+
+    for c in value.chars() {
+        match c {
+            '"' => line.push_str("\\\""),
+            '\\' => line.push_str("\\\\"),
+            '\n' => line.push_str("\\n"),
+            '\r' => line.push_str("\\r"),
+            '\t' => line.push_str("\\t"),
+            c if c.is_control() => {
+                let _ = write!(line, "\\u{:04x}", c as u32);
+            }
+            c => line.push(c),
+        }
+    }
+
+    line.push('"');
+}
+
+/// Returns `true` if a value containing `c` must be quoted.
+fn needs_quotes(c: char) -> bool {
+    c == ' ' || c == '=' || c == '"' || c.is_control()
+}
@@ -0,0 +1,100 @@
+extern crate std;
+
+use crate::Payload;
+
+use std::{io::Write as _, string::String};
+use tracing_appender::non_blocking::NonBlocking;
+
+/// Writes log entries to a [`tracing-appender`](tracing_appender) non-blocking writer
+/// (`tracing_appender` feature).
+///
+/// Logs are handed to the writer's background worker thread, so logging never waits on the disk,
+/// and the writer can wrap any of `tracing-appender`'s sinks, like its time-based
+/// [`RollingFileAppender`](tracing_appender::rolling::RollingFileAppender).
+///
+/// # Example
+///
+/// ```
+/// use loggery::{extensions::NonBlockingSink, info, Payload};
+/// use std::cell::RefCell;
+///
+/// thread_local! {
+///     static SINK: RefCell<Option<NonBlockingSink>> = RefCell::new(None);
+/// }
+///
+/// fn my_extension(payload: &Payload) {
+///     SINK.with(|sink| {
+///         if let Some(sink) = sink.borrow().as_ref() {
+///             let _ = sink.log(payload);
+///         }
+///     });
+/// }
+///
+/// fn main() {
+///     let path = std::env::temp_dir().join("loggery_non_blocking_doctest.log");
+///     let _ = std::fs::remove_file(&path);
+///
+///     let file = std::fs::File::create(&path).unwrap();
+///     let (writer, guard) = tracing_appender::non_blocking(file);
+///
+///     SINK.with(|sink| *sink.borrow_mut() = Some(NonBlockingSink::new(writer)));
+///
+/// #   #[cfg(not(feature = "static"))]
+///     loggery::set_extension(my_extension);
+///
+///     info!("Written by the worker thread");
+///
+///     // Dropping the guard waits for the pending logs to be written
+///     drop(guard);
+///
+/// #   #[cfg(all(not(feature = "static"), not(any(feature = "min_level_warn",
+/// #       feature = "min_level_error", feature = "min_level_off")), not(feature = "thread_meta"),
+/// #       not(feature = "cpu_meta")))]
+///     assert!(std::fs::read_to_string(&path)
+///         .unwrap()
+///         .ends_with("[ INFO] Written by the worker thread\n"));
+/// }
+/// ```
+///
+/// # Format
+///
+/// Logs are written in the format: `[LEVEL] message`
+///
+/// With the `timestamp` feature, the time is prepended in the format set with
+/// `set_timestamp_format`: `timestamp [LEVEL] message`
+///
+/// A formatter set with [`set_global_formatter`](crate::set_global_formatter) replaces this format.
+///
+/// # `WorkerGuard`
+///
+/// [`tracing_appender::non_blocking`] returns the writer together with a
+/// [`WorkerGuard`](tracing_appender::non_blocking::WorkerGuard). The guard must be kept alive for
+/// as long as logs are written (usually by binding it in `main`): dropping it flushes the pending
+/// logs and stops the worker thread, after which logs are silently lost. Don't bind it to `_`,
+/// which drops it immediately.
+///
+/// Logs are also lost if the writer's buffer is full and it was built as lossy (the default), see
+/// [`NonBlockingBuilder::lossy`](tracing_appender::non_blocking::NonBlockingBuilder::lossy).
+pub struct NonBlockingSink {
+    writer: NonBlocking,
+}
+
+impl NonBlockingSink {
+    /// Creates a sink writing to `writer`.
+    pub fn new(writer: NonBlocking) -> Self {
+        Self { writer }
+    }
+
+    /// Sends a log entry to the worker thread.
+    pub fn log(&self, payload: &Payload) -> std::io::Result<()> {
+        use core::fmt::Write as _;
+
+        let mut line = String::new();
+
+        let _ = writeln!(line, "{}", crate::format::Line::timestamped(payload));
+
+        // Every write is a separate message to the worker, so the line is sent in one piece. The
+        // writer is a cheap handle to the worker's channel, cloning it avoids a lock.
+        self.writer.clone().write_all(line.as_bytes())
+    }
+}
@@ -0,0 +1,243 @@
+extern crate std;
+
+use crate::{LoggerFn, Payload};
+
+use core::fmt::Write as _;
+use std::{borrow::ToOwned, string::String, vec::Vec};
+
+/// The text replacing the redacted parts of a log.
+const MASK: &str = "****";
+
+/// Wraps a logger, masking sensitive parts of the messages with `****` before it writes them
+/// (`std` feature).
+///
+/// Rules are added with [`mask`](RedactingLogger::mask) (masks the text itself) and
+/// [`mask_after`](RedactingLogger::mask_after) (masks the value following a prefix, e.g.,
+/// `password=` or `Bearer `), and applied in the order they were added.
+///
+/// # Best effort
+///
+/// Redaction is a safety net, not a guarantee: the message is rendered and scanned for the
+/// configured texts only, a secret that doesn't follow one of them (e.g., `password: hunter2`
+/// with a `password=` rule) goes through. Matching is case-sensitive unless
+/// [`ignore_case`](RedactingLogger::ignore_case) is set, which only folds ASCII letters.
+///
+/// Only the message is scanned. With the `kv` feature, the value of a field is masked as a whole
+/// when its key was added with `mask_field`, the other fields are
+/// passed as is. Extensions see the logs before the wrapper, unredacted.
+///
+/// # Performance
+///
+/// Every log is rendered to a `String` and scanned once per rule, on top of the wrapped logger's
+/// own formatting.
+///
+/// # Example
+///
+/// ```
+/// use loggery::{extensions::RedactingLogger, info, Payload};
+/// use std::cell::RefCell;
+///
+/// thread_local! {
+///     static LAST_MESSAGE: RefCell<String> = RefCell::new(String::new());
+///
+///     static REDACTING: RedactingLogger = RedactingLogger::new(inner_logger)
+///         .mask_after("password=")
+///         .mask_after("Bearer ")
+///         .ignore_case(true);
+/// }
+///
+/// fn inner_logger(payload: Payload) {
+///     LAST_MESSAGE.with(|message| *message.borrow_mut() = payload.args.to_string());
+/// }
+///
+/// fn my_logger(payload: Payload) {
+///     REDACTING.with(|redacting| redacting.log(payload));
+/// }
+///
+/// fn main() {
+/// #   #[cfg(not(feature = "static"))]
+///     loggery::set_logger(my_logger);
+///
+///     info!("Login with PASSWORD=hunter2, header bearer abc.def.ghi");
+///
+/// #   #[cfg(all(not(feature = "static"), not(feature = "min_level_off")))]
+///     LAST_MESSAGE.with(|message| {
+///         assert_eq!(*message.borrow(), "Login with PASSWORD=****, header bearer ****");
+///     });
+/// }
+/// ```
+pub struct RedactingLogger {
+    logger: LoggerFn,
+    rules: Vec<Rule>,
+    #[cfg(feature = "kv")]
+    fields: Vec<String>,
+    ignore_case: bool,
+}
+
+/// A redaction rule.
+enum Rule {
+    /// Masks the text.
+    Text(String),
+    /// Masks the value following the text.
+    After(String),
+}
+
+impl RedactingLogger {
+    /// Creates a wrapper around `logger`, without any rule.
+    pub fn new(logger: LoggerFn) -> Self {
+        Self {
+            logger,
+            rules: Vec::new(),
+            #[cfg(feature = "kv")]
+            fields: Vec::new(),
+            ignore_case: false,
+        }
+    }
+
+    /// Masks every occurrence of `text`, e.g., a known API key.
+    ///
+    /// An empty `text` is ignored.
+    pub fn mask(mut self, text: &str) -> Self {
+        if !text.is_empty() {
+            self.rules.push(Rule::Text(text.to_owned()));
+        }
+
+        self
+    }
+
+    /// Masks the value following every occurrence of `prefix`, e.g., `password=` or `Bearer `,
+    /// keeping the prefix itself.
+    ///
+    /// The value runs up to the next whitespace, `,`, `;`, `&`, `"` or `'` (or the end of the
+    /// message). An empty `prefix` is ignored.
+    pub fn mask_after(mut self, prefix: &str) -> Self {
+        if !prefix.is_empty() {
+            self.rules.push(Rule::After(prefix.to_owned()));
+        }
+
+        self
+    }
+
+    /// Masks the whole value of the fields named `key` (`kv` feature).
+    #[cfg(feature = "kv")]
+    pub fn mask_field(mut self, key: &str) -> Self {
+        self.fields.push(key.to_owned());
+
+        self
+    }
+
+    /// Sets whether the rules (and the field keys) match regardless of the ASCII case, `false` by
+    /// default.
+    pub fn ignore_case(mut self, ignore_case: bool) -> Self {
+        self.ignore_case = ignore_case;
+
+        self
+    }
+
+    /// Returns the `message` with the rules applied.
+    pub fn redact(&self, message: &str) -> String {
+        let mut redacted = message.to_owned();
+
+        for rule in &self.rules {
+            redacted = match rule {
+                Rule::Text(text) => self.replace(&redacted, text, false),
+                Rule::After(prefix) => self.replace(&redacted, prefix, true),
+            };
+        }
+
+        redacted
+    }
+
+    /// Renders the message of the log, redacts it and passes the log to the wrapped logger.
+    pub fn log(&self, payload: Payload) {
+        let mut message = String::new();
+        let _ = write!(message, "{}", payload.args);
+
+        let message = self.redact(&message);
+
+        #[cfg(feature = "kv")]
+        let fields: Vec<_> = payload
+            .fields
+            .iter()
+            .map(|&(key, value)| {
+                if self.is_masked_field(key) {
+                    (key, format_args!("****"))
+                } else {
+                    (key, value)
+                }
+            })
+            .collect();
+
+        (self.logger)(Payload {
+            args: format_args!("{}", message),
+            #[cfg(feature = "kv")]
+            fields: &fields,
+            ..payload
+        });
+    }
+
+    /// Returns `true` if the values of the fields named `key` are masked.
+    #[cfg(feature = "kv")]
+    fn is_masked_field(&self, key: &str) -> bool {
+        self.fields.iter().any(|field| self.matches(key, field))
+    }
+
+    /// Copies `message`, masking every occurrence of `text`, or the value following it with
+    /// `after`.
+    fn replace(&self, message: &str, text: &str, after: bool) -> String {
+        let mut redacted = String::with_capacity(message.len());
+        let mut rest = message;
+
+        while let Some(start) = self.find(rest, text) {
+            let end = start + text.len();
+
+            if after {
+                let value = &rest[end..];
+                let len = value.find(is_value_end).unwrap_or(value.len());
+
+                redacted.push_str(&rest[..end]);
+
+                if len > 0 {
+                    redacted.push_str(MASK);
+                }
+
+                rest = &value[len..];
+            } else {
+                redacted.push_str(&rest[..start]);
+                redacted.push_str(MASK);
+                rest = &rest[end..];
+            }
+        }
+
+        redacted.push_str(rest);
+        redacted
+    }
+
+    /// Returns the byte index of the first occurrence of `text` in `haystack`.
+    fn find(&self, haystack: &str, text: &str) -> Option<usize> {
+        if !self.ignore_case {
+            return haystack.find(text);
+        }
+
+        // A match starts with the same byte as `text` (up to the ASCII case), so it starts on a
+        // char boundary, and ends on one for the same reason.
+        (0..=haystack.len().checked_sub(text.len())?).find(|&start| {
+            haystack.as_bytes()[start..start + text.len()].eq_ignore_ascii_case(text.as_bytes())
+        })
+    }
+
+    /// Returns `true` if `text` matches `pattern`, up to the ASCII case with `ignore_case`.
+    #[cfg(feature = "kv")]
+    fn matches(&self, text: &str, pattern: &str) -> bool {
+        if self.ignore_case {
+            text.eq_ignore_ascii_case(pattern)
+        } else {
+            text == pattern
+        }
+    }
+}
+
+/// Returns `true` if `c` ends a value masked by [`RedactingLogger::mask_after`].
+fn is_value_end(c: char) -> bool {
+    c.is_whitespace() || matches!(c, ',' | ';' | '&' | '"' | '\'')
+}
@@ -0,0 +1,57 @@
+use crate::Level;
+
+/// Logs an [`anyhow::Error`] with its whole context chain (`anyhow` + `extension` features, `std`
+/// only).
+///
+/// The error is rendered with its alternate `Display` format (`{:#}`), which lists the contexts
+/// added with [`anyhow::Context`] down to the root cause on a single line:
+/// `outer context: inner context: root cause`. A plain `{}` would only render the outermost
+/// context.
+///
+/// With the `metadata` feature, the log is attributed to this module rather than to the caller:
+/// use `error!("{:#}", err)` directly to keep the call site.
+///
+/// # Example
+///
+/// ```
+/// use anyhow::Context as _;
+/// use loggery::{extensions::log_report, Level, Payload};
+/// use std::cell::RefCell;
+///
+/// thread_local! {
+///     static LAST: RefCell<String> = RefCell::new(String::new());
+/// }
+///
+/// fn my_logger(payload: Payload) {
+///     LAST.with(|last| *last.borrow_mut() = payload.args.to_string());
+/// }
+///
+/// fn load_config() -> anyhow::Result<String> {
+///     std::fs::read_to_string("/nonexistent/app.toml").context("Failed to read the config file")
+/// }
+///
+/// fn main() {
+/// #   #[cfg(not(feature = "static"))]
+///     loggery::set_logger(my_logger);
+///
+///     let err = load_config().context("Failed to start").unwrap_err();
+///
+///     log_report(Level::Error, &err);
+///
+/// #   #[cfg(all(not(feature = "static"), not(feature = "min_level_off")))]
+///     LAST.with(|last| {
+///         let last = last.borrow();
+///
+///         assert!(last.starts_with("Failed to start: Failed to read the config file: "));
+///     });
+/// }
+/// ```
+///
+/// # Dependency
+///
+/// The `anyhow` feature adds the [`anyhow`](https://docs.rs/anyhow) dependency, which requires
+/// the standard library: this extension isn't available without the `std` feature.
+#[inline]
+pub fn log_report(level: Level, err: &anyhow::Error) {
+    crate::log!(level, "{:#}", err);
+}
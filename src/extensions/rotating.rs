@@ -0,0 +1,197 @@
+extern crate std;
+
+use crate::Payload;
+
+use std::{
+    fs::File,
+    io::{BufWriter, Write as _},
+    string::String,
+    sync::Mutex,
+};
+
+/// Appends log entries to a file, rotating it when it reaches a maximum size (`std` feature).
+///
+/// When writing a log would make `app.log` larger than `max_size` bytes, the file is rotated:
+/// `app.log.1` is renamed to `app.log.2`, `app.log` to `app.log.1`, and so on, and a new `app.log`
+/// is created. At most `max_files` rotated files are kept, the oldest one is deleted. With a
+/// `max_files` of `0`, no rotated file is kept and `app.log` is simply restarted.
+///
+/// A single log larger than `max_size` is still written (to a fresh file), so a file can exceed
+/// `max_size` only when it holds that one log.
+///
+/// Like [`FileLogger`](super::FileLogger), the file is opened once and logs are buffered according
+/// to their level's [`FlushPolicy`](crate::FlushPolicy). Logs are never split between two files.
+///
+/// # Exit
+///
+/// Logs below [`warn!`](crate::warn) stay buffered until the buffer is full, the file is rotated,
+/// [`flush`](RotatingFileLogger::flush) is called or the logger is dropped. A logger that's never
+/// dropped (e.g., in a `static`, or a thread-local of the main thread) must be flushed before the
+/// program exits, or its last lines are silently lost: register a function flushing it with
+/// [`set_flush`](crate::set_flush), and call [`flush`](crate::flush()) on the way out (or install
+/// `install_atexit_flush`).
+///
+/// # Example
+///
+/// ```
+/// use loggery::{extensions::RotatingFileLogger, warn, Payload};
+///
+/// thread_local! {
+///     static LOGGER: RotatingFileLogger = RotatingFileLogger::open(&path(), 32, 2).unwrap();
+/// }
+///
+/// fn path() -> String {
+///     let path = std::env::temp_dir().join("loggery_rotating_doctest.log");
+///     path.to_string_lossy().into_owned()
+/// }
+///
+/// fn my_extension(payload: &Payload) {
+///     LOGGER.with(|logger| {
+///         let _ = logger.log(payload);
+///     });
+/// }
+///
+/// fn flush_logger() {
+///     LOGGER.with(|logger| {
+///         let _ = logger.flush();
+///     });
+/// }
+///
+/// fn main() {
+///     for suffix in &["", ".1", ".2", ".3"] {
+///         let _ = std::fs::remove_file(format!("{}{}", path(), suffix));
+///     }
+///
+/// #   #[cfg(not(feature = "static"))]
+///     loggery::set_extension(my_extension);
+///     loggery::set_flush(flush_logger);
+///
+///     // Every log is 18 bytes, so every file holds one of them
+///     warn!("First  log");
+///     warn!("Second log");
+///     warn!("Third  log");
+///     warn!("Fourth log");
+///
+///     // Before exiting, for the logs still buffered (none here, `warn!` logs are flushed)
+///     loggery::flush();
+///
+/// #   #[cfg(all(not(feature = "static"), not(feature = "min_level_off"),
+/// #       not(feature = "timestamp"), not(feature = "thread_meta"), not(feature = "cpu_meta")))]
+/// #   {
+///     let read = |suffix: &str| std::fs::read_to_string(format!("{}{}", path(), suffix));
+///
+///     assert_eq!(read("").unwrap(), "[ WARN] Fourth log\n");
+///     assert_eq!(read(".1").unwrap(), "[ WARN] Third  log\n");
+///     assert_eq!(read(".2").unwrap(), "[ WARN] Second log\n");
+///     // Only 2 rotated files are kept
+///     assert!(read(".3").is_err());
+/// #   }
+/// }
+/// ```
+///
+/// # Format
+///
+/// Logs are written in the format: `[LEVEL] message`
+///
+/// With the `timestamp` feature, the time is prepended in the format set with
+/// `set_timestamp_format`: `timestamp [LEVEL] message`
+///
+/// A formatter set with [`set_global_formatter`](crate::set_global_formatter) replaces this format.
+pub struct RotatingFileLogger {
+    state: Mutex<State>,
+    path: String,
+    max_size: u64,
+    max_files: usize,
+}
+
+struct State {
+    writer: BufWriter<File>,
+    size: u64,
+}
+
+impl RotatingFileLogger {
+    /// Opens (or creates) the file at `path` in append mode, rotating it when it would exceed
+    /// `max_size` bytes and keeping up to `max_files` rotated files.
+    pub fn open(path: &str, max_size: u64, max_files: usize) -> std::io::Result<Self> {
+        Ok(Self {
+            state: Mutex::new(open_state(path)?),
+            path: String::from(path),
+            max_size,
+            max_files,
+        })
+    }
+
+    /// Appends a log entry, rotating the file first if the entry doesn't fit in it.
+    pub fn log(&self, payload: &Payload) -> std::io::Result<()> {
+        use core::fmt::Write as _;
+
+        let mut line = String::new();
+
+        let _ = writeln!(line, "{}", crate::format::Line::timestamped(payload));
+
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        let len = line.len() as u64;
+
+        // An empty file is never rotated, so a log larger than `max_size` is written anyway
+        if state.size > 0 && state.size + len > self.max_size {
+            state.writer.flush()?;
+            self.rotate()?;
+            *state = open_state(&self.path)?;
+        }
+
+        state.writer.write_all(line.as_bytes())?;
+        state.size += len;
+
+        if crate::get_flush_policy(payload.level) == crate::FlushPolicy::Immediate {
+            state.writer.flush()?;
+        }
+
+        Ok(())
+    }
+
+    /// Writes the buffered log entries to the file.
+    pub fn flush(&self) -> std::io::Result<()> {
+        self.state
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .writer
+            .flush()
+    }
+
+    /// Shifts the rotated files by one and moves the current file to `path.1`.
+    fn rotate(&self) -> std::io::Result<()> {
+        if self.max_files == 0 {
+            return std::fs::remove_file(&self.path);
+        }
+
+        let rotated = |index: usize| std::format!("{}.{}", self.path, index);
+
+        match std::fs::remove_file(rotated(self.max_files)) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => return Err(e),
+            _ => {}
+        }
+
+        for index in (1..self.max_files).rev() {
+            match std::fs::rename(rotated(index), rotated(index + 1)) {
+                Err(e) if e.kind() != std::io::ErrorKind::NotFound => return Err(e),
+                _ => {}
+            }
+        }
+
+        std::fs::rename(&self.path, rotated(1))
+    }
+}
+
+/// Opens the file at `path` in append mode, along with its current size.
+fn open_state(path: &str) -> std::io::Result<State> {
+    let file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)?;
+    let size = file.metadata()?.len();
+
+    Ok(State {
+        writer: BufWriter::new(file),
+        size,
+    })
+}
@@ -0,0 +1,467 @@
+extern crate std;
+
+use crate::Payload;
+
+use core::sync::atomic::{fence, AtomicU32, AtomicU64, Ordering};
+use std::{ffi::CString, io, string::String, vec::Vec};
+
+/// Marks a ring as initialized ("LOGGERY1" in ASCII).
+const MAGIC: u64 = 0x4c4f_4747_4552_5931;
+/// Size of the ring header, the data area starts right after it.
+const HEADER_SIZE: usize = 64;
+/// Size of a record header, records are aligned to it.
+const RECORD_HEADER_SIZE: usize = 16;
+/// Length of a padding record, filling the end of the ring when a record doesn't fit there.
+const PADDING: u32 = u32::MAX;
+/// Smallest data area of a ring.
+const MIN_CAPACITY: usize = 1024;
+
+/// Header at the start of the shared memory region.
+#[repr(C)]
+struct Header {
+    /// [`MAGIC`] once the ring is initialized.
+    magic: AtomicU64,
+    /// Size of the data area in bytes, a multiple of [`RECORD_HEADER_SIZE`].
+    capacity: AtomicU64,
+    /// Number of bytes reserved by writers since the creation, a record at position `p` starts
+    /// at `p % capacity` in the data area.
+    write_cursor: AtomicU64,
+}
+
+/// Header of a record in the data area, followed by the entry.
+#[repr(C)]
+struct RecordHeader {
+    /// Position of the record (in bytes since the creation) plus one, once it's written.
+    tag: AtomicU64,
+    /// Length of the entry in bytes, or [`PADDING`].
+    len: AtomicU32,
+}
+
+/// Writes log entries to a ring buffer in POSIX shared memory, shared by several processes
+/// (`shm` feature, unix only).
+///
+/// For multi-process apps where workers log and a coordinator persists the logs: the coordinator
+/// [`create`](ShmSink::create)s the ring before starting the workers, which
+/// [`open`](ShmSink::open) it by name, and reads the entries with a [`ShmReader`]. Writers never
+/// block each other or wait for readers: once the ring is full, new entries overwrite the oldest.
+///
+/// # Example
+///
+/// Two workers (threads here, processes in practice) writing, one coordinator reading:
+///
+/// ```
+/// use loggery::{extensions::ShmSink, info, Payload};
+/// use std::cell::RefCell;
+///
+/// thread_local! {
+///     static WORKER_SINK: RefCell<Option<ShmSink>> = RefCell::new(None);
+/// }
+///
+/// fn my_extension(payload: &Payload) {
+///     WORKER_SINK.with(|sink| {
+///         if let Some(sink) = &*sink.borrow() {
+///             sink.log(payload);
+///         }
+///     });
+/// }
+///
+/// fn main() {
+///     let name = format!("/loggery_doctest_{}", std::process::id());
+///
+///     // Coordinator
+///     let coordinator = ShmSink::create(&name, 64 * 1024).unwrap();
+///     let mut reader = coordinator.reader();
+///
+/// #   #[cfg(not(feature = "static"))]
+///     loggery::set_extension(my_extension);
+///
+///     let workers: Vec<_> = (1..=2)
+///         .map(|worker| {
+///             let name = name.clone();
+///
+///             std::thread::spawn(move || {
+///                 let sink = ShmSink::open(&name).unwrap();
+///                 WORKER_SINK.with(|worker_sink| *worker_sink.borrow_mut() = Some(sink));
+///
+///                 for job in 0..3 {
+///                     info!("Worker {} finished job {}", worker, job);
+///                 }
+///             })
+///         })
+///         .collect();
+///
+///     for worker in workers {
+///         worker.join().unwrap();
+///     }
+///
+///     let entries: Vec<String> = reader.by_ref().collect();
+///
+/// #   #[cfg(all(not(feature = "static"), not(feature = "min_level_off")))]
+/// #   {
+///     assert_eq!(entries.len(), 6);
+///     assert!(entries.iter().any(|entry| entry.ends_with("Worker 2 finished job 1")));
+///     assert_eq!(reader.overruns(), 0);
+/// #   }
+///
+///     ShmSink::unlink(&name).unwrap();
+/// }
+/// ```
+///
+/// # Layout
+///
+/// The region starts with a 64 bytes header: a magic number (set last, once the ring is
+/// initialized), the capacity of the data area and the write cursor, all native-endian `u64`s.
+/// The data area follows, holding records aligned to 16 bytes. Each record has a 16 bytes
+/// header, a `u64` tag (the position of the record plus one, once it's written) and a `u32`
+/// length, followed by the entry in UTF-8. A record never wraps around the end of the data area:
+/// the remaining bytes are filled with a padding record instead.
+///
+/// # Synchronization
+///
+/// Writers reserve the space of their record by advancing the shared write cursor atomically, so
+/// any number of writers (threads or processes) can write concurrently without a lock. A record is
+/// published by storing its tag last: readers stop at the first record that isn't published yet,
+/// so entries are read in reservation order. A writer that dies between reserving and
+/// publishing stalls readers until the ring wraps around.
+///
+/// # Readers
+///
+/// Readers are separate from writers and from each other: each [`ShmReader`] keeps its own
+/// position in its own process. A reader that falls more than a full ring behind loses the
+/// overwritten entries, it skips to the newest entry and counts an
+/// [`overrun`](ShmReader::overruns).
+///
+/// # Format
+///
+/// Logs are written in the format: `[LEVEL] message` (without a trailing newline)
+///
+/// With the `timestamp` feature, the time is prepended in the format set with
+/// `set_timestamp_format`: `timestamp [LEVEL] message`
+///
+/// A formatter set with [`set_global_formatter`](crate::set_global_formatter) replaces this format.
+/// Entries longer than a quarter of the capacity are truncated.
+pub struct ShmSink {
+    ptr: *mut u8,
+    len: usize,
+}
+
+// SAFETY: the shared memory is only accessed through atomics, or by the writer that reserved it.
+unsafe impl Send for ShmSink {}
+// SAFETY: see `Send`.
+unsafe impl Sync for ShmSink {}
+
+impl ShmSink {
+    /// Creates the shared memory region `name` (e.g., `/my_app_logs`) with a data area of
+    /// `capacity` bytes (rounded up to a multiple of 16, and at least 1 KiB).
+    ///
+    /// Fails if a region with this name already exists, see [`unlink`](ShmSink::unlink).
+    pub fn create(name: &str, capacity: usize) -> io::Result<Self> {
+        let capacity = align(capacity.max(MIN_CAPACITY));
+        let len = HEADER_SIZE + capacity;
+        let sink = Self::map(name, libc::O_CREAT | libc::O_EXCL | libc::O_RDWR, Some(len))?;
+
+        let header = sink.header();
+        header.capacity.store(capacity as u64, Ordering::Relaxed);
+        header.write_cursor.store(0, Ordering::Relaxed);
+        header.magic.store(MAGIC, Ordering::Release);
+
+        Ok(sink)
+    }
+
+    /// Opens the shared memory region `name`, created by [`create`](ShmSink::create) (usually in
+    /// another process).
+    pub fn open(name: &str) -> io::Result<Self> {
+        let sink = Self::map(name, libc::O_RDWR, None)?;
+        let header = sink.header();
+
+        let is_valid = header.magic.load(Ordering::Acquire) == MAGIC
+            && header.capacity.load(Ordering::Relaxed) == (sink.len - HEADER_SIZE) as u64;
+
+        if !is_valid {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "not an initialized loggery ring",
+            ));
+        }
+
+        Ok(sink)
+    }
+
+    /// Removes the shared memory region `name`. Processes that mapped it keep using it until they
+    /// drop their sink.
+    pub fn unlink(name: &str) -> io::Result<()> {
+        let name = c_name(name)?;
+
+        // SAFETY: `name` is a valid C string.
+        if unsafe { libc::shm_unlink(name.as_ptr()) } != 0 {
+            return Err(io::Error::last_os_error());
+        }
+
+        Ok(())
+    }
+
+    /// Formats a log entry and writes it to the ring.
+    pub fn log(&self, payload: &Payload) {
+        use core::fmt::Write as _;
+
+        let mut entry = String::new();
+        let _ = write!(entry, "{}", crate::format::Line::timestamped(payload));
+
+        self.write(entry.as_bytes());
+    }
+
+    /// Returns a reader of the entries, starting at the oldest one still in the ring.
+    pub fn reader(&self) -> ShmReader<'_> {
+        let write_cursor = self.header().write_cursor.load(Ordering::Acquire);
+
+        // Before the first wrap around, every record since the creation is still there
+        let position = if write_cursor <= self.capacity() as u64 {
+            0
+        } else {
+            write_cursor
+        };
+
+        ShmReader {
+            sink: self,
+            position,
+            overruns: 0,
+        }
+    }
+
+    /// Maps the region `name`, resizing it to `len` bytes if given.
+    fn map(name: &str, flags: std::os::raw::c_int, len: Option<usize>) -> io::Result<Self> {
+        let name = c_name(name)?;
+
+        // SAFETY: `name` is a valid C string.
+        let fd = unsafe { libc::shm_open(name.as_ptr(), flags, 0o600 as libc::mode_t) };
+
+        if fd < 0 {
+            return Err(io::Error::last_os_error());
+        }
+
+        let result = map_fd(fd, len);
+
+        // SAFETY: `fd` is open, the mapping (if any) stays valid after closing it.
+        unsafe { libc::close(fd) };
+
+        let (ptr, len) = result?;
+
+        Ok(Self { ptr, len })
+    }
+
+    fn header(&self) -> &Header {
+        // SAFETY: the region starts with a `Header`, and is page-aligned.
+        unsafe { &*(self.ptr as *const Header) }
+    }
+
+    fn capacity(&self) -> usize {
+        self.len - HEADER_SIZE
+    }
+
+    /// Returns the header of the record at `position`.
+    fn record(&self, position: u64) -> &RecordHeader {
+        let offset = HEADER_SIZE + (position % self.capacity() as u64) as usize;
+
+        // SAFETY: records are aligned to 16 bytes and fit in the data area.
+        unsafe { &*(self.ptr.add(offset) as *const RecordHeader) }
+    }
+
+    /// Returns a pointer to the entry of the record at `position`.
+    fn entry(&self, position: u64) -> *mut u8 {
+        let offset = HEADER_SIZE + (position % self.capacity() as u64) as usize;
+
+        // SAFETY: the record is in the data area.
+        unsafe { self.ptr.add(offset + RECORD_HEADER_SIZE) }
+    }
+
+    /// Reserves a record and writes `entry` to it.
+    fn write(&self, entry: &[u8]) {
+        let capacity = self.capacity() as u64;
+        let entry = &entry[..entry.len().min(self.capacity() / 4)];
+        let size = align(RECORD_HEADER_SIZE + entry.len()) as u64;
+        let write_cursor = &self.header().write_cursor;
+
+        let mut start = write_cursor.load(Ordering::Relaxed);
+
+        let (padding, position) = loop {
+            let remaining = capacity - start % capacity;
+            let padding = if size > remaining { remaining } else { 0 };
+
+            match write_cursor.compare_exchange_weak(
+                start,
+                start + padding + size,
+                Ordering::AcqRel,
+                Ordering::Relaxed,
+            ) {
+                Ok(_) => break (padding, start + padding),
+                Err(current) => start = current,
+            }
+        };
+
+        if padding > 0 {
+            let record = self.record(start);
+            record.len.store(PADDING, Ordering::Relaxed);
+            record.tag.store(start + 1, Ordering::Release);
+        }
+
+        let record = self.record(position);
+
+        // SAFETY: the space was reserved above, and `entry` fits in it.
+        unsafe {
+            core::ptr::copy_nonoverlapping(entry.as_ptr(), self.entry(position), entry.len())
+        };
+
+        record.len.store(entry.len() as u32, Ordering::Relaxed);
+        record.tag.store(position + 1, Ordering::Release);
+    }
+}
+
+impl Drop for ShmSink {
+    fn drop(&mut self) {
+        // SAFETY: `ptr` and `len` come from a successful `mmap`.
+        unsafe { libc::munmap(self.ptr as *mut libc::c_void, self.len) };
+    }
+}
+
+/// Reads the entries of a [`ShmSink`] in the order they were reserved, see
+/// [`ShmSink::reader`].
+///
+/// It's an [`Iterator`] returning `None` when it caught up with the writers, so it can be polled
+/// again later for new entries.
+pub struct ShmReader<'a> {
+    sink: &'a ShmSink,
+    position: u64,
+    overruns: u64,
+}
+
+impl ShmReader<'_> {
+    /// Returns how many times the reader fell more than a full ring behind the writers, losing
+    /// entries.
+    pub fn overruns(&self) -> u64 {
+        self.overruns
+    }
+
+    /// Skips to the newest entry if the writers overwrote the record at the current position.
+    fn is_overrun(&mut self) -> bool {
+        let write_cursor = self.sink.header().write_cursor.load(Ordering::Acquire);
+
+        if write_cursor > self.position + self.sink.capacity() as u64 {
+            self.position = write_cursor;
+            self.overruns += 1;
+
+            return true;
+        }
+
+        false
+    }
+}
+
+impl Iterator for ShmReader<'_> {
+    type Item = String;
+
+    fn next(&mut self) -> Option<String> {
+        loop {
+            let record = self.sink.record(self.position);
+
+            if record.tag.load(Ordering::Acquire) != self.position + 1 {
+                if self.is_overrun() {
+                    continue;
+                }
+
+                // Not written yet
+                return None;
+            }
+
+            let len = record.len.load(Ordering::Relaxed);
+
+            if len == PADDING {
+                let capacity = self.sink.capacity() as u64;
+                self.position += capacity - self.position % capacity;
+
+                continue;
+            }
+
+            let mut entry = Vec::with_capacity(len as usize);
+
+            // SAFETY: the record is in the data area, and its entry is `len` bytes long.
+            unsafe {
+                core::ptr::copy_nonoverlapping(
+                    self.sink.entry(self.position),
+                    entry.as_mut_ptr(),
+                    len as usize,
+                );
+                entry.set_len(len as usize);
+            }
+
+            // The record may have been overwritten while copying it
+            fence(Ordering::Acquire);
+
+            if self.is_overrun() {
+                continue;
+            }
+
+            self.position += align(RECORD_HEADER_SIZE + len as usize) as u64;
+
+            return Some(String::from_utf8_lossy(&entry).into_owned());
+        }
+    }
+}
+
+/// Rounds `n` up to a multiple of the record alignment.
+fn align(n: usize) -> usize {
+    (n + RECORD_HEADER_SIZE - 1) / RECORD_HEADER_SIZE * RECORD_HEADER_SIZE
+}
+
+fn c_name(name: &str) -> io::Result<CString> {
+    CString::new(name).map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))
+}
+
+/// Maps the shared memory object `fd`, resizing it to `len` bytes if given.
+fn map_fd(fd: std::os::raw::c_int, len: Option<usize>) -> io::Result<(*mut u8, usize)> {
+    let len = match len {
+        Some(len) => {
+            // SAFETY: `fd` is open.
+            if unsafe { libc::ftruncate(fd, len as libc::off_t) } != 0 {
+                return Err(io::Error::last_os_error());
+            }
+
+            len
+        }
+        None => {
+            // SAFETY: `stat` is plain data, filled by `fstat`.
+            let mut stat: libc::stat = unsafe { core::mem::zeroed() };
+
+            // SAFETY: `fd` is open and `stat` is valid.
+            if unsafe { libc::fstat(fd, &mut stat) } != 0 {
+                return Err(io::Error::last_os_error());
+            }
+
+            stat.st_size as usize
+        }
+    };
+
+    if len < HEADER_SIZE + MIN_CAPACITY {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "not an initialized loggery ring",
+        ));
+    }
+
+    // SAFETY: mapping a shared memory object of `len` bytes.
+    let ptr = unsafe {
+        libc::mmap(
+            core::ptr::null_mut(),
+            len,
+            libc::PROT_READ | libc::PROT_WRITE,
+            libc::MAP_SHARED,
+            fd,
+            0,
+        )
+    };
+
+    if ptr == libc::MAP_FAILED {
+        return Err(io::Error::last_os_error());
+    }
+
+    Ok((ptr as *mut u8, len))
+}
@@ -0,0 +1,120 @@
+extern crate std;
+
+use crate::{Level, Payload};
+
+use core::fmt::Write as _;
+use std::string::String;
+
+/// Formats a log entry as an RFC 5424 syslog message (`std` feature).
+///
+/// The message has the form `<PRI>1 TIMESTAMP HOSTNAME APP-NAME PROCID MSGID SD MSG`:
+///
+/// ```text
+/// <134>1 2023-11-14T22:13:20.123Z - my_app 4242 - - Server started
+/// ```
+///
+/// - `PRI` is `facility * 8 + severity` (e.g., `16` for `local0`), with the severity mapped from
+///   the level:
+///
+///   | Level   | Severity          |
+///   |---------|-------------------|
+///   | `Error` | 3 (Error)         |
+///   | `Warn`  | 4 (Warning)       |
+///   | `Info`  | 6 (Informational) |
+///   | `Debug` | 7 (Debug)         |
+///   | `Trace` | 7 (Debug)         |
+///
+///   Facilities above `23` are clamped to `23` (`local7`).
+/// - `TIMESTAMP` is the UTC time with the `timestamp` feature, `-` (no value) otherwise.
+/// - `HOSTNAME` is always `-`, the syslog daemon (or relay) fills it in.
+/// - `APP-NAME` is the crate of the log call (the first segment of its module path) with the
+///   `metadata` feature, `-` otherwise. `PROCID` is the process ID, `MSGID` and `SD` (structured
+///   data) are `-`.
+/// - `MSG` is the message, followed by the ` key=value` fields with the `kv` feature.
+///
+/// This only formats the message: sending it to the daemon (over a Unix socket, UDP or TCP) is
+/// up to the extension.
+///
+/// # Example
+///
+/// ```
+/// use loggery::{extensions::to_syslog, info, Payload};
+/// use std::cell::RefCell;
+///
+/// /// The `user` facility
+/// const USER: u8 = 1;
+///
+/// thread_local! {
+///     static LAST_MESSAGE: RefCell<String> = RefCell::new(String::new());
+/// }
+///
+/// fn my_extension(payload: &Payload) {
+///     // E.g., sent to the daemon with a `UnixDatagram`
+///     LAST_MESSAGE.with(|message| *message.borrow_mut() = to_syslog(payload, USER));
+/// }
+///
+/// fn main() {
+/// #   #[cfg(not(feature = "static"))]
+///     loggery::set_extension(my_extension);
+///
+///     info!("Server started");
+///
+/// #   #[cfg(all(not(feature = "static"), not(feature = "min_level_off")))]
+///     LAST_MESSAGE.with(|message| {
+///         let message = message.borrow();
+///
+///         // 1 * 8 + 6
+///         assert!(message.starts_with("<14>1 "));
+///         assert!(message.ends_with(" - - Server started"));
+///     });
+/// }
+/// ```
+pub fn to_syslog(payload: &Payload, facility: u8) -> String {
+    let severity = match payload.level {
+        Level::Error | Level::Off => 3,
+        Level::Warn => 4,
+        Level::Info => 6,
+        Level::Debug | Level::Trace => 7,
+    };
+
+    let mut message = String::new();
+
+    let _ = write!(
+        message,
+        "<{}>1 ",
+        u16::from(facility.min(23)) * 8 + severity
+    );
+
+    #[cfg(feature = "timestamp")]
+    let _ = write!(
+        message,
+        "{}",
+        crate::TimestampFormat::Rfc3339.display(payload.timestamp)
+    );
+
+    #[cfg(not(feature = "timestamp"))]
+    message.push('-');
+
+    message.push_str(" - ");
+
+    #[cfg(feature = "metadata")]
+    message.push_str(
+        payload
+            .meta
+            .module_path
+            .split("::")
+            .next()
+            .filter(|app| !app.is_empty())
+            .unwrap_or("-"),
+    );
+
+    #[cfg(not(feature = "metadata"))]
+    message.push('-');
+
+    let _ = write!(message, " {} - - {}", std::process::id(), payload.args);
+
+    #[cfg(feature = "kv")]
+    let _ = write!(message, "{}", crate::fields::KeyValues(payload.fields));
+
+    message
+}
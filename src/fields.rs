@@ -0,0 +1,124 @@
+use core::fmt::{self, Arguments, Write as _};
+
+/// Renders `key = value` fields as ` key=value` pairs.
+///
+/// Values that are empty or contain whitespace are quoted (escaping their quotes and
+/// backslashes), so every pair stays a single token: ` reason="timed out"`.
+pub struct KeyValues<'a>(pub &'a [(&'a str, Arguments<'a>)]);
+
+impl fmt::Display for KeyValues<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (key, value) in self.0 {
+            write!(f, " {}=", key)?;
+
+            if needs_quotes(value) {
+                f.write_char('"')?;
+                write!(QuoteEscape(f), "{}", value)?;
+                f.write_char('"')?;
+            } else {
+                write!(f, "{}", value)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Returns the fields to append to the message of a log: all of them without the `kv` feature,
+/// none with it (they're stored in [`Payload::fields`](crate::Payload) instead).
+#[inline(always)]
+pub fn message_suffix<'a>(fields: &'a [(&'a str, Arguments<'a>)]) -> KeyValues<'a> {
+    #[cfg(feature = "kv")]
+    {
+        let _ = fields;
+
+        KeyValues(&[])
+    }
+
+    #[cfg(not(feature = "kv"))]
+    KeyValues(fields)
+}
+
+/// Renders the message of a log, followed by its fields with the `kv` feature.
+#[cfg(feature = "std")]
+pub(crate) struct Message<'a, 'b>(pub &'a crate::Payload<'b>);
+
+#[cfg(feature = "std")]
+impl fmt::Display for Message<'_, '_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let max_len = match crate::get_max_message_len() {
+            Some(max_len) => max_len,
+            None => return self.write_to(f),
+        };
+
+        let mut out = crate::truncate::Truncate::new(&mut *f, max_len);
+        self.write_to(&mut out)?;
+
+        if out.truncated() {
+            f.write_str(crate::truncate::TRUNCATION_MARKER)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(feature = "std")]
+impl Message<'_, '_> {
+    /// Writes the message and its fields, without the length limit.
+    fn write_to(&self, out: &mut impl fmt::Write) -> fmt::Result {
+        write!(out, "{}", self.0.args)?;
+
+        #[cfg(feature = "kv")]
+        write!(out, "{}", KeyValues(self.0.fields))?;
+
+        Ok(())
+    }
+}
+
+/// Returns whether a value is empty or contains whitespace, without allocating.
+fn needs_quotes(value: &Arguments<'_>) -> bool {
+    struct Scan {
+        empty: bool,
+        whitespace: bool,
+    }
+
+    impl fmt::Write for Scan {
+        fn write_str(&mut self, s: &str) -> fmt::Result {
+            self.empty &= s.is_empty();
+
+            if s.contains(char::is_whitespace) {
+                self.whitespace = true;
+
+                // No need to render the rest of the value
+                return Err(fmt::Error);
+            }
+
+            Ok(())
+        }
+    }
+
+    let mut scan = Scan {
+        empty: true,
+        whitespace: false,
+    };
+    let _ = write!(scan, "{}", value);
+
+    scan.empty || scan.whitespace
+}
+
+/// Writes strings into a quoted value (without the surrounding quotes), escaping them.
+struct QuoteEscape<'a, 'b>(&'a mut fmt::Formatter<'b>);
+
+impl fmt::Write for QuoteEscape<'_, '_> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        for c in s.chars() {
+            if c == '"' || c == '\\' {
+                self.0.write_char('\\')?;
+            }
+
+            self.0.write_char(c)?;
+        }
+
+        Ok(())
+    }
+}
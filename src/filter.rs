@@ -0,0 +1,82 @@
+use crate::Payload;
+
+use core::sync::atomic::{AtomicPtr, Ordering};
+
+/// Filter function storage.
+static FILTER_FN: AtomicPtr<()> = AtomicPtr::new(core::ptr::null_mut());
+
+/// Function type for filtering logs, see [`set_filter`]. (`filter` feature)
+///
+/// Returns `true` to emit the log, `false` to drop it.
+pub type FilterFn = fn(&Payload) -> bool;
+
+/// Sets a filter deciding whether each log is emitted. (`filter` feature)
+///
+/// The filter is called after the level filters (so only for logs that would be emitted), and
+/// before the extensions and the loggers: returning `false` drops the log, neither the extensions
+/// nor the loggers see it. It can drop spammy messages, or messages that must not leave the
+/// process.
+///
+/// The filter runs on every emitted log, so it should be cheap. Rendering `payload.args` to match
+/// on the message costs a formatting per log, matching on the level or the metadata doesn't. It's
+/// not called for logs emitted while the thread is panicking and handled by the
+/// `PanickingPolicy` (`std` feature).
+///
+/// Without a filter (the default), every log passing the level filters is emitted.
+///
+/// # Example
+///
+/// ```
+/// use loggery::{info, warn, Payload};
+/// use std::sync::atomic::{AtomicUsize, Ordering};
+///
+/// static LOGGED: AtomicUsize = AtomicUsize::new(0);
+///
+/// fn my_logger(_: Payload) {
+///     LOGGED.fetch_add(1, Ordering::Relaxed);
+/// }
+///
+/// fn no_heartbeats(payload: &Payload) -> bool {
+///     payload.args.as_str() != Some("Heartbeat")
+/// }
+///
+/// fn main() {
+/// #   #[cfg(not(feature = "static"))]
+///     loggery::set_logger(my_logger);
+///     loggery::set_filter(no_heartbeats);
+///
+///     info!("Heartbeat");
+///     warn!("Connection lost");
+///
+/// #   #[cfg(all(not(feature = "static"), not(any(feature = "min_level_warn",
+/// #       feature = "min_level_error", feature = "min_level_off"))))]
+///     assert_eq!(LOGGED.load(Ordering::Relaxed), 1);
+/// }
+/// ```
+#[inline]
+pub fn set_filter(filter_fn: FilterFn) {
+    FILTER_FN.store(filter_fn as *mut (), Ordering::Release);
+}
+
+/// Removes the filter set with [`set_filter`], emitting every log passing the level filters
+/// again. (`filter` feature)
+#[inline]
+pub fn clear_filter() {
+    FILTER_FN.store(core::ptr::null_mut(), Ordering::Release);
+}
+
+/// Returns `true` if the log passes the filter (or if there is none).
+#[inline(always)]
+pub(crate) fn passes(payload: &Payload) -> bool {
+    let ptr = FILTER_FN.load(Ordering::Acquire);
+
+    if ptr.is_null() {
+        return true;
+    }
+
+    // SAFETY: `ptr` was created from a `FilterFn` in `set_filter`.
+    // Function pointers are 'static. Atomics ensure cross-thread visibility.
+    let filter_fn = unsafe { core::mem::transmute::<*mut (), FilterFn>(ptr) };
+
+    filter_fn(payload)
+}
@@ -0,0 +1,202 @@
+use crate::Level;
+
+use core::sync::atomic::{AtomicBool, AtomicPtr, Ordering};
+
+/// Flush function storage.
+static FLUSH_FN: AtomicPtr<()> = AtomicPtr::new(core::ptr::null_mut());
+
+/// Whether every log is flushed, regardless of its level.
+static ALWAYS_FLUSH: AtomicBool = AtomicBool::new(false);
+/// Per-level "flush immediately" storage, indexed by level.
+static IMMEDIATE_FLUSH: [AtomicBool; 5] = [
+    AtomicBool::new(false), // Trace
+    AtomicBool::new(false), // Debug
+    AtomicBool::new(false), // Info
+    AtomicBool::new(true),  // Warn
+    AtomicBool::new(true),  // Error
+];
+
+/// When a buffered sink writes a log to its destination.
+///
+/// Consulted by the buffered sinks of the `extensions` module (like `FileLogger` and
+/// `GzipFileSink`), and available to custom sinks with [`get_flush_policy`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FlushPolicy {
+    /// The sink is flushed right after the log, so it survives a crash right after it. Also
+    /// flushes the logs buffered before it.
+    Immediate,
+    /// The log stays in the sink's buffer until the buffer is full or the sink is flushed,
+    /// which is faster but can lose it if the process dies.
+    Buffered,
+}
+
+/// Sets the flush policy of logs at `level`.
+///
+/// This is a durability/performance tradeoff: high-severity logs can be made durable, while
+/// low-severity (and much more frequent) logs stay fast.
+///
+/// | Level   | Default                     |
+/// |---------|-----------------------------|
+/// | `Trace` | [`FlushPolicy::Buffered`]   |
+/// | `Debug` | [`FlushPolicy::Buffered`]   |
+/// | `Info`  | [`FlushPolicy::Buffered`]   |
+/// | `Warn`  | [`FlushPolicy::Immediate`]  |
+/// | `Error` | [`FlushPolicy::Immediate`]  |
+///
+/// Setting the policy of [`Level::Off`] does nothing.
+///
+/// # Example
+///
+/// ```
+/// use loggery::{FlushPolicy, Level};
+///
+/// loggery::set_flush_policy(Level::Info, FlushPolicy::Immediate);
+///
+/// assert_eq!(loggery::get_flush_policy(Level::Info), FlushPolicy::Immediate);
+/// assert_eq!(loggery::get_flush_policy(Level::Trace), FlushPolicy::Buffered);
+/// ```
+#[inline(always)]
+pub fn set_flush_policy(level: Level, policy: FlushPolicy) {
+    if let Some(immediate) = IMMEDIATE_FLUSH.get(level as usize) {
+        immediate.store(policy == FlushPolicy::Immediate, Ordering::Relaxed);
+    }
+}
+
+/// Returns the flush policy of logs at `level` ([`FlushPolicy::Buffered`] for [`Level::Off`]).
+///
+/// Always [`FlushPolicy::Immediate`] while [`set_always_flush`] is enabled.
+#[inline(always)]
+pub fn get_flush_policy(level: Level) -> FlushPolicy {
+    if ALWAYS_FLUSH.load(Ordering::Relaxed) {
+        return FlushPolicy::Immediate;
+    }
+
+    match IMMEDIATE_FLUSH.get(level as usize) {
+        Some(immediate) if immediate.load(Ordering::Relaxed) => FlushPolicy::Immediate,
+        _ => FlushPolicy::Buffered,
+    }
+}
+
+/// Flushes every log as soon as it's written, when chasing a crash. **A debugging aid, not for
+/// production**: it makes every log pay for a flush (usually a syscall).
+///
+/// While enabled, [`get_flush_policy`] returns [`FlushPolicy::Immediate`] for every level (so the
+/// buffered sinks, like `FileLogger`, flush after each write), and [`flush`] is called after each
+/// log was passed to the loggers. Disabled by default, the per-level policies apply again when
+/// it's disabled.
+///
+/// # Example
+///
+/// ```
+/// use loggery::{info, trace};
+/// use std::sync::atomic::{AtomicUsize, Ordering};
+///
+/// static FLUSHES: AtomicUsize = AtomicUsize::new(0);
+///
+/// fn my_flush() {
+///     FLUSHES.fetch_add(1, Ordering::Relaxed);
+/// }
+///
+/// fn main() {
+///     loggery::set_flush(my_flush);
+///     loggery::set_always_flush(true);
+///
+///     info!("Loading the config");
+///     trace!("Parsing the config");
+///
+/// #   #[cfg(not(any(feature = "min_level_debug", feature = "min_level_info",
+/// #       feature = "min_level_warn", feature = "min_level_error", feature = "min_level_off")))]
+///     assert_eq!(FLUSHES.load(Ordering::Relaxed), 2);
+/// }
+/// ```
+#[inline]
+pub fn set_always_flush(enabled: bool) {
+    ALWAYS_FLUSH.store(enabled, Ordering::Relaxed);
+}
+
+/// Returns `true` if [`set_always_flush`] is enabled.
+#[inline(always)]
+pub fn is_always_flush() -> bool {
+    ALWAYS_FLUSH.load(Ordering::Relaxed)
+}
+
+/// Function type for flushing the buffered output of the loggers, see [`set_flush`].
+pub type FlushFn = fn();
+
+/// Sets the function called by [`flush`], e.g., to write out the buffer of a `BufWriter`-backed
+/// logger.
+///
+/// It's recommended to call once during the initialization, along with `set_logger`.
+///
+/// # Example
+///
+/// ```
+/// use std::sync::atomic::{AtomicBool, Ordering};
+///
+/// static FLUSHED: AtomicBool = AtomicBool::new(false);
+///
+/// fn my_flush() {
+///     // Flush your logger's buffers here
+///     FLUSHED.store(true, Ordering::Relaxed);
+/// }
+///
+/// loggery::set_flush(my_flush);
+/// loggery::flush();
+///
+/// assert!(FLUSHED.load(Ordering::Relaxed));
+/// ```
+#[inline]
+pub fn set_flush(flush_fn: FlushFn) {
+    FLUSH_FN.store(flush_fn as *mut (), Ordering::Release);
+}
+
+/// Flushes the buffered output of the loggers, by calling the function set with [`set_flush`].
+///
+/// With the `std` feature, it also flushes stdout and stderr, used by the default logger. Without
+/// a flush function (and without `std`), it does nothing.
+///
+/// # Shutdown
+///
+/// Buffered logs are lost if the process exits without flushing them, so call it on every way
+/// out of the program. A guard in `main` covers the normal return and panics that unwind:
+///
+/// ```
+/// struct FlushGuard;
+///
+/// impl Drop for FlushGuard {
+///     fn drop(&mut self) {
+///         loggery::flush();
+///     }
+/// }
+///
+/// fn main() {
+///     let _flush_guard = FlushGuard;
+///
+///     loggery::info!("Flushed when `main` returns");
+/// }
+/// ```
+///
+/// [`std::process::exit`] doesn't run destructors though: call `flush` before it, or register an
+/// exit handler with `install_atexit_flush` (`std` feature), which calls `flush` too.
+#[inline]
+pub fn flush() {
+    let ptr = FLUSH_FN.load(Ordering::Acquire);
+
+    if !ptr.is_null() {
+        // SAFETY: `ptr` was created from a `FlushFn` in `set_flush`.
+        // Function pointers are 'static. Atomics ensure cross-thread visibility.
+        let flush_fn = unsafe { core::mem::transmute::<*mut (), FlushFn>(ptr) };
+
+        flush_fn();
+    }
+
+    #[cfg(feature = "std")]
+    {
+        extern crate std;
+
+        use std::io::Write as _;
+
+        let _ = std::io::stdout().flush();
+        let _ = std::io::stderr().flush();
+    }
+}
@@ -0,0 +1,489 @@
+use crate::Payload;
+
+use core::sync::atomic::{AtomicPtr, Ordering};
+
+/// Global formatter function storage.
+static FORMATTER_FN: AtomicPtr<()> = AtomicPtr::new(core::ptr::null_mut());
+/// Format template of the default logger storage.
+static FORMAT_TEMPLATE: crate::spin::Mutex<Option<&'static str>> = crate::spin::Mutex::new(None);
+
+/// Function type for a formatter shared by the built-in sinks, see [`set_global_formatter`].
+///
+/// It writes a log entry to `out`, without a trailing newline (the sinks add it if they need it).
+pub type FormatterFn = fn(&Payload, &mut dyn core::fmt::Write) -> core::fmt::Result;
+
+/// Sets the formatter used by all built-in sinks instead of their default format. (`std` feature)
+///
+/// The formatter renders the whole entry: the default logger, the text extensions (e.g.,
+/// `save_to_file`, `FileLogger`) and the broadcast extension all write what it writes instead of
+/// their `timestamp [LEVEL] message` default, so the format is configured in a single place. Sinks
+/// that add their own framing (like the hash of `HashChainSink`) keep it around the formatted
+/// entry, and the JSON extension isn't affected.
+///
+/// The formatter replaces the default format entirely, including the level labels and the
+/// message length limit: apply [`get_level_label`](crate::get_level_label) or
+/// [`truncate_str`](crate::truncate_str) in it if needed.
+///
+/// [`multiline_fields`] is a built-in formatter, for tools expecting one field per line.
+///
+/// It's only consulted by the built-in sinks: a custom logger or extension formats its logs as it
+/// wants, which is how a sink overrides the global format locally. A built-in sink can be given
+/// its own formatter too, with
+/// [`StreamRouting::route_with_format`](crate::StreamRouting::route_with_format) for a route of
+/// the default logger, or `FileLogger::with_formatter`.
+///
+/// # Example
+///
+/// ```
+/// use loggery::{warn, Destination, Level, Payload, StreamRouting};
+/// use std::fmt;
+///
+/// fn my_format(payload: &Payload, out: &mut dyn fmt::Write) -> fmt::Result {
+///     write!(out, "{} | {}", payload.level, payload.args)
+/// }
+///
+/// fn path(name: &str) -> String {
+///     let path = std::env::temp_dir().join(name);
+///     path.to_string_lossy().into_owned()
+/// }
+///
+/// # #[cfg(feature = "extension")]
+/// fn my_extension(payload: &Payload) {
+///     let _ = loggery::extensions::save_to_file(payload, &path("loggery_formatter_file.log"));
+/// }
+///
+/// fn main() {
+///     loggery::set_global_formatter(my_format);
+///
+///     // Send the default logger's output to a file to check it
+///     let stdout_path = path("loggery_formatter_stdout.log");
+///     let _ = std::fs::remove_file(&stdout_path);
+///     let stdout_path: &'static str = Box::leak(stdout_path.into_boxed_str());
+///
+///     loggery::set_stream_routing(
+///         StreamRouting::new().route(Level::Warn, Destination::File(stdout_path)),
+///     );
+///
+/// #   #[cfg(all(feature = "extension", not(feature = "static")))]
+/// #   {
+///     let _ = std::fs::remove_file(path("loggery_formatter_file.log"));
+///     loggery::set_extension(my_extension);
+/// #   }
+///
+///     warn!("Disk almost full");
+///
+/// #   #[cfg(all(any(not(feature = "static"), feature = "static_default"),
+/// #       not(feature = "min_level_off")))]
+///     assert!(std::fs::read_to_string(stdout_path)
+///         .unwrap()
+///         .ends_with("WARN | Disk almost full\n"));
+///
+/// #   #[cfg(all(feature = "extension", not(feature = "static"), not(feature = "min_level_off")))]
+///     assert_eq!(
+///         std::fs::read_to_string(path("loggery_formatter_file.log")).unwrap(),
+///         "WARN | Disk almost full\n"
+///     );
+/// }
+/// ```
+#[inline]
+pub fn set_global_formatter(formatter_fn: FormatterFn) {
+    FORMATTER_FN.store(formatter_fn as *mut (), Ordering::Release);
+}
+
+/// Removes the formatter set with [`set_global_formatter`], going back to the default format of
+/// every sink. (`std` feature)
+#[inline]
+pub fn clear_global_formatter() {
+    FORMATTER_FN.store(core::ptr::null_mut(), Ordering::Release);
+}
+
+/// Returns the formatter set with [`set_global_formatter`], if any. (`std` feature)
+#[inline]
+pub fn get_global_formatter() -> Option<FormatterFn> {
+    let ptr = FORMATTER_FN.load(Ordering::Acquire);
+
+    if ptr.is_null() {
+        return None;
+    }
+
+    // SAFETY: `ptr` was created from a `FormatterFn` in `set_global_formatter`.
+    // Function pointers are 'static. Atomics ensure cross-thread visibility.
+    Some(unsafe { core::mem::transmute::<*mut (), FormatterFn>(ptr) })
+}
+
+/// Sets a template for the format of the default logger. (`std` feature)
+///
+/// The template is written for every log, with these tokens replaced:
+///
+/// | Token      | Replaced by                                                     |
+/// |------------|-----------------------------------------------------------------|
+/// | `{level}`  | The level label (see `set_level_labels`), e.g., ` INFO`         |
+/// | `{msg}`    | The message (with its `key = value` fields)                     |
+/// | `{module}` | The module path of the log call (`metadata` feature)            |
+/// | `{target}` | The target of the log call (`metadata` feature)                 |
+/// | `{file}`   | The source file of the log call (`metadata` feature)            |
+/// | `{line}`   | The line number of the log call (`metadata` feature)            |
+/// | `{thread}` | The name (or `thread-ID`) of the thread (`thread_meta` feature) |
+/// | `{cpu}`    | The CPU core of the thread, `-` if unknown (`cpu_meta` feature) |
+/// | `{time}`   | The timestamp, see `set_timestamp_format` (`timestamp` feature) |
+/// | `{gen}`    | The generation, see [`bump_generation`](crate::bump_generation) |
+/// | `{seq}`    | The sequence number of the log (`seq` feature)                  |
+///
+/// Anything else, including unknown tokens and tokens of disabled features, is written verbatim.
+/// The template doesn't include the trailing newline.
+///
+/// It only tweaks the default logger: a logger set with [`set_logger`](crate::set_logger) bypasses
+/// it entirely, and a formatter set with [`set_global_formatter`] takes precedence over it.
+///
+/// # Example
+///
+/// ```
+/// use loggery::{error, Destination, Level, StreamRouting};
+///
+/// loggery::set_format("{level} | {module}: {msg} {unknown}");
+///
+/// assert_eq!(loggery::get_format(), Some("{level} | {module}: {msg} {unknown}"));
+///
+/// // Send the default logger's output to a file to check it
+/// let path = std::env::temp_dir().join("loggery_format_doctest.log");
+/// let _ = std::fs::remove_file(&path);
+/// let path: &'static str = Box::leak(path.to_string_lossy().into_owned().into_boxed_str());
+///
+/// loggery::set_stream_routing(StreamRouting::new().route(Level::Error, Destination::File(path)));
+///
+/// error!("Out of memory");
+///
+/// # #[cfg(all(feature = "metadata", any(not(feature = "static"), feature = "static_default"),
+/// #     not(feature = "min_level_off")))]
+/// assert!(std::fs::read_to_string(path)
+///     .unwrap()
+///     .ends_with("ERROR | rust_out: Out of memory {unknown}\n"));
+/// ```
+#[inline]
+pub fn set_format(template: &'static str) {
+    *FORMAT_TEMPLATE.lock() = Some(template);
+}
+
+/// Removes the template set with [`set_format`], going back to the default format of the
+/// default logger. (`std` feature)
+#[inline]
+pub fn clear_format() {
+    *FORMAT_TEMPLATE.lock() = None;
+}
+
+/// Returns the template set with [`set_format`], if any. (`std` feature)
+#[inline]
+pub fn get_format() -> Option<&'static str> {
+    *FORMAT_TEMPLATE.lock()
+}
+
+/// Formats a log entry on several lines: a header line, then the message and every `key = value`
+/// field on its own indented `key: value` line. (`std` feature)
+///
+/// Meant for old line-oriented tools expecting one `key: value` per line, with
+/// [`set_global_formatter`]:
+///
+/// ```text
+/// [ WARN]
+///     message: Disk almost full
+///     disk: /dev/sda1
+///     free: 2%
+/// ```
+///
+/// The header is the level label, prefixed by the timestamp with the `timestamp` feature. The
+/// fields are only available with the `kv` feature, without it the entry is the header and the
+/// message line (with the fields appended to the message, see [`log!`](crate::log!)).
+///
+/// # Multi-line entries
+///
+/// Every log takes several lines, which breaks the "one log per line" assumption of most tools
+/// (`grep`, log shippers, the JSON extension's consumers...), so it's opt-in. The field values are
+/// written verbatim, a value containing a newline spans several lines too.
+///
+/// # Example
+///
+/// ```
+/// use loggery::{warn, Payload};
+/// use std::cell::RefCell;
+///
+/// thread_local! {
+///     static LAST: RefCell<String> = RefCell::new(String::new());
+/// }
+///
+/// fn my_logger(payload: Payload) {
+///     LAST.with(|last| {
+///         let mut last = last.borrow_mut();
+///
+///         last.clear();
+///         let _ = loggery::multiline_fields(&payload, &mut *last);
+///     });
+/// }
+///
+/// fn main() {
+/// #   #[cfg(not(feature = "static"))]
+///     loggery::set_logger(my_logger);
+///
+///     warn!(disk = "/dev/sda1", free = "2%"; "Disk almost full");
+///
+/// #   #[cfg(all(feature = "kv", not(feature = "static"), not(feature = "timestamp"),
+/// #       not(any(feature = "min_level_error", feature = "min_level_off"))))]
+///     LAST.with(|last| {
+///         assert_eq!(
+///             *last.borrow(),
+///             "[ WARN]\n    message: Disk almost full\n    disk: /dev/sda1\n    free: 2%"
+///         );
+///     });
+/// }
+/// ```
+pub fn multiline_fields(payload: &Payload, out: &mut dyn core::fmt::Write) -> core::fmt::Result {
+    #[cfg(feature = "timestamp")]
+    write!(
+        out,
+        "{} ",
+        crate::get_timestamp_format().display(payload.timestamp)
+    )?;
+
+    write!(out, "[{}]", crate::labels::LevelLabel(payload.level))?;
+    write!(out, "\n    message: {}", payload.args)?;
+
+    #[cfg(feature = "kv")]
+    for (key, value) in payload.fields {
+        write!(out, "\n    {}: {}", key, value)?;
+    }
+
+    Ok(())
+}
+
+/// Renders a log entry of a built-in sink: with the global formatter if one is set, otherwise in
+/// the default `[LEVEL] message` format (prefixed by the timestamp if `timestamp` is `true`).
+pub(crate) struct Line<'a, 'b> {
+    payload: &'a Payload<'b>,
+    #[cfg_attr(not(feature = "timestamp"), allow(dead_code))]
+    timestamp: bool,
+}
+
+impl<'a, 'b> Line<'a, 'b> {
+    /// Renders the entry with the timestamp, for the sinks writing it.
+    pub(crate) fn timestamped(payload: &'a Payload<'b>) -> Self {
+        Self {
+            payload,
+            timestamp: true,
+        }
+    }
+
+    /// Renders the entry without the timestamp.
+    #[cfg(feature = "extension")]
+    pub(crate) fn plain(payload: &'a Payload<'b>) -> Self {
+        Self {
+            payload,
+            timestamp: false,
+        }
+    }
+}
+
+impl core::fmt::Display for Line<'_, '_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        if let Some(formatter_fn) = get_global_formatter() {
+            return formatter_fn(self.payload, f);
+        }
+
+        write_default(f, self.payload, self.timestamp, false)
+    }
+}
+
+/// Renders a log entry with a formatter function.
+pub(crate) struct Formatted<'a, 'b>(pub(crate) FormatterFn, pub(crate) &'a Payload<'b>);
+
+impl core::fmt::Display for Formatted<'_, '_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        (self.0)(self.1, f)
+    }
+}
+
+/// Formats a log entry in the default format, with the level label colored with ANSI escape
+/// codes. (`std` feature)
+///
+/// Meant for a terminal, as the formatter of a route (see
+/// [`StreamRouting::route_with_format`](crate::StreamRouting::route_with_format)): `TRACE` is
+/// gray, `DEBUG` blue, `INFO` green, `WARN` yellow and `ERROR` red. The rest of the entry is
+/// written as in the default format, prefixed by the timestamp with the `timestamp` feature.
+///
+/// The escape codes are written unconditionally, it's up to the caller to only use it for a
+/// terminal that supports them.
+///
+/// # Example
+///
+/// ```
+/// use loggery::{Level, Payload};
+/// use std::cell::RefCell;
+///
+/// thread_local! {
+///     static LAST: RefCell<String> = RefCell::new(String::new());
+/// }
+///
+/// fn my_logger(payload: Payload) {
+///     LAST.with(|last| {
+///         let mut last = last.borrow_mut();
+///
+///         last.clear();
+///         let _ = loggery::colored(&payload, &mut *last);
+///     });
+/// }
+///
+/// fn main() {
+/// #   #[cfg(not(feature = "static"))]
+///     loggery::set_logger(my_logger);
+///
+///     loggery::warn!("Disk almost full");
+///
+/// #   #[cfg(all(not(feature = "static"), not(any(feature = "min_level_error",
+/// #       feature = "min_level_off")), not(feature = "timestamp"), not(feature = "thread_meta"),
+/// #       not(feature = "cpu_meta")))]
+///     LAST.with(|last| assert_eq!(*last.borrow(), "\x1b[33m[ WARN]\x1b[0m Disk almost full"));
+/// }
+/// ```
+pub fn colored(payload: &Payload, out: &mut dyn core::fmt::Write) -> core::fmt::Result {
+    write_default(out, payload, true, true)
+}
+
+/// Writes a log entry in the default `[LEVEL] message` format, prefixed by the timestamp if
+/// `timestamp` is `true` and with the level label colored if `colored` is `true`.
+fn write_default(
+    f: &mut dyn core::fmt::Write,
+    payload: &Payload,
+    #[cfg_attr(not(feature = "timestamp"), allow(unused_variables))] timestamp: bool,
+    colored: bool,
+) -> core::fmt::Result {
+    #[cfg(feature = "timestamp")]
+    if timestamp {
+        write!(
+            f,
+            "{} ",
+            crate::get_timestamp_format().display(payload.timestamp)
+        )?;
+    }
+
+    let label = crate::labels::LevelLabel(payload.level);
+
+    if colored {
+        let color = match payload.level {
+            crate::Level::Trace => "90",
+            crate::Level::Debug => "34",
+            crate::Level::Info => "32",
+            crate::Level::Warn => "33",
+            crate::Level::Error | crate::Level::Off => "31",
+        };
+
+        write!(f, "\x1b[{}m[{}]\x1b[0m", color, label)?;
+    } else {
+        write!(f, "[{}]", label)?;
+    }
+
+    #[cfg(feature = "thread_meta")]
+    write!(
+        f,
+        " [{}]",
+        crate::thread::ThreadLabel(payload.meta.thread_id)
+    )?;
+
+    #[cfg(feature = "cpu_meta")]
+    if let Some(cpu) = payload.meta.cpu {
+        write!(f, " [cpu {}]", cpu)?;
+    }
+
+    if crate::is_show_generation() {
+        write!(f, " [gen {}]", crate::current_generation())?;
+    }
+
+    write!(
+        f,
+        "{} {}",
+        crate::scope::Scopes,
+        crate::fields::Message(payload)
+    )
+}
+
+/// Renders a log entry of the default logger: with the global formatter if one is set, then with
+/// the template set with [`set_format`] if any, otherwise in the default format.
+pub(crate) struct DefaultLoggerLine<'a, 'b>(pub(crate) &'a Payload<'b>);
+
+impl core::fmt::Display for DefaultLoggerLine<'_, '_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match get_format() {
+            Some(template) if get_global_formatter().is_none() => {
+                write_template(f, template, self.0)
+            }
+            _ => write!(f, "{}", Line::timestamped(self.0)),
+        }
+    }
+}
+
+/// Writes `template` with its tokens replaced, see [`set_format`].
+fn write_template(
+    f: &mut core::fmt::Formatter<'_>,
+    template: &str,
+    payload: &Payload,
+) -> core::fmt::Result {
+    let mut rest = template;
+
+    while let Some(start) = rest.find('{') {
+        f.write_str(&rest[..start])?;
+        rest = &rest[start..];
+
+        let token = rest.find('}').map(|end| &rest[..=end]);
+        let written = match token {
+            Some(token) => write_token(f, token, payload)?,
+            None => false,
+        };
+
+        match token {
+            Some(token) if written => rest = &rest[token.len()..],
+            _ => {
+                // Not a token, the brace is written verbatim
+                f.write_str("{")?;
+                rest = &rest[1..];
+            }
+        }
+    }
+
+    f.write_str(rest)
+}
+
+/// Writes the value of a `{token}`, returning `false` if it isn't a known token.
+fn write_token(
+    f: &mut core::fmt::Formatter<'_>,
+    token: &str,
+    payload: &Payload,
+) -> Result<bool, core::fmt::Error> {
+    match token {
+        "{level}" => write!(f, "{}", crate::labels::LevelLabel(payload.level))?,
+        "{msg}" => write!(f, "{}", crate::fields::Message(payload))?,
+        "{gen}" => write!(f, "{}", crate::current_generation())?,
+        #[cfg(feature = "seq")]
+        "{seq}" => write!(f, "{}", payload.seq)?,
+        #[cfg(feature = "metadata")]
+        "{module}" => f.write_str(payload.meta.module_path)?,
+        #[cfg(feature = "metadata")]
+        "{target}" => f.write_str(payload.meta.target)?,
+        #[cfg(feature = "metadata")]
+        "{file}" => f.write_str(payload.meta.file)?,
+        #[cfg(feature = "metadata")]
+        "{line}" => write!(f, "{}", payload.meta.line)?,
+        #[cfg(feature = "thread_meta")]
+        "{thread}" => write!(f, "{}", crate::thread::ThreadLabel(payload.meta.thread_id))?,
+        #[cfg(feature = "cpu_meta")]
+        "{cpu}" => match payload.meta.cpu {
+            Some(cpu) => write!(f, "{}", cpu)?,
+            None => f.write_str("-")?,
+        },
+        #[cfg(feature = "timestamp")]
+        "{time}" => write!(
+            f,
+            "{}",
+            crate::get_timestamp_format().display(payload.timestamp)
+        )?,
+        _ => return Ok(false),
+    }
+
+    Ok(true)
+}
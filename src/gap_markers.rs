@@ -0,0 +1,114 @@
+use crate::metrics::DropReason;
+
+use core::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+/// Whether gap markers are enabled.
+static GAP_MARKERS: AtomicBool = AtomicBool::new(false);
+/// Number of logs dropped since the last emitted log.
+static PENDING_GAP: AtomicUsize = AtomicUsize::new(0);
+
+/// Sets whether the next emitted log reports how many logs were dropped before it, `false` by
+/// default. (`std` feature)
+///
+/// When a log that would have been emitted is dropped, the gap is recorded, and the next emitted
+/// log carries a ` dropped_before=N` annotation at the end of its message (like the `key=value`
+/// fields without the `kv` feature), so consumers can reconcile the missing entries. The counted
+/// drops are those of the `DropReason`s that lose logs the level filters let through:
+///
+/// - `Shutdown`: suppressed by `enter_shutdown_mode`, or dropped by
+///   `AsyncLogger::shutdown_timeout`.
+/// - `Panicking`: dropped by the `PanickingPolicy::Drop` policy.
+/// - `InvalidLevel`: dropped by the `InvalidLevelPolicy::Drop` policy.
+/// - `Filter`: dropped by the filter set with `set_filter` (`filter` feature).
+///
+/// Logs filtered by the level, or skipped by `log_once!` and `log_every_n!`, are left out on
+/// purpose, they aren't gaps. A `BroadcastSink` subscriber missing a log isn't a gap either, the
+/// other sinks got it.
+///
+/// The markers count the same drops as the counters of `drop_breakdown` (`metrics` feature) for
+/// these reasons, but don't depend on the `metrics` feature, and emitting a marker doesn't reset
+/// the counters. Drops happening while the markers are disabled aren't recorded.
+///
+/// # Example
+///
+/// ```
+/// use loggery::{info, try_log_level, InvalidLevelPolicy, Payload};
+/// use std::cell::RefCell;
+///
+/// thread_local! {
+///     static LAST: RefCell<String> = RefCell::new(String::new());
+/// }
+///
+/// fn my_logger(payload: Payload) {
+///     LAST.with(|last| *last.borrow_mut() = payload.args.to_string());
+/// }
+///
+/// fn main() {
+/// #   #[cfg(not(feature = "static"))]
+///     loggery::set_logger(my_logger);
+///
+///     loggery::set_seq_gap_markers(true);
+///     loggery::set_invalid_level_policy(InvalidLevelPolicy::Drop);
+///
+///     // Dropped, the raw level is invalid
+///     try_log_level!(42, "Bad sensor level");
+///     try_log_level!(42, "Bad sensor level");
+///
+///     info!("Sensor reset");
+///
+/// #   #[cfg(all(not(feature = "static"), not(any(feature = "min_level_warn",
+/// #       feature = "min_level_error", feature = "min_level_off"))))]
+/// #   {
+///     LAST.with(|last| assert_eq!(*last.borrow(), "Sensor reset dropped_before=2"));
+///
+///     info!("No gap");
+///
+///     LAST.with(|last| assert_eq!(*last.borrow(), "No gap"));
+/// #   }
+/// }
+/// ```
+#[inline]
+pub fn set_seq_gap_markers(enabled: bool) {
+    GAP_MARKERS.store(enabled, Ordering::Relaxed);
+}
+
+/// Returns `true` if gap markers are enabled, see [`set_seq_gap_markers`]. (`std` feature)
+#[inline]
+pub fn is_seq_gap_markers() -> bool {
+    GAP_MARKERS.load(Ordering::Relaxed)
+}
+
+/// Records a dropped log, if it leaves a gap and the markers are enabled.
+#[inline]
+pub(crate) fn record(reason: DropReason) {
+    let is_gap = matches!(
+        reason,
+        DropReason::Shutdown
+            | DropReason::Panicking
+            | DropReason::InvalidLevel
+            | DropReason::Filter
+    );
+
+    if is_gap && is_seq_gap_markers() {
+        PENDING_GAP.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+/// Returns the number of logs dropped since the last emitted log, and resets it.
+#[inline(always)]
+pub(crate) fn take() -> usize {
+    if PENDING_GAP.load(Ordering::Relaxed) == 0 {
+        return 0;
+    }
+
+    PENDING_GAP.swap(0, Ordering::Relaxed)
+}
+
+/// Writes the ` dropped_before=N` annotation of a gap.
+pub(crate) struct Marker(pub(crate) usize);
+
+impl core::fmt::Display for Marker {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, " dropped_before={}", self.0)
+    }
+}
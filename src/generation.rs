@@ -0,0 +1,77 @@
+use core::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+/// Current generation storage.
+static GENERATION: AtomicUsize = AtomicUsize::new(0);
+/// Whether the default format shows the generation.
+static SHOW_GENERATION: AtomicBool = AtomicBool::new(false);
+
+/// Starts a new generation of logs, e.g., after reloading the configuration or restarting a
+/// subsystem, and returns it. (`std` feature)
+///
+/// Generations start at `0` and only go up, so logs written before and after a reload can be told
+/// apart. They're cheaper than full correlation IDs: a single global counter, nothing per log.
+///
+/// The generation is shown by the default format once [`set_show_generation`] is on, as
+/// `[gen N]` after the level, and by the `{gen}` token of `set_format` templates. It's read when
+/// the log is written, not when it's emitted: a log queued by an asynchronous logger (or replayed
+/// by `dump_recent`) shows the generation of the moment it's written.
+///
+/// # Example
+///
+/// ```
+/// use loggery::{info, Destination, Level, StreamRouting};
+///
+/// // Send the default logger's output to a file to check it
+/// let path = std::env::temp_dir().join("loggery_generation_doctest.log");
+/// let _ = std::fs::remove_file(&path);
+/// let path: &'static str = Box::leak(path.to_string_lossy().into_owned().into_boxed_str());
+///
+/// loggery::set_stream_routing(StreamRouting::new().route(Level::Info, Destination::File(path)));
+/// loggery::set_show_generation(true);
+///
+/// info!("Config loaded");
+///
+/// assert_eq!(loggery::bump_generation(), 1);
+/// assert_eq!(loggery::current_generation(), 1);
+///
+/// info!("Config reloaded");
+///
+/// # #[cfg(all(any(not(feature = "static"), feature = "static_default"), not(any(
+/// #     feature = "min_level_warn", feature = "min_level_error", feature = "min_level_off")),
+/// #     not(feature = "thread_meta"), not(feature = "cpu_meta")))]
+/// # {
+/// let contents = std::fs::read_to_string(path).unwrap();
+/// let mut lines = contents.lines();
+///
+/// assert!(lines.next().unwrap().ends_with("[ INFO] [gen 0] Config loaded"));
+/// assert!(lines.next().unwrap().ends_with("[ INFO] [gen 1] Config reloaded"));
+/// # }
+/// ```
+#[inline]
+pub fn bump_generation() -> u64 {
+    GENERATION.fetch_add(1, Ordering::Relaxed) as u64 + 1
+}
+
+/// Returns the current generation, see [`bump_generation`]. (`std` feature)
+#[inline]
+pub fn current_generation() -> u64 {
+    GENERATION.load(Ordering::Relaxed) as u64
+}
+
+/// Sets whether the default format shows the generation (see [`bump_generation`]), `false` by
+/// default. (`std` feature)
+///
+/// This affects the default logger and the built-in text extensions (e.g., `save_to_file`), not a
+/// formatter set with `set_global_formatter` or a template set with `set_format`, which use the
+/// `{gen}` token instead.
+#[inline]
+pub fn set_show_generation(show: bool) {
+    SHOW_GENERATION.store(show, Ordering::Relaxed);
+}
+
+/// Returns `true` if the default format shows the generation, see [`set_show_generation`].
+/// (`std` feature)
+#[inline]
+pub fn is_show_generation() -> bool {
+    SHOW_GENERATION.load(Ordering::Relaxed)
+}
@@ -0,0 +1,240 @@
+use crate::Level;
+
+use core::sync::atomic::{AtomicBool, AtomicU8, Ordering};
+
+/// Level labels storage, indexed by level.
+static LEVEL_LABELS: crate::spin::Mutex<[&'static str; 5]> =
+    crate::spin::Mutex::new(DEFAULT_LEVEL_LABELS);
+/// Whether the labels are sized to the enabled levels.
+static COMPACT_LEVEL_LABELS: AtomicBool = AtomicBool::new(false);
+/// Alignment of the labels storage.
+static LEVEL_ALIGN: AtomicU8 = AtomicU8::new(LevelAlign::Right as u8);
+
+/// The labels of [`Level::as_str`], used by default.
+const DEFAULT_LEVEL_LABELS: [&str; 5] = ["TRACE", "DEBUG", " INFO", " WARN", "ERROR"];
+
+/// Replaces the level labels of the default format, e.g., for localization or a house style.
+/// (`std` feature)
+///
+/// The labels are given in level order: `[trace, debug, info, warn, error]`. They're used instead
+/// of [`Level::as_str`] by the default logger and the built-in text extensions (e.g.,
+/// `save_to_file`), but not by the JSON extension, which keeps the standard level names.
+///
+/// The default labels are padded to the same width so the messages line up. Custom labels are
+/// written verbatim, keeping them aligned (if needed) is up to you, or to
+/// [`set_compact_level_labels`].
+///
+/// # Example
+///
+/// ```
+/// use loggery::{warn, Destination, Level, StreamRouting};
+///
+/// loggery::set_level_labels(["TRC", "DBG", "INF", "WRN", "ERR"]);
+///
+/// assert_eq!(loggery::get_level_label(Level::Warn), "WRN");
+///
+/// // Send the default logger's output to a file to check it
+/// let path = std::env::temp_dir().join("loggery_level_labels_doctest.log");
+/// let _ = std::fs::remove_file(&path);
+/// let path: &'static str = Box::leak(path.to_string_lossy().into_owned().into_boxed_str());
+///
+/// loggery::set_stream_routing(StreamRouting::new().route(Level::Warn, Destination::File(path)));
+///
+/// warn!("Disk almost full");
+///
+/// # #[cfg(all(any(not(feature = "static"), feature = "static_default"), not(feature = "min_level_off"),
+/// #     not(feature = "thread_meta"), not(feature = "cpu_meta")))]
+/// assert!(std::fs::read_to_string(path)
+///     .unwrap()
+///     .ends_with("[WRN] Disk almost full\n"));
+/// ```
+#[inline]
+pub fn set_level_labels(labels: [&'static str; 5]) {
+    *LEVEL_LABELS.lock() = labels;
+}
+
+/// Returns the label of `level` in the default format, see [`set_level_labels`]. (`std` feature)
+#[inline]
+pub fn get_level_label(level: Level) -> &'static str {
+    LEVEL_LABELS
+        .lock()
+        .get(level as usize)
+        .copied()
+        .unwrap_or_else(|| level.as_str())
+}
+
+/// Sets whether the level labels are padded to the widest label of the enabled levels only,
+/// `false` by default. (`std` feature)
+///
+/// The labels are padded to line the messages up, so the widest label of all levels sets the
+/// width, even if that level can't be logged. Once on, the labels (default or set with
+/// [`set_level_labels`]) are trimmed and right-aligned to the widest label among the levels that
+/// pass [`is_enabled`](crate::is_enabled) instead, giving tighter output when fewer levels are
+/// active.
+///
+/// The width is computed when each log is written, so it changes with:
+///
+/// - The `min_level_*` features, e.g., `min_level_info` leaves `INFO`, `WARN` and `ERROR`.
+/// - The runtime level set with `set_min_level`, from the next log on.
+/// - The level mask set with `set_level_mask` (`level_mask` feature).
+///
+/// Module levels aren't taken into account: a log let through by a module level more verbose than
+/// the global level keeps its whole label, even if it's wider. With the default labels, the width
+/// only shrinks (from 5 to 4) when none of `TRACE`, `DEBUG` and `ERROR` is enabled, e.g., with a
+/// level mask of `INFO` and `WARN`. It makes more of a difference with custom labels of uneven
+/// widths.
+///
+/// This affects the default logger, the built-in text extensions and the `{level}` token of
+/// `set_format` templates.
+///
+/// # Example
+///
+/// ```
+/// use loggery::{warn, Destination, Level, StreamRouting};
+///
+/// // `VERBOSE` is the widest label, but it's only needed when `trace!` logs are enabled
+/// loggery::set_level_labels(["VERBOSE", "  DEBUG", "   INFO", "   WARN", "  ERROR"]);
+/// loggery::set_compact_level_labels(true);
+///
+/// // Send the default logger's output to a file to check it
+/// let path = std::env::temp_dir().join("loggery_compact_level_labels_doctest.log");
+/// let _ = std::fs::remove_file(&path);
+/// let path: &'static str = Box::leak(path.to_string_lossy().into_owned().into_boxed_str());
+///
+/// loggery::set_stream_routing(StreamRouting::new().route(Level::Warn, Destination::File(path)));
+///
+/// warn!("Disk almost full");
+///
+/// # #[cfg(all(any(not(feature = "static"), feature = "static_default"),
+/// #     not(feature = "thread_meta"), not(feature = "cpu_meta")))]
+/// # {
+/// let contents = std::fs::read_to_string(path).unwrap();
+///
+/// // With `min_level_warn`, only `WARN` and `ERROR` are enabled, 5 columns are enough
+/// # #[cfg(feature = "min_level_warn")]
+/// assert!(contents.ends_with("[ WARN] Disk almost full\n"));
+///
+/// // Otherwise, `VERBOSE` sets the width (unless the runtime level filters `trace!` logs)
+/// # #[cfg(not(any(feature = "min_level_debug", feature = "min_level_info",
+/// #     feature = "min_level_warn", feature = "min_level_error", feature = "min_level_off")))]
+/// assert!(contents.ends_with("[   WARN] Disk almost full\n"));
+/// # }
+/// ```
+#[inline]
+pub fn set_compact_level_labels(compact: bool) {
+    COMPACT_LEVEL_LABELS.store(compact, Ordering::Relaxed);
+}
+
+/// Returns `true` if the level labels are sized to the enabled levels, see
+/// [`set_compact_level_labels`]. (`std` feature)
+#[inline]
+pub fn is_compact_level_labels() -> bool {
+    COMPACT_LEVEL_LABELS.load(Ordering::Relaxed)
+}
+
+/// How the level labels are aligned in the default format, see [`set_level_align`].
+/// (`std` feature)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum LevelAlign {
+    /// Padded on the left, e.g., `[ INFO]` (default).
+    Right = 0,
+    /// Padded on the right, e.g., `[INFO ]`.
+    Left = 1,
+    /// Not padded, e.g., `[INFO]`. The messages don't line up.
+    None = 2,
+}
+
+/// Sets how the level labels are aligned in the default format, [`LevelAlign::Right`] by
+/// default. (`std` feature)
+///
+/// The labels are trimmed, then padded to the widest label (of all levels, or of the enabled ones
+/// with [`set_compact_level_labels`]) on the chosen side. With [`LevelAlign::Right`] and without
+/// compact labels, the labels are written as they are, including the custom ones set with
+/// [`set_level_labels`].
+///
+/// This affects the default logger, the built-in text extensions and the `{level}` token of
+/// `set_format` templates. To drop the brackets as well, use a template, e.g., `"{level} {msg}"`.
+///
+/// # Example
+///
+/// ```
+/// use loggery::{info, Destination, Level, LevelAlign, StreamRouting};
+///
+/// loggery::set_level_align(LevelAlign::Left);
+///
+/// assert_eq!(loggery::get_level_align(), LevelAlign::Left);
+///
+/// // Send the default logger's output to a file to check it
+/// let path = std::env::temp_dir().join("loggery_level_align_doctest.log");
+/// let _ = std::fs::remove_file(&path);
+/// let path: &'static str = Box::leak(path.to_string_lossy().into_owned().into_boxed_str());
+///
+/// loggery::set_stream_routing(StreamRouting::new().route(Level::Info, Destination::File(path)));
+///
+/// info!("Left-aligned");
+///
+/// loggery::set_level_align(LevelAlign::None);
+///
+/// info!("Not padded");
+///
+/// # #[cfg(all(any(not(feature = "static"), feature = "static_default"), not(any(
+/// #     feature = "min_level_warn", feature = "min_level_error", feature = "min_level_off")),
+/// #     not(feature = "thread_meta"), not(feature = "cpu_meta"), not(feature = "timestamp")))]
+/// # {
+/// let contents = std::fs::read_to_string(path).unwrap();
+/// let lines: Vec<_> = contents.lines().collect();
+///
+/// // The label keeps the width of the widest ones (5 columns)
+/// assert_eq!(lines, ["[INFO ] Left-aligned", "[INFO] Not padded"]);
+/// # }
+/// ```
+#[inline]
+pub fn set_level_align(align: LevelAlign) {
+    LEVEL_ALIGN.store(align as u8, Ordering::Relaxed);
+}
+
+/// Returns the alignment set with [`set_level_align`]. (`std` feature)
+#[inline]
+pub fn get_level_align() -> LevelAlign {
+    match LEVEL_ALIGN.load(Ordering::Relaxed) {
+        1 => LevelAlign::Left,
+        2 => LevelAlign::None,
+        _ => LevelAlign::Right,
+    }
+}
+
+/// Writes the label of a level in the default format, see [`set_level_labels`],
+/// [`set_compact_level_labels`] and [`set_level_align`].
+pub(crate) struct LevelLabel(pub(crate) Level);
+
+impl core::fmt::Display for LevelLabel {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let align = get_level_align();
+        let compact = is_compact_level_labels();
+
+        if align == LevelAlign::Right && !compact {
+            return f.write_str(get_level_label(self.0));
+        }
+
+        // Copied, so the lock isn't held while writing
+        let labels = *LEVEL_LABELS.lock();
+        let label = |level: Level| labels.get(level as usize).map_or("", |label| label.trim());
+
+        if align == LevelAlign::None {
+            return f.write_str(label(self.0));
+        }
+
+        let width = Level::iter()
+            .filter(|&level| !compact || crate::is_enabled(level))
+            .map(|level| label(level).chars().count())
+            .max()
+            .unwrap_or(0);
+
+        if align == LevelAlign::Left {
+            write!(f, "{:<1$}", label(self.0), width)
+        } else {
+            write!(f, "{:>1$}", label(self.0), width)
+        }
+    }
+}
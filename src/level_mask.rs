@@ -0,0 +1,157 @@
+use crate::Level;
+
+use core::sync::atomic::{AtomicU8, Ordering};
+
+/// Runtime level mask storage.
+static LEVEL_MASK: AtomicU8 = AtomicU8::new(LevelSet::all().bits);
+
+/// A set of [`Level`]s, where each level is a bit. (`level_mask` feature)
+///
+/// Unlike a minimum level, a set can hold any subset of levels, e.g., only [`Level::Trace`] and
+/// [`Level::Error`]. [`Level::Off`] is never part of a set.
+///
+/// # Example
+///
+/// ```
+/// use loggery::{Level, LevelSet};
+///
+/// let set = LevelSet::empty().with(Level::Trace).with(Level::Error);
+///
+/// assert!(set.contains(Level::Trace));
+/// assert!(!set.contains(Level::Info));
+/// assert!(set.contains(Level::Error));
+///
+/// assert_eq!(set, LevelSet::from(Level::Trace) | LevelSet::from(Level::Error));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct LevelSet {
+    bits: u8,
+}
+
+impl LevelSet {
+    /// Returns a set without any level.
+    #[inline]
+    pub const fn empty() -> Self {
+        Self { bits: 0 }
+    }
+
+    /// Returns a set with every level (except [`Level::Off`]).
+    #[inline]
+    pub const fn all() -> Self {
+        Self { bits: 0b1_1111 }
+    }
+
+    /// Returns this set with `level` added. Adding [`Level::Off`] does nothing.
+    #[inline]
+    pub const fn with(self, level: Level) -> Self {
+        Self {
+            bits: (self.bits | (1 << level as u8)) & Self::all().bits,
+        }
+    }
+
+    /// Returns this set with `level` removed.
+    #[inline]
+    pub const fn without(self, level: Level) -> Self {
+        Self {
+            bits: self.bits & !(1 << level as u8),
+        }
+    }
+
+    /// Returns `true` if `level` is in this set.
+    #[inline]
+    pub const fn contains(self, level: Level) -> bool {
+        self.bits & (1 << level as u8) & Self::all().bits != 0
+    }
+
+    /// Returns `true` if this set has no level.
+    #[inline]
+    pub const fn is_empty(self) -> bool {
+        self.bits == 0
+    }
+}
+
+impl Default for LevelSet {
+    /// Returns a set with every level, see [`LevelSet::all`].
+    #[inline]
+    fn default() -> Self {
+        Self::all()
+    }
+}
+
+impl From<Level> for LevelSet {
+    #[inline]
+    fn from(level: Level) -> Self {
+        Self::empty().with(level)
+    }
+}
+
+impl core::ops::BitOr for LevelSet {
+    type Output = Self;
+
+    #[inline]
+    fn bitor(self, rhs: Self) -> Self {
+        Self {
+            bits: self.bits | rhs.bits,
+        }
+    }
+}
+
+impl core::ops::BitAnd for LevelSet {
+    type Output = Self;
+
+    #[inline]
+    fn bitand(self, rhs: Self) -> Self {
+        Self {
+            bits: self.bits & rhs.bits,
+        }
+    }
+}
+
+/// Sets the runtime level mask, only the levels in `mask` are logged. (`level_mask` feature)
+///
+/// By default, the mask has every level ([`LevelSet::all`]).
+///
+/// # Precedence
+///
+/// The mask doesn't replace the minimum levels, it's applied on top of them: a log is only emitted
+/// if its level passes the compile-time level (`min_level_*` features), the runtime level
+/// ([`set_min_level`](crate::set_min_level), or the module level with the `module_level` feature)
+/// **and** is in the mask. To filter with the mask alone, leave the minimum level at
+/// [`Level::Trace`].
+///
+/// # Example
+///
+/// ```
+/// use loggery::{Level, LevelSet};
+///
+/// loggery::set_min_level(Level::Trace);
+/// loggery::set_level_mask(LevelSet::empty().with(Level::Trace).with(Level::Error));
+///
+/// # #[cfg(not(any(feature = "min_level_debug", feature = "min_level_info",
+/// #     feature = "min_level_warn", feature = "min_level_error", feature = "min_level_off")))]
+/// # {
+/// assert!(loggery::is_enabled(Level::Trace));
+/// assert!(!loggery::is_enabled(Level::Debug));
+/// assert!(!loggery::is_enabled(Level::Info));
+/// assert!(!loggery::is_enabled(Level::Warn));
+/// assert!(loggery::is_enabled(Level::Error));
+///
+/// // The minimum level still applies
+/// loggery::set_min_level(Level::Debug);
+///
+/// assert!(!loggery::is_enabled(Level::Trace));
+/// assert!(loggery::is_enabled(Level::Error));
+/// # }
+/// ```
+#[inline(always)]
+pub fn set_level_mask(mask: LevelSet) {
+    LEVEL_MASK.store(mask.bits, Ordering::Relaxed);
+}
+
+/// Returns the runtime level mask. (`level_mask` feature)
+#[inline(always)]
+pub fn get_level_mask() -> LevelSet {
+    LevelSet {
+        bits: LEVEL_MASK.load(Ordering::Relaxed),
+    }
+}
@@ -65,6 +65,60 @@
 //! [APPLICATION]-DEBUG-(A log message using my custom logger!)
 //! ```
 //!
+//! The default logger writes [`warn!`] and [`error!`] logs to stderr and the other levels to stdout.
+//! Use `set_stderr_threshold` to move that split, or `set_stream_routing` to send each level to
+//! stdout, stderr, a file or nowhere. Long messages can be cut with [`set_max_message_len`], and
+//! `set_global_formatter` replaces the format of the default logger and all built-in extensions.
+//! For small tweaks, `set_format` gives the default logger a template, e.g., `"{level} {msg}"`.
+//! Each log ends with a single newline, even when its message already ends with one.
+//! The `key = value` fields of a log (e.g., `info!(user = name, id = 42; "Logged in")`) are
+//! appended after the message as ` key=value` pairs, separated by spaces, in call order. Values
+//! that are empty or contain whitespace are quoted, escaping their `"` and `\`, so each pair
+//! stays a single token for `grep` and `awk`:
+//!
+//! ```
+//! # #[cfg(feature = "std")]
+//! # {
+//! use loggery::{info, Destination, Level, StreamRouting};
+//!
+//! // Send the default logger's output to a file to check it
+//! let path = std::env::temp_dir().join("loggery_fields_doctest.log");
+//! let _ = std::fs::remove_file(&path);
+//! let path: &'static str = Box::leak(path.to_string_lossy().into_owned().into_boxed_str());
+//!
+//! loggery::set_stream_routing(StreamRouting::new().route(Level::Info, Destination::File(path)));
+//!
+//! info!(user = "Ada Lovelace", id = 42; "Logged in");
+//!
+//! # #[cfg(all(any(not(feature = "static"), feature = "static_default"), not(any(
+//! #     feature = "min_level_warn", feature = "min_level_error", feature = "min_level_off")),
+//! #     not(feature = "timestamp"), not(feature = "thread_meta"), not(feature = "cpu_meta")))]
+//! assert_eq!(
+//!     std::fs::read_to_string(path).unwrap(),
+//!     "[ INFO] Logged in user=\"Ada Lovelace\" id=42\n"
+//! );
+//! # }
+//! ```
+//!
+//! Logs emitted inside a [`scope!`] are prefixed with its label: `[ INFO] [request] message`.
+//! With the `thread_meta` feature, the logging thread follows the level: `[ INFO] [main] message`,
+//! and with the `cpu_meta` feature, the CPU core it was running on: `[ INFO] [cpu 3] message`.
+//!
+//! To send every log to several loggers (e.g., stdout and a file), register the extra ones with
+//! [`add_logger`], they're called in order after the first one. [`clear_logger`] removes them all,
+//! going back to the default logger (e.g., between tests), and [`set_logger_scoped`] sets a logger
+//! until the returned guard is dropped. Loggers buffering their output can register a function
+//! with [`set_flush`], called by [`flush`] at shutdown. With the `target_route` feature,
+//! `add_target_route` sends the logs of some targets (e.g., `"net::*"`) to their own sink instead.
+//! Custom loggers can delegate to `default_logger` (`std` feature) to keep the default format.
+//! Loggers are plain `fn` pointers, per-thread state (e.g., a log file per request) can be kept
+//! with `set_thread_sink` and reached from the logger with `with_thread_sink`.
+//!
+//! Without `std` (e.g., on embedded targets), the [`embedded`] module provides a logger writing
+//! to any `core::fmt::Write` (like a UART), without allocating, and [`embedded::render_into`]
+//! renders a log in the default format into a byte buffer. Code that forbids global mutable
+//! state can log through a [`Dispatcher`] owning its sink instead, with [`info_to!`] and friends.
+//!
 //! > **Note:** [`set_logger`] isn't available if the `static` feature is enabled!
 //! > Read [Static](#static) for more details.
 //!
@@ -84,10 +138,24 @@
 //! warn!("This will be logged");
 //! ```
 //!
+//! Setting the minimum level to [`Level::Off`] disables all logs at runtime. [`set_max_level`]
+//! filters out the levels above a maximum too, so only a range of levels is emitted, e.g.,
+//! [`debug!`] to [`warn!`].
+//!
 //! This works alongside compile-time filtering using `min_level_*` features.
 //! Runtime filtering can only be more restrictive, not less restrictive than compile-time feature.
 //! For example if the `min_level_info` feature is enabled, [`debug!`], [`trace!`] calls are
 //! removed at compile-time and cannot be re-enabled at runtime.
+//! [`diagnose_config`] reports such contradictions (and `warn_config_issues` prints them to
+//! stderr at startup).
+//!
+//! With the `module_level` feature, you can also give specific modules (and their submodules) their
+//! own level with `set_module_level`, e.g., to enable [`debug!`] logs of a single noisy subsystem
+//! while everything else stays at [`warn!`]. Use `is_enabled_for` to check the effective level of
+//! a module.
+//!
+//! With the `level_mask` feature, `set_level_mask` additionally restricts logging to an arbitrary
+//! `LevelSet`, e.g., only [`trace!`] and [`error!`], on top of the minimum levels.
 //!
 //! # Static
 //!
@@ -132,6 +200,10 @@
 //! > **Tip:** Even with `static` feature, you can still use the `runtime_level` feature and
 //! > therefore the [`set_min_level`] function to do runtime log level filtering.
 //!
+//! Libraries don't need to `cfg` on the `static` feature, only the binary crate picks it: the
+//! macros, [`log()`] and [`emit()`] (which skips the filters) resolve the dispatch internally, so
+//! the same library code works with both.
+//!
 //! <div class="warning">
 //!
 //! When using the `static` feature, you **must** define `__loggery_log_impl` function in your
@@ -143,8 +215,9 @@
 //!
 //! > **Note:** Only available when the `extension` feature is enabled.
 //!
-//! Extensions provide a hook for extra processing *alongside* the actual logger. They're called
-//! before the logger and receive a reference to the [`Payload`], giving you the ability to:
+//! Extensions provide a hook for extra processing *alongside* the actual logger. They're always
+//! called (and finished) before the logger and receive a reference to the [`Payload`], giving you
+//! the ability to:
 //! - Save logs to files
 //! - Send logs to external services
 //! - Collect metrics
@@ -169,6 +242,9 @@
 //! }
 //! ```
 //!
+//! To run several extensions (e.g., saving to a file and collecting metrics), register the extra
+//! ones with `add_extension`, they're called in order after the first one.
+//!
 //! > **Note:** When the `static` feature is enabled, `set_extension` isn't available. Instead,
 //! > you must do this:
 //! >
@@ -188,27 +264,90 @@
 //!
 //! </div>
 //!
+//! # Panicking loggers
+//!
+//! > **Note:** Only available when the `catch_panic` feature is enabled.
+//!
+//! A logger that panics (including `__loggery_log_impl` with the `static` feature) unwinds into
+//! the code that logged, which has nothing to do with logging, and can leave it half-done (e.g., a
+//! lock poisoned, or a `Drop` implementation cut short). With the `catch_panic` feature, each
+//! logger call is wrapped in `std::panic::catch_unwind`: a panic stops at the logger, the log is
+//! lost, and a short note is written to stderr instead. The extensions aren't covered.
+//!
+//! ```
+//! use loggery::{error, Payload};
+//!
+//! fn fragile_logger(_: Payload) {
+//!     panic!("The logger failed");
+//! }
+//!
+//! fn main() {
+//! #   #[cfg(not(feature = "static"))]
+//!     loggery::set_logger(fragile_logger);
+//!
+//! #   #[cfg(all(feature = "catch_panic", not(feature = "static")))]
+//! #   {
+//! #   std::panic::set_hook(Box::new(|_| {}));
+//!     // Writes "loggery: the logger panicked, a log at the ERROR level was lost" to stderr
+//!     error!("Disk full");
+//! #   }
+//!
+//!     // Still running
+//! }
+//! ```
+//!
+//! Catching is cheap when nothing panics (no more than a regular call on most platforms), but the
+//! closure passed to `catch_unwind` keeps the logger from being inlined into the macros' call
+//! sites with the `static` feature. The feature needs `std`, since `catch_unwind` isn't available
+//! in `no_std`, and it does nothing when panics abort (`panic = "abort"`). A panic while the
+//! thread is already panicking aborts the process before it can be caught: logs emitted then are
+//! handled by `set_panicking_policy` instead.
+//!
 //! # Features
 //!
 //! > **Default features:** `std`, `metadata`, `runtime_level`
 //!
-//! |      Feature      | Default |                          Description                          |
-//! |-------------------|:-------:|---------------------------------------------------------------|
-//! | `std`             |  __✓__  | Enables default stdout logger                                 |
-//! | `static`          |  __✗__  | Enables static extern logger definition                       |
-//! | `static_default`  |  __✗__  | Provides default static logger (enables `std` + `static`)     |
-//! | `metadata`        |  __✓__  | Enables [`meta`](Metadata) field in the [`Payload`]           |
-//! | `extension`       |  __✗__  | Enables extension hooks for extra functionality               |
-//! | `runtime_level`   |  __✓__  | Allows changing log level filtering at runtime                |
-//! | `min_level_off`   |  __✗__  | Disables all logs at compile time                             |
-//! | `min_level_trace` |  __✗__  | Only logs [`trace`], [`debug`], [`info`], [`warn`], [`error`] |
-//! | `min_level_debug` |  __✗__  | Only logs [`debug`], [`info`], [`warn`], [`error`]            |
-//! | `min_level_info`  |  __✗__  | Only logs [`info`], [`warn`], [`error`]                       |
-//! | `min_level_warn`  |  __✗__  | Only logs [`warn`], [`error`]                                 |
-//! | `min_level_error` |  __✗__  | Only logs [`error`]                                           |
+//! |      Feature       | Default |                          Description                          |
+//! |--------------------|:-------:|---------------------------------------------------------------|
+//! | `std`              |  __✓__  | Enables default stdout logger                                 |
+//! | `static`           |  __✗__  | Enables static extern logger definition                       |
+//! | `static_default`   |  __✗__  | Provides default static logger (enables `std` + `static`)     |
+//! | `metadata`         |  __✓__  | Enables [`meta`](Metadata) field in the [`Payload`]           |
+//! | `extension`        |  __✗__  | Enables extension hooks for extra functionality               |
+//! | `runtime_level`    |  __✓__  | Allows changing log level filtering at runtime                |
+//! | `assert_logger`    |  __✗__  | Panics in debug builds when logging before a logger is set    |
+//! | `module_level`     |  __✗__  | Enables per-module runtime levels                             |
+//! | `level_mask`       |  __✗__  | Enables filtering by an arbitrary set of levels               |
+//! | `timestamp`        |  __✗__  | Enables `timestamp` field in the [`Payload`]                  |
+//! | `gzip`             |  __✗__  | Enables `GzipFileSink` extension (adds `flate2`)              |
+//! | `testing`          |  __✗__  | Enables `testing` module with log capture helpers             |
+//! | `tracing_appender` |  __✗__  | Enables `NonBlockingSink` extension (adds `tracing-appender`) |
+//! | `kv`               |  __✗__  | Stores `key = value` fields in `Payload::fields`              |
+//! | `anyhow`           |  __✗__  | Enables `log_report` extension for `anyhow` errors            |
+//! | `thread_meta`      |  __✗__  | Adds the logging thread to the [`Metadata`]                   |
+//! | `cpu_meta`         |  __✗__  | Adds the CPU core (Linux) to the [`Metadata`] (adds `libc`)   |
+//! | `metrics`          |  __✗__  | Counts emitted logs per level and dropped logs per reason     |
+//! | `filter`           |  __✗__  | Enables `set_filter` to drop logs before they're emitted      |
+//! | `shm`              |  __✗__  | Enables `ShmSink` shared memory extension (unix, adds `libc`) |
+//! | `target_route`     |  __✗__  | Routes logs to sinks by target glob, see `add_target_route`   |
+//! | `emission_stats`   |  __✗__  | Times the sinks per level, see `emission_stats`               |
+//! | `catch_panic`      |  __✗__  | Catches panics of the loggers (enables `std`)                 |
+//! | `seq`              |  __✗__  | Adds an increasing sequence number to the [`Payload`]         |
+//! | `min_level_off`    |  __✗__  | Disables all logs at compile time                             |
+//! | `min_level_trace`  |  __✗__  | Only logs [`trace`], [`debug`], [`info`], [`warn`], [`error`] |
+//! | `min_level_debug`  |  __✗__  | Only logs [`debug`], [`info`], [`warn`], [`error`]            |
+//! | `min_level_info`   |  __✗__  | Only logs [`info`], [`warn`], [`error`]                       |
+//! | `min_level_warn`   |  __✗__  | Only logs [`warn`], [`error`]                                 |
+//! | `min_level_error`  |  __✗__  | Only logs [`error`]                                           |
+//!
+//! The `min_level_*` features are mutually exclusive: enabling more than one of them (e.g., in two
+//! crates of the same build) is a compile error, instead of silently picking one of the levels.
 
 #![no_std]
 
+#[cfg(feature = "std")]
+extern crate std;
+
 /// Log levels in order of incraesing severity.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
 #[repr(u8)]
@@ -223,10 +362,49 @@ pub enum Level {
     Warn = 3,
     /// Error
     Error = 4,
+    /// Off
+    ///
+    /// Not a level to log at, but a minimum level that disables all logs, e.g.,
+    /// `set_min_level(Level::Off)`. Logs at this level are never emitted.
+    Off = 5,
 }
 
 impl Level {
+    /// The levels to log at, in increasing severity order ([`Level::Off`] isn't one of them).
+    ///
+    /// Handy to list the allowed levels (e.g., in a dropdown or a config validator) or to exercise
+    /// every level in tests, without hardcoding them.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use loggery::Level;
+    ///
+    /// assert!(Level::ALL.windows(2).all(|pair| pair[0] < pair[1]));
+    ///
+    /// for (value, level) in (0..=4).zip(Level::ALL.iter()) {
+    ///     assert_eq!(Level::from_u8(value), Some(*level));
+    /// }
+    ///
+    /// assert!(Level::iter().eq(Level::ALL.iter().copied()));
+    /// ```
+    pub const ALL: [Level; 5] = [
+        Level::Trace,
+        Level::Debug,
+        Level::Info,
+        Level::Warn,
+        Level::Error,
+    ];
+
+    /// Returns an iterator over [`Level::ALL`], in increasing severity order.
+    #[inline]
+    pub fn iter() -> impl Iterator<Item = Level> {
+        Level::ALL.iter().copied()
+    }
+
     /// Returns the string representation with consistent width for right aligned output.
+    ///
+    /// Same as [`Level::as_str_padded`], see [`Level::name`] for the name without padding.
     #[inline(always)]
     pub fn as_str(&self) -> &'static str {
         match self {
@@ -235,35 +413,328 @@ impl Level {
             Level::Info => " INFO",
             Level::Warn => " WARN",
             Level::Error => "ERROR",
+            Level::Off => "  OFF",
+        }
+    }
+
+    /// Returns the name of the level, right-aligned to 5 characters (the width of the longest
+    /// names), e.g., `" INFO"`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use loggery::Level;
+    ///
+    /// assert!(Level::iter().all(|level| level.as_str_padded().len() == 5));
+    /// assert_eq!(Level::Warn.as_str_padded(), " WARN");
+    /// assert_eq!(Level::Error.as_str_padded(), "ERROR");
+    /// ```
+    #[inline(always)]
+    pub fn as_str_padded(&self) -> &'static str {
+        self.as_str()
+    }
+
+    /// Returns the name of the level, without padding, e.g., `"INFO"`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use loggery::Level;
+    ///
+    /// assert_eq!(Level::Info.name(), "INFO");
+    /// assert_eq!(Level::Error.name(), "ERROR");
+    /// assert_eq!(Level::Info.name(), Level::Info.as_str_padded().trim_start());
+    /// ```
+    #[inline(always)]
+    pub fn name(&self) -> &'static str {
+        match self {
+            Level::Trace => "TRACE",
+            Level::Debug => "DEBUG",
+            Level::Info => "INFO",
+            Level::Warn => "WARN",
+            Level::Error => "ERROR",
+            Level::Off => "OFF",
         }
     }
 
     /// Converts a u8 to a level, returning `None` if invalid.
     #[inline(always)]
-    pub fn from_u8(value: u8) -> Option<Self> {
+    pub const fn from_u8(value: u8) -> Option<Self> {
         match value {
             0 => Some(Level::Trace),
             1 => Some(Level::Debug),
             2 => Some(Level::Info),
             3 => Some(Level::Warn),
             4 => Some(Level::Error),
+            5 => Some(Level::Off),
             _ => None,
         }
     }
+
+    /// Converts a value of an external verbosity scale to a level.
+    ///
+    /// Values past the end of a scale are clamped to its most verbose level, see
+    /// [`VerbosityScale`] for the exact mappings.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use loggery::{Level, VerbosityScale};
+    ///
+    /// let syslog = [
+    ///     Level::Error, // 0: Emergency
+    ///     Level::Error, // 1: Alert
+    ///     Level::Error, // 2: Critical
+    ///     Level::Error, // 3: Error
+    ///     Level::Warn,  // 4: Warning
+    ///     Level::Info,  // 5: Notice
+    ///     Level::Info,  // 6: Informational
+    ///     Level::Debug, // 7: Debug
+    ///     Level::Trace, // 8+
+    /// ];
+    /// let clap = [
+    ///     Level::Error, // (no flag)
+    ///     Level::Warn,  // -v
+    ///     Level::Info,  // -vv
+    ///     Level::Debug, // -vvv
+    ///     Level::Trace, // -vvvv
+    ///     Level::Trace, // -vvvvv+
+    /// ];
+    /// let zero = [
+    ///     Level::Off,   // 0
+    ///     Level::Error, // 1
+    ///     Level::Warn,  // 2
+    ///     Level::Info,  // 3
+    ///     Level::Debug, // 4
+    ///     Level::Trace, // 5
+    ///     Level::Trace, // 6+
+    /// ];
+    ///
+    /// for (n, level) in syslog.iter().enumerate() {
+    ///     assert_eq!(Level::from_scale(n as u8, VerbosityScale::Syslog), *level);
+    /// }
+    /// for (n, level) in clap.iter().enumerate() {
+    ///     assert_eq!(Level::from_scale(n as u8, VerbosityScale::Clap), *level);
+    /// }
+    /// for (n, level) in zero.iter().enumerate() {
+    ///     assert_eq!(Level::from_scale(n as u8, VerbosityScale::Zero), *level);
+    /// }
+    ///
+    /// assert_eq!(Level::from_scale(u8::MAX, VerbosityScale::Syslog), Level::Trace);
+    /// ```
+    #[inline]
+    pub fn from_scale(n: u8, scale: VerbosityScale) -> Self {
+        match scale {
+            VerbosityScale::Syslog => match n {
+                0..=3 => Level::Error,
+                4 => Level::Warn,
+                5 | 6 => Level::Info,
+                7 => Level::Debug,
+                _ => Level::Trace,
+            },
+            VerbosityScale::Clap => match n {
+                0 => Level::Error,
+                1 => Level::Warn,
+                2 => Level::Info,
+                3 => Level::Debug,
+                _ => Level::Trace,
+            },
+            VerbosityScale::Zero => match n {
+                0 => Level::Off,
+                1 => Level::Error,
+                2 => Level::Warn,
+                3 => Level::Info,
+                4 => Level::Debug,
+                _ => Level::Trace,
+            },
+        }
+    }
+}
+
+/// External verbosity conventions that can be converted to a [`Level`] with [`Level::from_scale`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VerbosityScale {
+    /// Syslog severities (RFC 5424), where lower is more severe:
+    ///
+    /// | Value | Syslog severity                    | Level     |
+    /// |-------|------------------------------------|-----------|
+    /// | `0-3` | Emergency, Alert, Critical, Error  | `Error`   |
+    /// | `4`   | Warning                            | `Warn`    |
+    /// | `5-6` | Notice, Informational              | `Info`    |
+    /// | `7`   | Debug                              | `Debug`   |
+    /// | `8+`  | (none)                             | `Trace`   |
+    Syslog,
+    /// Number of `-v` flags on the command line, quiet by default (like `clap-verbosity-flag`):
+    ///
+    /// | Value | Flags           | Level     |
+    /// |-------|-----------------|-----------|
+    /// | `0`   | (none)          | `Error`   |
+    /// | `1`   | `-v`            | `Warn`    |
+    /// | `2`   | `-vv`           | `Info`    |
+    /// | `3`   | `-vvv`          | `Debug`   |
+    /// | `4+`  | `-vvvv`         | `Trace`   |
+    Clap,
+    /// Zero-based verbosity, where `0` disables logging (like `log::LevelFilter`):
+    ///
+    /// | Value | Level     |
+    /// |-------|-----------|
+    /// | `0`   | `Off`     |
+    /// | `1`   | `Error`   |
+    /// | `2`   | `Warn`    |
+    /// | `3`   | `Info`    |
+    /// | `4`   | `Debug`   |
+    /// | `5+`  | `Trace`   |
+    Zero,
+}
+
+impl core::fmt::Display for Level {
+    /// Writes the level name without the alignment padding of [`Level::as_str`].
+    ///
+    /// Width and alignment flags are supported, e.g., `{:<5}`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use loggery::Level;
+    ///
+    /// assert_eq!(format!("{}", Level::Info), "INFO");
+    /// assert_eq!(Level::Info.as_str(), " INFO");
+    /// assert_eq!(format!("[{:<5}]", Level::Warn), "[WARN ]");
+    /// ```
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.pad(self.name())
+    }
+}
+
+impl core::str::FromStr for Level {
+    type Err = LevelParseError;
+
+    /// Parses a level name, ignoring ASCII case and surrounding whitespace.
+    ///
+    /// Accepts `trace`, `debug`, `info`, `warn` (or `warning`), `error` and `off`, and with the
+    /// `std` feature, the aliases registered with `register_level_alias`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use loggery::Level;
+    ///
+    /// assert_eq!("info".parse::<Level>(), Ok(Level::Info));
+    /// assert_eq!("WARNING".parse::<Level>(), Ok(Level::Warn));
+    /// assert!("verbose".parse::<Level>().is_err());
+    ///
+    /// // Round-trips with `as_str`
+    /// assert_eq!(Level::Info.as_str().parse::<Level>(), Ok(Level::Info));
+    /// ```
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let names = [
+            ("trace", Level::Trace),
+            ("debug", Level::Debug),
+            ("info", Level::Info),
+            ("warn", Level::Warn),
+            ("warning", Level::Warn),
+            ("error", Level::Error),
+            ("off", Level::Off),
+        ];
+
+        let level = names
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case(s))
+            .map(|(_, level)| *level);
+
+        #[cfg(feature = "std")]
+        let level = level.or_else(|| aliases::find(s));
+
+        level.ok_or(LevelParseError(()))
+    }
+}
+
+/// The error returned when parsing an unknown [`Level`] name.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LevelParseError(());
+
+impl core::fmt::Display for LevelParseError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str("unknown log level, expected one of: trace, debug, info, warn, error, off")
+    }
 }
 
+#[cfg(feature = "std")]
+impl std::error::Error for LevelParseError {}
+
 #[cfg(feature = "metadata")]
 /// Extra context and information for a log.
+#[derive(Clone, Copy)]
 pub struct Metadata {
     /// The module path where the log was generated.
     pub module_path: &'static str,
+    /// The target of the log, set with `info!(target: "net", ...)`, or the module path by default.
+    ///
+    /// Module levels set with `set_module_level` are matched against the target (`module_level`
+    /// feature), like the targets of the `log` crate.
+    pub target: &'static str,
     /// The source file containing the log call.
     pub file: &'static str,
     /// The line number of the log call.
     pub line: u32,
+    /// The ID of the thread that logged, see [`current_thread_id`] (requires `thread_meta`
+    /// feature).
+    ///
+    /// Thread names aren't `'static`, so only the ID is stored. Loggers run on the thread that
+    /// logged, so they can get the name with `std::thread::current().name()`.
+    #[cfg(feature = "thread_meta")]
+    pub thread_id: u64,
+    /// The CPU core the thread was running on at the log call, see [`current_cpu`] (requires
+    /// `cpu_meta` feature).
+    ///
+    /// `None` on targets other than Linux. Best-effort: the thread may have been migrated to
+    /// another core by the time the log is written.
+    #[cfg(feature = "cpu_meta")]
+    pub cpu: Option<u32>,
+}
+
+#[cfg(feature = "metadata")]
+impl Metadata {
+    /// Creates the metadata of a log call, capturing the thread with the `thread_meta` feature and
+    /// the CPU core with the `cpu_meta` feature.
+    ///
+    /// Used internally by the [`log!`] macro, so it keeps working regardless of enabled features.
+    #[doc(hidden)]
+    #[inline(always)]
+    pub fn __new(module_path: &'static str, file: &'static str, line: u32) -> Self {
+        Self::__with_target(module_path, module_path, file, line)
+    }
+
+    /// Creates the metadata of a log call with an explicit target, see [`Metadata::__new`].
+    #[doc(hidden)]
+    #[inline(always)]
+    pub fn __with_target(
+        target: &'static str,
+        module_path: &'static str,
+        file: &'static str,
+        line: u32,
+    ) -> Self {
+        Self {
+            module_path,
+            target,
+            file,
+            line,
+            #[cfg(feature = "thread_meta")]
+            thread_id: current_thread_id(),
+            #[cfg(feature = "cpu_meta")]
+            cpu: current_cpu(),
+        }
+    }
 }
 
 /// The data passed to the logger and extensions.
+///
+/// The message is passed as [`core::fmt::Arguments`], rendered only when a logger or extension
+/// formats it: the dispatch to the extension and the loggers never renders it. An extension (or
+/// logger) that only reads the [`level`](Payload::level) (e.g., to count logs per level for
+/// metrics) doesn't pay for the formatting at all, see [`Payload::level_u8`].
+#[derive(Clone, Copy)]
 pub struct Payload<'a> {
     /// The severity level of the log.
     pub level: Level,
@@ -272,6 +743,185 @@ pub struct Payload<'a> {
     #[cfg(feature = "metadata")]
     /// Additional context and metadata (requires `metadata` feature).
     pub meta: Metadata,
+    #[cfg(feature = "timestamp")]
+    /// Milliseconds since the UNIX epoch, captured at the log call (requires `timestamp` feature).
+    ///
+    /// Captured from the system clock with the `std` feature, or from the clock set with
+    /// `set_clock`.
+    pub timestamp: u64,
+    #[cfg(feature = "kv")]
+    /// Structured `key = value` fields of the log, e.g., `info!(user_id = 42; "Logged in")`, in
+    /// call order (requires `kv` feature).
+    ///
+    /// The values are formatted lazily, like the message. Logs without fields have an empty slice.
+    pub fields: &'a [(&'a str, core::fmt::Arguments<'a>)],
+    #[cfg(feature = "seq")]
+    /// Sequence number of the log, increasing by one with each emitted log (requires `seq`
+    /// feature).
+    ///
+    /// It's assigned by [`log()`] once the log passes the filters, right before it's passed to the
+    /// extensions and the loggers, so every sink sees the same number for the same log, and the
+    /// order of the logs split across sinks (e.g., stdout and a file) can be rebuilt from it. The
+    /// first log is `1`: logs that didn't go through [`log()`] (e.g., passed to [`emit()`]
+    /// directly, or replayed by `dump_recent`) have `0`.
+    ///
+    /// The counter is global to the process, starting over at `1` on every run. The `{seq}` token
+    /// of `set_format` renders it in the default logger.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use loggery::{info, warn, Payload};
+    /// use std::cell::RefCell;
+    ///
+    /// thread_local! {
+    ///     static STDOUT: RefCell<Vec<u64>> = RefCell::new(Vec::new());
+    ///     static FILE: RefCell<Vec<u64>> = RefCell::new(Vec::new());
+    /// }
+    ///
+    /// fn stdout_logger(payload: Payload) {
+    ///     STDOUT.with(|seqs| seqs.borrow_mut().push(payload.seq));
+    /// }
+    ///
+    /// fn file_logger(payload: Payload) {
+    ///     FILE.with(|seqs| seqs.borrow_mut().push(payload.seq));
+    /// }
+    ///
+    /// fn main() {
+    /// #   #[cfg(not(feature = "static"))]
+    /// #   {
+    ///     loggery::set_logger(stdout_logger);
+    ///     loggery::add_logger(file_logger);
+    /// #   }
+    ///
+    ///     info!("Listening");
+    ///     warn!("Slow request");
+    ///
+    /// #   #[cfg(all(not(feature = "static"), not(any(feature = "min_level_warn",
+    /// #       feature = "min_level_error", feature = "min_level_off"))))]
+    /// #   {
+    ///     let stdout = STDOUT.with(|seqs| seqs.borrow().clone());
+    ///     let file = FILE.with(|seqs| seqs.borrow().clone());
+    ///
+    ///     // Both sinks see the same numbers, in emission order
+    ///     assert_eq!(stdout, file);
+    ///     assert_eq!(stdout[1], stdout[0] + 1);
+    /// #   }
+    /// }
+    /// ```
+    pub seq: u64,
+}
+
+impl<'a> Payload<'a> {
+    /// Returns the level as its raw `u8` value (`0` = [`Level::Trace`] to `4` = [`Level::Error`]),
+    /// e.g., to index per-level counters.
+    ///
+    /// # Example
+    ///
+    /// A level-only extension counting logs per level, which never touches the message, so it's
+    /// never rendered for it:
+    ///
+    /// ```
+    /// use loggery::{error, Payload};
+    /// use std::fmt;
+    /// use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+    ///
+    /// static LEVEL_COUNTS: [AtomicUsize; 5] = [
+    ///     AtomicUsize::new(0),
+    ///     AtomicUsize::new(0),
+    ///     AtomicUsize::new(0),
+    ///     AtomicUsize::new(0),
+    ///     AtomicUsize::new(0),
+    /// ];
+    /// static RENDERED: AtomicBool = AtomicBool::new(false);
+    ///
+    /// fn count_levels(payload: &Payload) {
+    ///     if let Some(count) = LEVEL_COUNTS.get(payload.level_u8() as usize) {
+    ///         count.fetch_add(1, Ordering::Relaxed);
+    ///     }
+    /// }
+    ///
+    /// fn quiet_logger(_: Payload) {}
+    ///
+    /// struct Expensive;
+    ///
+    /// impl fmt::Display for Expensive {
+    ///     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    ///         RENDERED.store(true, Ordering::Relaxed);
+    ///         f.write_str("expensive")
+    ///     }
+    /// }
+    ///
+    /// fn main() {
+    /// #   #[cfg(all(feature = "extension", not(feature = "static")))]
+    /// #   {
+    ///     loggery::set_extension(count_levels);
+    ///     loggery::set_logger(quiet_logger);
+    /// #   }
+    ///
+    ///     error!("{}", Expensive);
+    ///
+    /// #   #[cfg(all(feature = "extension", not(feature = "static"),
+    /// #       not(feature = "min_level_off")))]
+    ///     assert_eq!(LEVEL_COUNTS[4].load(Ordering::Relaxed), 1);
+    ///     // The message was never rendered
+    /// #   #[cfg(all(feature = "extension", not(feature = "static")))]
+    ///     assert!(!RENDERED.load(Ordering::Relaxed));
+    /// }
+    /// ```
+    #[inline(always)]
+    pub const fn level_u8(&self) -> u8 {
+        self.level as u8
+    }
+
+    /// Creates a payload, capturing everything that has to be captured at the log call site.
+    ///
+    /// Used internally by the [`log!`] macro, so it keeps working regardless of enabled features.
+    #[doc(hidden)]
+    #[inline(always)]
+    pub fn __new(
+        level: Level,
+        args: core::fmt::Arguments<'a>,
+        #[cfg(feature = "metadata")] meta: Metadata,
+    ) -> Self {
+        Self {
+            level,
+            args,
+            #[cfg(feature = "metadata")]
+            meta,
+            #[cfg(feature = "timestamp")]
+            timestamp: timestamp::now(),
+            #[cfg(feature = "kv")]
+            fields: &[],
+            #[cfg(feature = "seq")]
+            seq: 0,
+        }
+    }
+
+    /// Creates a payload with `key = value` fields, which are only stored with the `kv` feature
+    /// (without it, the [`log!`] macro appends them to the message instead).
+    #[doc(hidden)]
+    #[inline(always)]
+    pub fn __with_fields(
+        level: Level,
+        args: core::fmt::Arguments<'a>,
+        fields: &'a [(&'a str, core::fmt::Arguments<'a>)],
+        #[cfg(feature = "metadata")] meta: Metadata,
+    ) -> Self {
+        #[cfg(not(feature = "kv"))]
+        let _ = fields;
+
+        Self {
+            #[cfg(feature = "kv")]
+            fields,
+            ..Self::__new(
+                level,
+                args,
+                #[cfg(feature = "metadata")]
+                meta,
+            )
+        }
+    }
 }
 
 /// Function type for custom logger implementation.
@@ -281,6 +931,44 @@ pub type LoggerFn = fn(Payload);
 #[cfg(feature = "extension")]
 pub type ExtensionFn = fn(&Payload);
 
+#[cfg(any(
+    all(
+        feature = "min_level_off",
+        any(
+            feature = "min_level_trace",
+            feature = "min_level_debug",
+            feature = "min_level_info",
+            feature = "min_level_warn",
+            feature = "min_level_error"
+        )
+    ),
+    all(
+        feature = "min_level_trace",
+        any(
+            feature = "min_level_debug",
+            feature = "min_level_info",
+            feature = "min_level_warn",
+            feature = "min_level_error"
+        )
+    ),
+    all(
+        feature = "min_level_debug",
+        any(
+            feature = "min_level_info",
+            feature = "min_level_warn",
+            feature = "min_level_error"
+        )
+    ),
+    all(
+        feature = "min_level_info",
+        any(feature = "min_level_warn", feature = "min_level_error")
+    ),
+    all(feature = "min_level_warn", feature = "min_level_error"),
+))]
+compile_error!(
+    "loggery: the `min_level_*` features are mutually exclusive, enable only one of them"
+);
+
 /// Compile-time minimum log level set by `min_level_*` feature flags.
 ///
 /// If no specific level is set, all logs are enabled by default (`min_level_trace`).
@@ -336,18 +1024,58 @@ extern "Rust" {
     fn __loggery_extension_impl(payload: &Payload);
 }
 
-/// Global logger function pointer storage. (NOT `static` feature)
+/// Empty function slot, used to initialize [`LOGGERS`] and `EXTENSIONS`.
 #[cfg(not(feature = "static"))]
-static LOGGER_FN: core::sync::atomic::AtomicPtr<()> =
+#[allow(clippy::declare_interior_mutable_const)]
+const NO_FN: core::sync::atomic::AtomicPtr<()> =
     core::sync::atomic::AtomicPtr::new(core::ptr::null_mut());
-/// Global extension function pointer storage. (`extension` feature, NOT `static` feature)
+/// Global logger function pointers storage, filled in order. (NOT `static` feature)
+#[cfg(not(feature = "static"))]
+static LOGGERS: [core::sync::atomic::AtomicPtr<()>; MAX_LOGGERS] = [NO_FN; MAX_LOGGERS];
+/// Global extension function pointers storage, filled in order. (`extension` feature, NOT
+/// `static` feature)
 #[cfg(all(feature = "extension", not(feature = "static")))]
-static EXTENSION_FN: core::sync::atomic::AtomicPtr<()> =
-    core::sync::atomic::AtomicPtr::new(core::ptr::null_mut());
+static EXTENSIONS: [core::sync::atomic::AtomicPtr<()>; MAX_EXTENSIONS] = [NO_FN; MAX_EXTENSIONS];
+/// Policy for raw levels that aren't a valid [`Level`] storage.
+static INVALID_LEVEL_POLICY: core::sync::atomic::AtomicU8 =
+    core::sync::atomic::AtomicU8::new(InvalidLevelPolicy::Clamp as u8);
+/// Number of logs dropped because of an invalid raw level storage. (`std` feature)
+#[cfg(feature = "std")]
+static INVALID_LEVEL_COUNT: core::sync::atomic::AtomicUsize =
+    core::sync::atomic::AtomicUsize::new(0);
 /// Runtime minimum log level storage. (`runtime_level` feature)
 #[cfg(feature = "runtime_level")]
 static RUNTIME_MIN_LEVEL: core::sync::atomic::AtomicU8 =
     core::sync::atomic::AtomicU8::new(Level::Trace as u8);
+/// Runtime maximum log level storage. (`runtime_level` feature)
+#[cfg(feature = "runtime_level")]
+static RUNTIME_MAX_LEVEL: core::sync::atomic::AtomicU8 =
+    core::sync::atomic::AtomicU8::new(Level::Error as u8);
+/// Lowest level the runtime minimum log level can be set to. (`runtime_level` feature)
+#[cfg(feature = "runtime_level")]
+static RUNTIME_LEVEL_FLOOR: core::sync::atomic::AtomicU8 =
+    core::sync::atomic::AtomicU8::new(Level::Trace as u8);
+/// Whether the runtime minimum log level was explicitly set. (`runtime_level` feature)
+#[cfg(feature = "runtime_level")]
+static RUNTIME_MIN_LEVEL_SET: core::sync::atomic::AtomicBool =
+    core::sync::atomic::AtomicBool::new(false);
+/// Per-module runtime minimum log levels storage. (`module_level` feature)
+#[cfg(feature = "module_level")]
+static MODULE_LEVELS: spin::Mutex<[Option<(&'static str, Level)>; MAX_MODULE_LEVELS]> =
+    spin::Mutex::new([None; MAX_MODULE_LEVELS]);
+
+/// Maximum number of loggers that can be registered with [`add_logger`]. (NOT `static` feature)
+#[cfg(not(feature = "static"))]
+pub const MAX_LOGGERS: usize = 8;
+
+/// Maximum number of extensions that can be registered with [`add_extension`]. (`extension`
+/// feature, NOT `static` feature)
+#[cfg(all(feature = "extension", not(feature = "static")))]
+pub const MAX_EXTENSIONS: usize = 8;
+
+/// Maximum number of modules that can have their own level. (`module_level` feature)
+#[cfg(feature = "module_level")]
+pub const MAX_MODULE_LEVELS: usize = 16;
 
 /// Sets the global logger function. (NOT `static` feature)
 ///
@@ -388,47 +1116,309 @@ static RUNTIME_MIN_LEVEL: core::sync::atomic::AtomicU8 =
 ///
 /// When the `std` feature is enabled, a default logger is automatically initialized if no logger
 /// has been set. This function can still be used to override that default.
+///
+/// Without the `std` feature, logs emitted before calling this function are silently dropped.
+/// Enable the `assert_logger` feature to catch that during development: logging without a logger
+/// then panics, but only in debug builds (it's a `debug_assert!`, so release builds are unaffected).
+///
+/// ```
+/// # #[cfg(all(feature = "assert_logger", not(feature = "std"), debug_assertions))]
+/// # {
+/// let result = std::panic::catch_unwind(|| loggery::info!("Too early!"));
+///
+/// assert!(result.is_err());
+/// # }
+/// ```
 #[cfg(not(feature = "static"))]
 #[inline(always)]
 pub fn set_logger(logger_fn: LoggerFn) {
-    LOGGER_FN.store(logger_fn as *mut (), core::sync::atomic::Ordering::Release)
+    LOGGERS[0].store(logger_fn as *mut (), core::sync::atomic::Ordering::Release);
+
+    for slot in LOGGERS[1..].iter() {
+        slot.store(core::ptr::null_mut(), core::sync::atomic::Ordering::Release);
+    }
 }
 
-/// Sets the global extension function. (`extension` feature, NOT `static` feature)
+/// Adds a logger function, called after the already registered ones. (NOT `static` feature)
 ///
-/// Extensions are called before the logger and receive a reference to the [`Payload`], giving us
-/// the ability to do additional functionality like saving logs to file.
+/// Every log is passed to all registered loggers, in the order they were added, e.g., to send the
+/// same log to stdout and to a file-backed logger. [`set_logger`] replaces all of them with a
+/// single logger.
 ///
-/// It's recommended to call once during the initialization.
+/// With the `std` feature, if no logger was set yet, the default stdout logger is kept as the
+/// first one.
+///
+/// Returns `false` if the logger couldn't be added because [`MAX_LOGGERS`] loggers are already
+/// registered.
+///
+/// Each logger formats the log its own way, e.g., the console logger writes `[LEVEL] message`
+/// while the file logger writes JSON with `extensions::to_json`. The [`Payload`] is shared, not
+/// cloned: its `args` are rendered lazily, so every logger renders them again from the same
+/// payload. The built-in sinks take a formatter as well, see
+/// [`StreamRouting::route_with_format`].
+///
+/// # Note
+///
+/// Registering isn't synchronized (so it also works on targets without compare-and-swap atomics),
+/// loggers should be added from a single thread during the initialization. Logging concurrently
+/// with registering is fine.
 ///
 /// # Example
 ///
-/// ```no_run
-/// use loggery::{Payload, debug};
+/// ```
+/// use loggery::{Payload, info};
+/// use std::sync::atomic::{AtomicUsize, Ordering};
 ///
-/// fn my_extension(payload: &Payload) {
-///     // Your custom implementation
+/// static CONSOLE_LOGS: AtomicUsize = AtomicUsize::new(0);
+/// static FILE_LOGS: AtomicUsize = AtomicUsize::new(0);
 ///
-///     // For example, you can use the provided extension `save_to_file`
-///     let _ = loggery::extensions::save_to_file(payload, "path/to/app.log");
+/// fn console_logger(_: Payload) {
+///     CONSOLE_LOGS.fetch_add(1, Ordering::Relaxed);
+/// }
+///
+/// fn file_logger(_: Payload) {
+///     FILE_LOGS.fetch_add(1, Ordering::Relaxed);
 /// }
 ///
 /// fn main() {
-///     loggery::set_extension(my_extension);
+///     loggery::set_logger(console_logger);
+///     assert!(loggery::add_logger(file_logger));
 ///
-///     debug!("A log message that will be saved to a file too!");
+///     info!("Sent to both loggers");
+///
+/// #   #[cfg(not(feature = "min_level_off"))]
+/// #   {
+///     assert_eq!(CONSOLE_LOGS.load(Ordering::Relaxed), 1);
+///     assert_eq!(FILE_LOGS.load(Ordering::Relaxed), 1);
+/// #   }
+///
+///     // Fill the remaining slots
+///     for _ in 2..loggery::MAX_LOGGERS {
+///         assert!(loggery::add_logger(file_logger));
+///     }
+///
+///     assert!(!loggery::add_logger(file_logger));
 /// }
 /// ```
+#[cfg(not(feature = "static"))]
+pub fn add_logger(logger_fn: LoggerFn) -> bool {
+    // Initializes the default logger in the first slot if none was set
+    #[cfg(feature = "std")]
+    let _ = get_logger();
+
+    for slot in LOGGERS.iter() {
+        if slot.load(core::sync::atomic::Ordering::Acquire).is_null() {
+            slot.store(logger_fn as *mut (), core::sync::atomic::Ordering::Release);
+
+            return true;
+        }
+    }
+
+    false
+}
+
+/// Removes all loggers set with [`set_logger`] and [`add_logger`]. (NOT `static` feature)
 ///
-/// # Note
+/// With the `std` feature, the default stdout logger is initialized again on the next log, as if
+/// no logger was ever set. Without it, logs are dropped until a logger is set.
 ///
-/// When the `static` feature is enabled, this function isn't available. Instead, you must define
-/// this function in your binary crate:
+/// Mostly useful in tests, to undo a logger installed by a previous test.
 ///
-/// ```no_run
-/// use loggery::Payload;
+/// # Example
 ///
-/// #[no_mangle]
+/// ```
+/// use loggery::{Payload, info};
+/// use std::sync::atomic::{AtomicUsize, Ordering};
+///
+/// static CAPTURED: AtomicUsize = AtomicUsize::new(0);
+///
+/// fn capturing_logger(_: Payload) {
+///     CAPTURED.fetch_add(1, Ordering::Relaxed);
+/// }
+///
+/// fn main() {
+///     loggery::set_logger(capturing_logger);
+///     info!("Captured");
+///
+///     loggery::clear_logger();
+///     info!("Back to the default logger");
+///
+/// #   #[cfg(not(feature = "min_level_off"))]
+///     assert_eq!(CAPTURED.load(Ordering::Relaxed), 1);
+///
+///     // Without the `std` feature, there's no logger anymore
+/// #   #[cfg(not(feature = "std"))]
+///     assert!(loggery::diagnose_config().contains(loggery::ConfigIssue::NoLogger));
+/// }
+/// ```
+#[cfg(not(feature = "static"))]
+#[inline(always)]
+pub fn clear_logger() {
+    for slot in LOGGERS.iter() {
+        slot.store(core::ptr::null_mut(), core::sync::atomic::Ordering::Release);
+    }
+}
+
+/// Sets the global logger function until the returned guard is dropped. (NOT `static` feature)
+///
+/// Like [`set_logger`], it replaces all the registered loggers. When the [`LogGuard`] is dropped,
+/// the loggers registered before the call are restored, or no logger if none was set (so the
+/// default logger is initialized again with the `std` feature).
+///
+/// Guards can be nested: dropping them in the reverse order of their creation (LIFO, like
+/// variables going out of scope) restores each previous state in turn. Dropping them in another
+/// order restores whatever the dropped guard saw when it was created.
+///
+/// # Example
+///
+/// ```
+/// use loggery::{Payload, info};
+/// use std::sync::atomic::{AtomicUsize, Ordering};
+///
+/// static OUTER: AtomicUsize = AtomicUsize::new(0);
+/// static INNER: AtomicUsize = AtomicUsize::new(0);
+///
+/// fn outer_logger(_: Payload) {
+///     OUTER.fetch_add(1, Ordering::Relaxed);
+/// }
+///
+/// fn inner_logger(_: Payload) {
+///     INNER.fetch_add(1, Ordering::Relaxed);
+/// }
+///
+/// fn main() {
+///     let _outer = loggery::set_logger_scoped(outer_logger);
+///
+///     {
+///         let _inner = loggery::set_logger_scoped(inner_logger);
+///
+///         info!("To the inner logger");
+///     }
+///
+///     info!("Back to the outer logger");
+///
+/// #   #[cfg(not(feature = "min_level_off"))]
+/// #   {
+///     assert_eq!(INNER.load(Ordering::Relaxed), 1);
+///     assert_eq!(OUTER.load(Ordering::Relaxed), 1);
+/// #   }
+/// }
+/// ```
+#[cfg(not(feature = "static"))]
+#[inline]
+pub fn set_logger_scoped(logger_fn: LoggerFn) -> LogGuard {
+    let mut previous = [None; MAX_LOGGERS];
+
+    for (previous, slot) in previous.iter_mut().zip(LOGGERS.iter()) {
+        let ptr = slot.load(core::sync::atomic::Ordering::Acquire);
+
+        if !ptr.is_null() {
+            *previous = Some(ptr_to_logger_fn(ptr));
+        }
+    }
+
+    set_logger(logger_fn);
+
+    LogGuard { previous }
+}
+
+/// Restores the loggers registered before [`set_logger_scoped`] when dropped.
+/// (NOT `static` feature)
+#[cfg(not(feature = "static"))]
+#[must_use = "the previous logger is restored as soon as the guard is dropped"]
+pub struct LogGuard {
+    previous: [Option<LoggerFn>; MAX_LOGGERS],
+}
+
+#[cfg(not(feature = "static"))]
+impl Drop for LogGuard {
+    fn drop(&mut self) {
+        for (previous, slot) in self.previous.iter().zip(LOGGERS.iter()) {
+            let ptr = match previous {
+                Some(logger_fn) => *logger_fn as *mut (),
+                None => core::ptr::null_mut(),
+            };
+
+            slot.store(ptr, core::sync::atomic::Ordering::Release);
+        }
+    }
+}
+
+/// Sets the global extension function. (`extension` feature, NOT `static` feature)
+///
+/// Extensions are called before the logger and receive a reference to the [`Payload`], giving us
+/// the ability to do additional functionality like saving logs to file.
+///
+/// This replaces all extensions registered with [`add_extension`] with a single one.
+///
+/// It's recommended to call once during the initialization.
+///
+/// # Example
+///
+/// ```no_run
+/// use loggery::{Payload, debug};
+///
+/// fn my_extension(payload: &Payload) {
+///     // Your custom implementation
+///
+///     // For example, you can use the provided extension `save_to_file`
+///     let _ = loggery::extensions::save_to_file(payload, "path/to/app.log");
+/// }
+///
+/// fn main() {
+///     loggery::set_extension(my_extension);
+///
+///     debug!("A log message that will be saved to a file too!");
+/// }
+/// ```
+///
+/// # Ordering
+///
+/// For every log that passes the level filters, the extension is guaranteed to run to completion
+/// *before* the logger is called, on the same thread and with the same [`Payload`].
+///
+/// If the extension panics, the panic propagates out of the logging macro and the logger is
+/// **not** called for that log.
+///
+/// ```
+/// use loggery::{Payload, info};
+/// use std::sync::atomic::{AtomicUsize, Ordering};
+///
+/// static STEP: AtomicUsize = AtomicUsize::new(0);
+/// static EXTENSION_STEP: AtomicUsize = AtomicUsize::new(0);
+/// static LOGGER_STEP: AtomicUsize = AtomicUsize::new(0);
+///
+/// fn my_extension(_: &Payload) {
+///     EXTENSION_STEP.store(STEP.fetch_add(1, Ordering::SeqCst) + 1, Ordering::SeqCst);
+/// }
+///
+/// fn my_logger(_: Payload) {
+///     LOGGER_STEP.store(STEP.fetch_add(1, Ordering::SeqCst) + 1, Ordering::SeqCst);
+/// }
+///
+/// fn main() {
+///     loggery::set_extension(my_extension);
+///     loggery::set_logger(my_logger);
+///
+///     info!("Ordered!");
+///
+/// #   #[cfg(not(feature = "min_level_off"))]
+/// #   {
+///     assert_eq!(EXTENSION_STEP.load(Ordering::SeqCst), 1);
+///     assert_eq!(LOGGER_STEP.load(Ordering::SeqCst), 2);
+/// #   }
+/// }
+/// ```
+///
+/// # Note
+///
+/// When the `static` feature is enabled, this function isn't available. Instead, you must define
+/// this function in your binary crate:
+///
+/// ```no_run
+/// use loggery::Payload;
+///
+/// #[no_mangle]
 /// pub extern "Rust" fn __loggery_extension_impl(payload: &Payload) {
 ///     // Your custom implementation
 /// }
@@ -436,10 +1426,84 @@ pub fn set_logger(logger_fn: LoggerFn) {
 #[cfg(all(feature = "extension", not(feature = "static")))]
 #[inline(always)]
 pub fn set_extension(extension_fn: ExtensionFn) {
-    EXTENSION_FN.store(
+    EXTENSIONS[0].store(
         extension_fn as *mut (),
         core::sync::atomic::Ordering::Release,
-    )
+    );
+
+    for slot in EXTENSIONS[1..].iter() {
+        slot.store(core::ptr::null_mut(), core::sync::atomic::Ordering::Release);
+    }
+}
+
+/// Adds an extension function, called after the already registered ones. (`extension` feature,
+/// NOT `static` feature)
+///
+/// Every log is passed to all registered extensions, in the order they were added, before the
+/// loggers are called, e.g., to save logs to a file *and* count them for a metrics collector.
+/// [`set_extension`] replaces all of them with a single extension.
+///
+/// Returns `false` if the extension couldn't be added because [`MAX_EXTENSIONS`] extensions are
+/// already registered: it's dropped, the registered ones are kept.
+///
+/// # Note
+///
+/// Registering isn't synchronized (so it also works on targets without compare-and-swap atomics),
+/// extensions should be added from a single thread during the initialization. Logging
+/// concurrently with registering is fine.
+///
+/// # Example
+///
+/// ```
+/// use loggery::{Payload, info};
+/// use std::sync::atomic::{AtomicUsize, Ordering};
+///
+/// static STEP: AtomicUsize = AtomicUsize::new(0);
+/// static FILE_STEP: AtomicUsize = AtomicUsize::new(0);
+/// static METRICS_STEP: AtomicUsize = AtomicUsize::new(0);
+///
+/// fn file_extension(_: &Payload) {
+///     FILE_STEP.store(STEP.fetch_add(1, Ordering::SeqCst) + 1, Ordering::SeqCst);
+/// }
+///
+/// fn metrics_extension(_: &Payload) {
+///     METRICS_STEP.store(STEP.fetch_add(1, Ordering::SeqCst) + 1, Ordering::SeqCst);
+/// }
+///
+/// fn main() {
+///     loggery::set_extension(file_extension);
+///     assert!(loggery::add_extension(metrics_extension));
+///
+///     info!("Sent to both extensions");
+///
+/// #   #[cfg(not(feature = "min_level_off"))]
+/// #   {
+///     assert_eq!(FILE_STEP.load(Ordering::SeqCst), 1);
+///     assert_eq!(METRICS_STEP.load(Ordering::SeqCst), 2);
+/// #   }
+///
+///     // Fill the remaining slots
+///     for _ in 2..loggery::MAX_EXTENSIONS {
+///         assert!(loggery::add_extension(metrics_extension));
+///     }
+///
+///     assert!(!loggery::add_extension(metrics_extension));
+/// }
+/// ```
+#[cfg(all(feature = "extension", not(feature = "static")))]
+pub fn add_extension(extension_fn: ExtensionFn) -> bool {
+    for slot in EXTENSIONS.iter() {
+        if slot.load(core::sync::atomic::Ordering::Acquire).is_null() {
+            slot.store(
+                extension_fn as *mut (),
+                core::sync::atomic::Ordering::Release,
+            );
+
+            return true;
+        }
+    }
+
+    false
 }
 
 /// Sets the runtime minimum log level. (`runtime_level` feature)
@@ -451,7 +1515,7 @@ pub fn set_extension(extension_fn: ExtensionFn) {
 ///
 /// This cannot enable levels that were filtered at compile time.
 /// If compiled with feature `min_level_info`, calling `set_min_level(Level::Debug)` will have
-/// no effect.
+/// no effect. [`diagnose_config`] reports it as [`ConfigIssue::RuntimeLevelBelowCompileTime`].
 ///
 /// # Example
 ///
@@ -463,342 +1527,2395 @@ pub fn set_extension(extension_fn: ExtensionFn) {
 /// debug!("This will NOT be logged");
 /// warn!("This will be logged");
 /// ```
+///
+/// Use [`Level::Off`] to disable all logs at runtime:
+///
+/// ```
+/// use loggery::{Level, error};
+///
+/// loggery::set_min_level(Level::Off);
+///
+/// error!("This will NOT be logged either");
+///
+/// assert!(!loggery::is_enabled(Level::Error));
+/// # #[cfg(not(feature = "min_level_off"))]
+/// assert_eq!(loggery::get_min_level(), Some(Level::Off));
+/// ```
+///
+/// A level below the floor set with [`set_level_floor`] is clamped to the floor.
 #[cfg(feature = "runtime_level")]
 #[inline(always)]
 pub fn set_min_level(level: Level) {
-    RUNTIME_MIN_LEVEL.store(level as u8, core::sync::atomic::Ordering::Release);
+    let floor = RUNTIME_LEVEL_FLOOR.load(core::sync::atomic::Ordering::Acquire);
+
+    RUNTIME_MIN_LEVEL.store(
+        (level as u8).max(floor),
+        core::sync::atomic::Ordering::Release,
+    );
+    RUNTIME_MIN_LEVEL_SET.store(true, core::sync::atomic::Ordering::Relaxed);
 }
 
-/// Returns the effective minimum log level (the stricter of compile-time and runtime levels).
+/// Sets the runtime maximum log level, [`Level::Error`] (no maximum) by default.
+/// (`runtime_level` feature)
+///
+/// Logs above it are filtered out, so only the levels from the minimum level to the maximum level
+/// are emitted, e.g., to drop [`error!`] logs in a fuzz target where errors are expected noise,
+/// while keeping [`debug!`] ones.
+///
+/// # Interaction with the minimum levels
+///
+/// The maximum level is checked on top of the minimum ones: the `min_level_*` features,
+/// [`set_min_level`] and the module levels (`module_level` feature), which only replace the
+/// global minimum level. A maximum level below the minimum level leaves an empty range: nothing is
+/// emitted. [`Level::Off`] has the same effect as [`Level::Error`], no log is above it.
+///
+/// For an arbitrary set of levels rather than a range, see `set_level_mask` (`level_mask`
+/// feature).
 ///
 /// # Example
 ///
 /// ```
-/// use loggery::Level;
+/// use loggery::{debug, error, Level};
 ///
-/// # #[cfg(feature = "runtime_level")]
-/// loggery::set_min_level(Level::Debug);
+/// loggery::set_max_level(Level::Warn);
 ///
-/// # #[cfg(all(feature = "runtime_level", not(feature = "min_level_off")))]
-/// assert_eq!(loggery::get_min_level(), Some(Level::Debug));
+/// debug!("This will be logged");
+/// error!("This will NOT be logged");
+///
+/// assert!(!loggery::is_enabled(Level::Error));
+/// assert_eq!(loggery::get_max_level(), Level::Warn);
+///
+/// // Empty range
+/// loggery::set_min_level(Level::Error);
+///
+/// assert!(Level::iter().all(|level| !loggery::is_enabled(level)));
 /// ```
+#[cfg(feature = "runtime_level")]
 #[inline(always)]
-pub fn get_min_level() -> Option<Level> {
-    let compile_time = COMPILE_TIME_MIN_LEVEL?;
-
-    let level = {
-        #[cfg(feature = "runtime_level")]
-        {
-            let runtime = RUNTIME_MIN_LEVEL.load(core::sync::atomic::Ordering::Relaxed);
-
-            // Return whichever is stricter (higher level)
-            compile_time.max(runtime)
-        }
-
-        #[cfg(not(feature = "runtime_level"))]
-        compile_time
-    };
+pub fn set_max_level(level: Level) {
+    RUNTIME_MAX_LEVEL.store(level as u8, core::sync::atomic::Ordering::Release);
+}
 
-    Level::from_u8(level)
+/// Returns the runtime maximum log level set with [`set_max_level`]. (`runtime_level` feature)
+#[cfg(feature = "runtime_level")]
+#[inline(always)]
+pub fn get_max_level() -> Level {
+    Level::from_u8(RUNTIME_MAX_LEVEL.load(core::sync::atomic::Ordering::Relaxed))
+        .unwrap_or(Level::Error)
 }
 
-/// Core logging entry point used internally by macros like [`debug!`] and [`error!`].
+/// Sets the runtime minimum log level from the environment variable `var_name`, e.g.,
+/// `RUST_LOG`. (`std` and `runtime_level` features)
 ///
-/// It's recommended to use the macros instead of calling directly.
-#[inline(always)]
-pub fn log(payload: Payload) {
-    let is_compile_time_enabled = match COMPILE_TIME_MIN_LEVEL {
-        Some(level) => (payload.level as u8) >= level,
-        None => false,
+/// The value is parsed as a [`Level`] name (ignoring the case and the surrounding whitespace, see
+/// [`Level::from_str`](core::str::FromStr::from_str)) and passed to [`set_min_level`]. Returns the
+/// level set, or `Ok(None)` without changing anything if the variable isn't set, so that only a
+/// bad value is an error, which the startup code can report.
+///
+/// Only a single level is accepted: `env_logger`-style directives (e.g., `my_crate=debug`) are
+/// rejected.
+///
+/// # Example
+///
+/// ```
+/// use loggery::Level;
+///
+/// std::env::set_var("MY_APP_LOG", "warn");
+/// assert_eq!(loggery::set_min_level_from_env("MY_APP_LOG"), Ok(Some(Level::Warn)));
+/// # #[cfg(not(any(feature = "min_level_error", feature = "min_level_off")))]
+/// assert_eq!(loggery::get_min_level(), Some(Level::Warn));
+///
+/// std::env::remove_var("MY_APP_LOG");
+/// assert_eq!(loggery::set_min_level_from_env("MY_APP_LOG"), Ok(None));
+///
+/// std::env::set_var("MY_APP_LOG", "loud");
+/// assert!(loggery::set_min_level_from_env("MY_APP_LOG").is_err());
+/// ```
+#[cfg(all(feature = "std", feature = "runtime_level"))]
+pub fn set_min_level_from_env(var_name: &str) -> Result<Option<Level>, LevelParseError> {
+    let value = match std::env::var(var_name) {
+        Ok(value) => value,
+        Err(std::env::VarError::NotPresent) => return Ok(None),
+        Err(std::env::VarError::NotUnicode(_)) => return Err(LevelParseError(())),
     };
 
-    if !is_compile_time_enabled {
-        return;
-    }
+    let level = value.parse()?;
 
-    #[cfg(feature = "runtime_level")]
-    {
-        let runtime_min_level = RUNTIME_MIN_LEVEL.load(core::sync::atomic::Ordering::Relaxed);
+    set_min_level(level);
 
-        if (payload.level as u8) < runtime_min_level {
-            return;
-        }
-    }
+    Ok(Some(level))
+}
 
-    #[cfg(all(feature = "extension", feature = "static"))]
-    {
-        unsafe { __loggery_extension_impl(&payload) };
-    }
+/// Sets the lowest level the runtime minimum log level can be set to. (`runtime_level` feature)
+///
+/// Once set, [`set_min_level`] clamps lower levels to the floor, e.g., so that nothing (including
+/// a compromised configuration path) can turn on verbose logs that may leak sensitive data. If the
+/// current runtime level is below the floor, it's raised to it right away. With the
+/// `module_level` feature, the floor applies to the module levels too.
+///
+/// The floor can only be raised: setting a lower floor than the current one does nothing. Set it
+/// during the initialization, before anything else can change the levels.
+///
+/// This is a runtime guard on top of the compile-time filtering: the `min_level_*` features still
+/// remove the levels below them entirely, whatever the floor.
+///
+/// # Example
+///
+/// ```
+/// use loggery::Level;
+///
+/// loggery::set_level_floor(Level::Info);
+///
+/// // Clamped to the floor
+/// loggery::set_min_level(Level::Trace);
+///
+/// # #[cfg(not(any(feature = "min_level_warn", feature = "min_level_error",
+/// #     feature = "min_level_off")))]
+/// # {
+/// assert_eq!(loggery::get_min_level(), Some(Level::Info));
+/// assert!(!loggery::is_enabled(Level::Debug));
+/// # }
+///
+/// // The floor can't be lowered
+/// loggery::set_level_floor(Level::Trace);
+///
+/// assert_eq!(loggery::get_level_floor(), Level::Info);
+/// ```
+#[cfg(feature = "runtime_level")]
+#[inline]
+pub fn set_level_floor(floor: Level) {
+    let floor = (floor as u8).max(RUNTIME_LEVEL_FLOOR.load(core::sync::atomic::Ordering::Acquire));
 
-    #[cfg(all(feature = "extension", not(feature = "static")))]
-    {
-        if let Some(extension_fn) = get_extension() {
-            extension_fn(&payload)
-        }
-    }
+    RUNTIME_LEVEL_FLOOR.store(floor, core::sync::atomic::Ordering::Release);
 
-    #[cfg(feature = "static")]
-    {
-        unsafe { __loggery_log_impl(payload) };
+    if RUNTIME_MIN_LEVEL.load(core::sync::atomic::Ordering::Acquire) < floor {
+        RUNTIME_MIN_LEVEL.store(floor, core::sync::atomic::Ordering::Release);
     }
+}
 
-    #[cfg(not(feature = "static"))]
-    {
-        if let Some(logger_fn) = get_logger() {
-            logger_fn(payload)
-        }
-    }
+/// Returns the floor set with [`set_level_floor`] ([`Level::Trace`] by default).
+/// (`runtime_level` feature)
+#[cfg(feature = "runtime_level")]
+#[inline]
+pub fn get_level_floor() -> Level {
+    Level::from_u8(RUNTIME_LEVEL_FLOOR.load(core::sync::atomic::Ordering::Acquire))
+        .unwrap_or(Level::Trace)
 }
 
-/// Converts a raw pointer back to a `LoggerFn`.
+/// Sets the runtime minimum log level for a module and all of its submodules.
+/// (`module_level` feature)
 ///
-/// # Safety
+/// The `module` is matched against the target of the log call, which is its module path unless
+/// set with `target:` (e.g., `debug!(target: "net", ...)`), so `"net"` applies to logs from `net`,
+/// `net::http`, `net::http::client`, etc. but not to logs from `network`. When several registered
+/// modules match, the longest (most specific) one wins. Setting the level of an already
+/// registered module replaces it.
 ///
-/// Safe only when `ptr` was created by casting a valid `LoggerFn` to `*mut ()`.
-/// The caller must ensure:
-/// - Pointer originated from a valid function pointer cast
-/// - Fnuction pointer has 'static lifetime (guaranteed for all fn pointers)
-/// - Proper synchronization (handled by atomic ops)
-#[cfg(not(feature = "static"))]
-#[inline(always)]
-fn ptr_to_logger_fn(ptr: *mut ()) -> LoggerFn {
-    // SAFETY: `ptr` was created from `LoggerFn` in `set_logger`. Function pointers are 'static.
-    // Atomics ensure cross-thread visibility.
-    unsafe { core::mem::transmute::<*mut (), LoggerFn>(ptr) }
-}
-
-/// Converts a raw pointer back to an `ExtensionFn`.
+/// Returns `false` if the module couldn't be registered because [`MAX_MODULE_LEVELS`] modules
+/// already have their own level.
 ///
-/// # Safety
+/// # Note
 ///
-/// Safe only when `ptr` was created by casting a valid `ExtensionFn` to `*mut ()`.
-/// The caller must ensure:
-/// - Pointer originated from a valid function pointer cast
-/// - Fnuction pointer has 'static lifetime (guaranteed for all fn pointers)
-/// - Proper synchronization (handled by atomic ops)
-#[cfg(all(feature = "extension", not(feature = "static")))]
-#[inline(always)]
-fn ptr_to_extension_fn(ptr: *mut ()) -> ExtensionFn {
-    // SAFETY: `ptr` was created from `ExtensionFn` in `set_extension`.
-    // Function pointers are 'static. Atomics ensure cross-thread visibility.
-    unsafe { core::mem::transmute::<*mut (), ExtensionFn>(ptr) }
+/// A module level *replaces* the global level set by [`set_min_level`] for that module, so it can
+/// be more verbose than the global level, but it still can't enable levels that were filtered at
+/// compile time.
+///
+/// # Example
+///
+/// ```
+/// use loggery::Level;
+///
+/// loggery::set_min_level(Level::Warn);
+/// loggery::set_module_level("my_app::net", Level::Debug);
+///
+/// // `debug!` calls are now only logged from within `my_app::net` (and its submodules)
+/// ```
+///
+/// A target groups logs from different modules under one name:
+///
+/// ```
+/// use loggery::{debug, Level, Payload};
+/// use std::sync::atomic::{AtomicUsize, Ordering};
+///
+/// static LOGGED: AtomicUsize = AtomicUsize::new(0);
+///
+/// fn my_logger(_: Payload) {
+///     LOGGED.fetch_add(1, Ordering::Relaxed);
+/// }
+///
+/// fn main() {
+/// #   #[cfg(not(feature = "static"))]
+///     loggery::set_logger(my_logger);
+///
+///     loggery::set_min_level(Level::Info);
+///     loggery::set_module_level("net", Level::Debug);
+///
+///     debug!(target: "net", "Connected");
+///     debug!(target: "net::tls", "Handshake done");
+///     debug!("Filtered, the target is the module path");
+///
+/// #   #[cfg(all(not(feature = "static"), not(any(feature = "min_level_info",
+/// #       feature = "min_level_warn", feature = "min_level_error", feature = "min_level_off"))))]
+///     assert_eq!(LOGGED.load(Ordering::Relaxed), 2);
+/// }
+/// ```
+#[cfg(feature = "module_level")]
+pub fn set_module_level(module: &'static str, level: Level) -> bool {
+    let mut modules = MODULE_LEVELS.lock();
+
+    if let Some(entry) = modules
+        .iter_mut()
+        .flatten()
+        .find(|(name, _)| *name == module)
+    {
+        entry.1 = level;
+
+        return true;
+    }
+
+    match modules.iter_mut().find(|entry| entry.is_none()) {
+        Some(slot) => {
+            *slot = Some((module, level));
+
+            true
+        }
+        None => false,
+    }
 }
 
-/// Gets the logger, auto-initializing a default stdout logger if needed (`std` feature).
-/// Returns `None` if not set and `std` feature isn't enabled.
-#[cfg(not(feature = "static"))]
+/// Removes every level set by [`set_module_level`], so all modules use the global level again.
+/// (`module_level` feature)
+#[cfg(feature = "module_level")]
+pub fn clear_module_levels() {
+    *MODULE_LEVELS.lock() = [None; MAX_MODULE_LEVELS];
+}
+
+/// Returns the runtime minimum log level of the most specific registered module matching `target`.
+#[cfg(feature = "module_level")]
+fn get_module_level(target: &str) -> Option<Level> {
+    let modules = MODULE_LEVELS.lock();
+
+    modules
+        .iter()
+        .flatten()
+        .filter(|(module, _)| {
+            target == *module
+                || (target.starts_with(module) && target[module.len()..].starts_with("::"))
+        })
+        .max_by_key(|(module, _)| module.len())
+        .map(|(_, level)| *level)
+}
+
+/// Returns the effective minimum log level (the stricter of compile-time and runtime levels).
+///
+/// # Example
+///
+/// ```
+/// use loggery::Level;
+///
+/// # #[cfg(feature = "runtime_level")]
+/// loggery::set_min_level(Level::Debug);
+///
+/// # #[cfg(all(feature = "runtime_level", not(feature = "min_level_off")))]
+/// assert_eq!(loggery::get_min_level(), Some(Level::Debug));
+/// ```
 #[inline(always)]
-fn get_logger() -> Option<LoggerFn> {
-    let ptr = LOGGER_FN.load(core::sync::atomic::Ordering::Acquire);
+pub fn get_min_level() -> Option<Level> {
+    let compile_time = COMPILE_TIME_MIN_LEVEL?;
 
-    if ptr.is_null() {
-        #[cfg(feature = "std")]
+    let level = {
+        #[cfg(feature = "runtime_level")]
         {
-            let _ = LOGGER_FN.compare_exchange(
-                core::ptr::null_mut(),
-                stdout::logger_fn as usize as *mut (),
-                core::sync::atomic::Ordering::AcqRel,
-                core::sync::atomic::Ordering::Acquire,
-            );
-
-            // Reload after initialization
-            let new_ptr = LOGGER_FN.load(core::sync::atomic::Ordering::Acquire);
+            let runtime = RUNTIME_MIN_LEVEL.load(core::sync::atomic::Ordering::Relaxed);
 
-            return Some(ptr_to_logger_fn(new_ptr));
+            // Return whichever is stricter (higher level)
+            compile_time.max(runtime)
         }
 
-        #[cfg(not(feature = "std"))]
-        return None;
+        #[cfg(not(feature = "runtime_level"))]
+        compile_time
+    };
+
+    Level::from_u8(level)
+}
+
+/// What to do with a raw level that isn't a valid [`Level`] to log at, see [`try_log_level!`].
+///
+/// [`Level::Off`] is considered invalid too, since it's not a level to log at.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum InvalidLevelPolicy {
+    /// Log at the nearest valid level, i.e., [`Level::Error`] (default).
+    Clamp = 0,
+    /// Drop the log (counted by `invalid_level_count` when the `std` feature is enabled).
+    Drop = 1,
+}
+
+/// Sets what [`try_log_level!`] and [`resolve_level`] do with invalid raw levels.
+///
+/// Defaults to [`InvalidLevelPolicy::Clamp`], so a log with a bad level from external data is
+/// still visible (at the highest severity) instead of silently disappearing. Use
+/// [`InvalidLevelPolicy::Drop`] when the input can't be trusted at all, e.g., to not let it flood
+/// the logs with [`Level::Error`] entries.
+#[inline(always)]
+pub fn set_invalid_level_policy(policy: InvalidLevelPolicy) {
+    INVALID_LEVEL_POLICY.store(policy as u8, core::sync::atomic::Ordering::Relaxed);
+}
+
+/// Converts a raw level (e.g., from external data) to a [`Level`], applying the
+/// [`InvalidLevelPolicy`] if it's out of range.
+///
+/// Returns `None` if the log should be dropped.
+///
+/// # Example
+///
+/// ```
+/// use loggery::{InvalidLevelPolicy, Level};
+///
+/// assert_eq!(loggery::resolve_level(1), Some(Level::Debug));
+/// assert_eq!(loggery::resolve_level(Level::Off as u8), Some(Level::Error));
+/// assert_eq!(loggery::resolve_level(42), Some(Level::Error));
+///
+/// loggery::set_invalid_level_policy(InvalidLevelPolicy::Drop);
+///
+/// assert_eq!(loggery::resolve_level(42), None);
+/// ```
+#[inline]
+pub fn resolve_level(raw: u8) -> Option<Level> {
+    match Level::from_u8(raw) {
+        Some(Level::Off) | None => {}
+        level => return level,
     }
 
-    Some(ptr_to_logger_fn(ptr))
+    let policy = INVALID_LEVEL_POLICY.load(core::sync::atomic::Ordering::Relaxed);
+
+    if policy == InvalidLevelPolicy::Clamp as u8 {
+        return Some(Level::Error);
+    }
+
+    #[cfg(feature = "std")]
+    INVALID_LEVEL_COUNT.fetch_add(1, core::sync::atomic::Ordering::Relaxed);
+
+    metrics::count_drop(metrics::DropReason::InvalidLevel);
+
+    None
 }
 
-/// Gets the extension function if one is set, otherwise returns None.
-#[cfg(all(feature = "extension", not(feature = "static")))]
+/// Returns how many logs were dropped because of an invalid raw level. (`std` feature)
+///
+/// Only counts with the [`InvalidLevelPolicy::Drop`] policy.
+#[cfg(feature = "std")]
 #[inline(always)]
-fn get_extension() -> Option<ExtensionFn> {
-    let ptr = EXTENSION_FN.load(core::sync::atomic::Ordering::Acquire);
+pub fn invalid_level_count() -> usize {
+    INVALID_LEVEL_COUNT.load(core::sync::atomic::Ordering::Relaxed)
+}
 
-    if ptr.is_null() {
-        return None;
+/// Returns the minimum log level set at compile time with the `min_level_*` features, or `None` if
+/// all logs are disabled (`min_level_off`).
+///
+/// Unlike [`get_min_level`], it ignores the runtime level, so it can be evaluated in a `const`.
+///
+/// # Example
+///
+/// ```
+/// use loggery::Level;
+///
+/// const MIN_LEVEL: Option<Level> = loggery::compile_time_min_level();
+///
+/// # #[cfg(not(any(feature = "min_level_debug", feature = "min_level_info",
+/// #     feature = "min_level_warn", feature = "min_level_error", feature = "min_level_off")))]
+/// assert_eq!(MIN_LEVEL, Some(Level::Trace));
+/// # #[cfg(feature = "min_level_off")]
+/// # assert_eq!(MIN_LEVEL, None);
+/// ```
+#[inline(always)]
+pub const fn compile_time_min_level() -> Option<Level> {
+    match COMPILE_TIME_MIN_LEVEL {
+        Some(min_level) => Level::from_u8(min_level),
+        None => None,
     }
+}
 
-    Some(ptr_to_extension_fn(ptr))
+/// Returns `true` if a log at `level` passes the compile-time level filter (`min_level_*`
+/// features).
+///
+/// It's a `const fn`, so code only needed for a log at a level disabled at compile time is
+/// optimized away entirely, without relying on the optimizer to see through the runtime checks.
+/// Use [`is_enabled`] to also take the runtime filters into account.
+///
+/// # Example
+///
+/// ```
+/// use loggery::{debug, Level};
+///
+/// fn expensive_prep() -> String {
+///     "State dump".to_string()
+/// }
+///
+/// if loggery::level_enabled_at_compile_time(Level::Debug) {
+///     debug!("{}", expensive_prep());
+/// }
+///
+/// const DEBUG_ENABLED: bool = loggery::level_enabled_at_compile_time(Level::Debug);
+///
+/// let removed = cfg!(any(feature = "min_level_info", feature = "min_level_warn",
+///     feature = "min_level_error", feature = "min_level_off"));
+///
+/// assert_eq!(DEBUG_ENABLED, !removed);
+/// assert!(!loggery::level_enabled_at_compile_time(Level::Off));
+/// ```
+#[inline(always)]
+pub const fn level_enabled_at_compile_time(level: Level) -> bool {
+    match COMPILE_TIME_MIN_LEVEL {
+        Some(min_level) => (level as u8) >= min_level && (level as u8) < Level::Off as u8,
+        None => false,
+    }
 }
 
-/// Logs a message at the specified level.
+/// Returns `true` if a log at `level` would pass the compile-time and runtime level filters
+/// (minimum and maximum levels, and the level mask with the `level_mask` feature).
 ///
-/// This is the underlying macro used by [`trace!`], [`debug!`], [`info!`], [`warn!`] and [`error!`].
-/// While you mostly use those level-specific macros, `log!` can be useful when you want to specify
-/// the log level dynamically or crate your own logging abstractions.
+/// Useful to skip expensive work that's only needed for a log that would be filtered anyway.
+/// Unlike [`log_enabled!`], it ignores the module levels (`module_level` feature).
 ///
 /// # Example
 ///
 /// ```
-/// use loggery::{Level, log};
+/// use loggery::{Level, debug};
 ///
-/// let level = if cfg!(debug_assertions) {
-///     Level::Debug
-/// } else {
-///     Level::Info
-/// };
+/// # #[cfg(feature = "runtime_level")]
+/// loggery::set_min_level(Level::Info);
 ///
-/// log!(level, "This is a log with dynamically set level")
+/// if loggery::is_enabled(Level::Debug) {
+///     debug!("Expensive state dump: {}", 42);
+/// }
+///
+/// # #[cfg(feature = "runtime_level")]
+/// assert!(!loggery::is_enabled(Level::Debug));
 /// ```
-#[cfg(feature = "metadata")]
-#[macro_export]
-macro_rules! log {
-    ($level:expr, $($arg:tt)*) => {
-        $crate::log($crate::Payload {
-            level: $level,
-            args: format_args!($($arg)*),
-            meta: $crate::Metadata {
-                module_path: module_path!(),
-                file: file!(),
-                line: line!(),
-            },
-        })
+#[inline(always)]
+pub fn is_enabled(level: Level) -> bool {
+    if !level_enabled_at_compile_time(level) {
+        return false;
+    }
+
+    #[cfg(feature = "runtime_level")]
+    {
+        let runtime_min_level = RUNTIME_MIN_LEVEL.load(core::sync::atomic::Ordering::Relaxed);
+
+        if (level as u8) < runtime_min_level || !is_below_max_level(level) {
+            return false;
+        }
+    }
+
+    #[cfg(feature = "level_mask")]
+    if !get_level_mask().contains(level) {
+        return false;
+    }
+
+    true
+}
+
+/// Returns `true` if a log at `level` from the `target` module would pass the level filters.
+/// (`module_level` feature)
+///
+/// The level is resolved in this order:
+/// 1. Compile-time level (`min_level_*` features): always applies, nothing can go below it.
+/// 2. Module level ([`set_module_level`]): the most specific module matching `target`.
+/// 3. Global runtime level ([`set_min_level`]): used if no registered module matches `target`.
+///
+/// With the `level_mask` feature, the level must also be in the mask set with `set_level_mask`.
+///
+/// # Example
+///
+/// ```
+/// use loggery::Level;
+///
+/// loggery::set_min_level(Level::Warn);
+/// loggery::set_module_level("net", Level::Debug);
+///
+/// # #[cfg(not(any(feature = "min_level_info", feature = "min_level_warn",
+/// #     feature = "min_level_error", feature = "min_level_off")))]
+/// # {
+/// assert!(!loggery::is_enabled(Level::Debug));
+/// assert!(loggery::is_enabled_for(Level::Debug, "net::http"));
+/// assert!(!loggery::is_enabled_for(Level::Trace, "net::http"));
+/// assert!(!loggery::is_enabled_for(Level::Debug, "network"));
+/// # }
+/// ```
+#[cfg(feature = "module_level")]
+#[inline]
+pub fn is_enabled_for(level: Level, target: &str) -> bool {
+    if !level_enabled_at_compile_time(level) {
+        return false;
+    }
+
+    #[cfg(feature = "level_mask")]
+    if !get_level_mask().contains(level) {
+        return false;
+    }
+
+    match get_module_level(target) {
+        Some(min_level) => {
+            level >= min_level && level >= get_level_floor() && is_below_max_level(level)
+        }
+        None => is_enabled(level),
+    }
+}
+
+/// Returns `true` if `level` isn't above the runtime maximum level, see [`set_max_level`].
+#[cfg(feature = "runtime_level")]
+#[inline(always)]
+fn is_below_max_level(level: Level) -> bool {
+    level as u8 <= RUNTIME_MAX_LEVEL.load(core::sync::atomic::Ordering::Relaxed)
+}
+
+/// Core logging entry point used internally by macros like [`debug!`] and [`error!`].
+///
+/// It's recommended to use the macros instead of calling directly.
+///
+/// Returns `true` if the log passed the level filters and was emitted, `false` if it was
+/// filtered out (or suppressed, e.g., by `enter_shutdown_mode`). The macros discard it, so they
+/// keep working in statement and tail position. Call this function directly when the outcome
+/// matters, e.g., for tests or to only do follow-up work when the log was emitted.
+///
+/// # Example
+///
+/// ```
+/// use loggery::Level;
+///
+/// # #[cfg(feature = "runtime_level")]
+/// loggery::set_min_level(Level::Info);
+///
+/// // A `Payload` as created by the macros, e.g., `info!("Cache miss")`
+/// # let payload = |level| {
+/// #     loggery::Payload::__new(
+/// #         level,
+/// #         format_args!("Cache miss"),
+/// #         #[cfg(feature = "metadata")]
+/// #         loggery::Metadata::__new(module_path!(), file!(), line!()),
+/// #     )
+/// # };
+/// # #[cfg(all(feature = "runtime_level", not(any(feature = "min_level_warn",
+/// #     feature = "min_level_error", feature = "min_level_off"))))]
+/// # {
+/// assert!(loggery::log(payload(Level::Info)));
+/// assert!(!loggery::log(payload(Level::Debug)));
+/// # }
+/// ```
+#[inline(always)]
+pub fn log(payload: Payload) -> bool {
+    #[cfg(feature = "module_level")]
+    let is_level_enabled = is_enabled_for(payload.level, payload.meta.target);
+
+    #[cfg(not(feature = "module_level"))]
+    let is_level_enabled = is_enabled(payload.level);
+
+    log_checked(payload, is_level_enabled)
+}
+
+/// The rest of [`log()`] once the level filters are checked, so the macros (which check them
+/// before building the payload) don't check them twice.
+#[inline(always)]
+fn log_checked(payload: Payload, is_level_enabled: bool) -> bool {
+    if !is_level_enabled {
+        if level_enabled_at_compile_time(payload.level) {
+            metrics::count_drop(metrics::DropReason::Level);
+
+            #[cfg(feature = "std")]
+            recent::record(&payload);
+        }
+
+        return false;
+    }
+
+    #[cfg(feature = "std")]
+    if shutdown_mode::suppresses(payload.level) {
+        metrics::count_drop(metrics::DropReason::Shutdown);
+
+        return false;
+    }
+
+    #[cfg(feature = "std")]
+    if let Some(written) = panicking::intercept(&payload) {
+        return written;
+    }
+
+    #[cfg(feature = "filter")]
+    if !filter::passes(&payload) {
+        metrics::count_drop(metrics::DropReason::Filter);
+
+        return false;
+    }
+
+    #[cfg(feature = "metrics")]
+    metrics::count_emitted(payload.level);
+
+    #[cfg(feature = "seq")]
+    let payload = Payload {
+        seq: seq::next(),
+        ..payload
+    };
+
+    #[cfg(feature = "emission_stats")]
+    let (level, started) = (payload.level, emission_stats::start());
+
+    #[cfg(feature = "std")]
+    match gap_markers::take() {
+        0 => emit(payload),
+        gap => emit(Payload {
+            args: format_args!("{}{}", payload.args, gap_markers::Marker(gap)),
+            ..payload
+        }),
+    }
+
+    #[cfg(not(feature = "std"))]
+    emit(payload);
+
+    #[cfg(feature = "emission_stats")]
+    emission_stats::record(level, started);
+
+    if is_always_flush() {
+        flush();
+    }
+
+    true
+}
+
+/// Logs an already rendered message at `level`, verbatim.
+///
+/// The function form of [`log_str!`], for a message that's already a `&str` (e.g., from a cache):
+/// it's borrowed by the [`Payload`] as is, without being copied or allocated, and written with a
+/// single `write_str` call. Braces and `%` in it are written as they are.
+///
+/// Unlike the macros, a function can't capture the module path of its caller: the metadata has the
+/// file and line of the call (`metadata` feature), but an empty module path and target, so only
+/// the global level applies, not the module levels. Use [`log_str!`] to keep them.
+///
+/// Returns `true` if the log was emitted, like [`log()`].
+///
+/// # Example
+///
+/// ```
+/// use loggery::{Level, Payload};
+/// use std::cell::RefCell;
+///
+/// thread_local! {
+///     static LAST: RefCell<String> = RefCell::new(String::new());
+/// }
+///
+/// fn my_logger(payload: Payload) {
+///     LAST.with(|last| *last.borrow_mut() = payload.args.to_string());
+/// }
+///
+/// fn main() {
+/// #   #[cfg(not(feature = "static"))]
+///     loggery::set_logger(my_logger);
+///
+///     let emitted = loggery::log_str(Level::Warn, "Progress: {} 50% {done}");
+///
+/// #   #[cfg(all(not(feature = "static"), not(any(feature = "min_level_error",
+/// #       feature = "min_level_off"))))]
+/// #   {
+///     assert!(emitted);
+///     LAST.with(|last| assert_eq!(*last.borrow(), "Progress: {} 50% {done}"));
+/// #   }
+/// }
+/// ```
+#[track_caller]
+#[inline]
+pub fn log_str(level: Level, msg: &str) -> bool {
+    #[cfg(feature = "metadata")]
+    let meta = {
+        let location = core::panic::Location::caller();
+
+        Metadata::__with_target("", "", location.file(), location.line())
+    };
+
+    __private::log_str(
+        level,
+        msg,
+        #[cfg(feature = "metadata")]
+        meta,
+    )
+}
+
+/// Passes a log to the extensions and the loggers, without filtering it.
+///
+/// This is the last step of [`log()`], after the level filters and the other checks (shutdown
+/// mode, filter, etc.), which `emit` skips. It resolves the dispatch the same way whatever the
+/// features: with the `static` feature, the log goes to `__loggery_log_impl` (and
+/// `__loggery_extension_impl` with the `extension` feature), otherwise to the loggers (and
+/// extensions) registered at runtime, or to the default logger with the `std` feature.
+///
+/// Library code can use it to hand a log over to the application's logger without `cfg`-ing on
+/// the `static` feature, which is up to the binary crate. It's a [`LoggerFn`], so it can be the
+/// sink of a [`Dispatcher`], giving a component its own level while logging through the global
+/// logger.
+///
+/// > **Note:** Don't call it from a logger or an extension with the log it received: that log
+/// > would be passed to the same logger again, forever.
+///
+/// # Example
+///
+/// ```
+/// use loggery::{info_to, warn_to, Dispatcher, LoggerFn};
+///
+/// // Library code: no `cfg` on the `static` feature, the application decides how logs are
+/// // dispatched (this doctest is run both with and without it)
+/// fn connect(dispatcher: &Dispatcher<LoggerFn>) {
+///     info_to!(dispatcher, "Connecting");
+///     warn_to!(dispatcher, "Retrying");
+/// }
+///
+/// # #[cfg(feature = "std")]
+/// # {
+/// use loggery::{Destination, Level, StreamRouting};
+///
+/// // Send the default logger's output to a file to check it
+/// let path = std::env::temp_dir().join("loggery_emit_doctest.log");
+/// let _ = std::fs::remove_file(&path);
+/// let path: &'static str = Box::leak(path.to_string_lossy().into_owned().into_boxed_str());
+///
+/// loggery::set_stream_routing(
+///     StreamRouting::new()
+///         .route(Level::Info, Destination::File(path))
+///         .route(Level::Warn, Destination::File(path)),
+/// );
+///
+/// let dispatcher: Dispatcher<LoggerFn> = Dispatcher::new(loggery::emit, Level::Warn);
+///
+/// connect(&dispatcher);
+///
+/// # #[cfg(all(any(not(feature = "static"), feature = "static_default"), not(any(
+/// #     feature = "min_level_error", feature = "min_level_off"))))]
+/// # {
+/// let contents = std::fs::read_to_string(path).unwrap();
+///
+/// assert!(contents.contains("Retrying"));
+/// assert!(!contents.contains("Connecting"));
+/// # }
+/// # }
+/// ```
+#[inline(always)]
+pub fn emit(payload: Payload) {
+    #[cfg(all(feature = "extension", feature = "static"))]
+    {
+        unsafe { __loggery_extension_impl(&payload) };
+    }
+
+    #[cfg(all(feature = "extension", not(feature = "static")))]
+    {
+        for slot in EXTENSIONS.iter() {
+            let ptr = slot.load(core::sync::atomic::Ordering::Acquire);
+
+            if ptr.is_null() {
+                break;
+            }
+
+            ptr_to_extension_fn(ptr)(&payload)
+        }
+    }
+
+    #[cfg(feature = "target_route")]
+    if let Some(sink) = target_route::find(payload.meta.target) {
+        call_logger(payload, sink);
+
+        return;
+    }
+
+    #[cfg(feature = "static")]
+    {
+        call_logger(payload, |payload| unsafe { __loggery_log_impl(payload) });
+    }
+
+    #[cfg(not(feature = "static"))]
+    {
+        let logger_fn = get_logger();
+
+        #[cfg(feature = "assert_logger")]
+        debug_assert!(
+            logger_fn.is_some(),
+            "loggery: a log was emitted before a logger was set, call `loggery::set_logger` first"
+        );
+
+        if let Some(logger_fn) = logger_fn {
+            call_logger(payload, logger_fn);
+
+            // Loggers registered with `add_logger`
+            for slot in LOGGERS[1..].iter() {
+                let ptr = slot.load(core::sync::atomic::Ordering::Acquire);
+
+                if ptr.is_null() {
+                    break;
+                }
+
+                call_logger(payload, ptr_to_logger_fn(ptr));
+            }
+        }
+    }
+}
+
+/// Calls a logger, catching its panics with the `catch_panic` feature.
+#[inline(always)]
+fn call_logger(payload: Payload, logger: impl FnOnce(Payload)) {
+    #[cfg(feature = "catch_panic")]
+    catch_panic::call_logger(payload, logger);
+
+    #[cfg(not(feature = "catch_panic"))]
+    logger(payload);
+}
+
+/// Converts a raw pointer back to a `LoggerFn`.
+///
+/// # Safety
+///
+/// Safe only when `ptr` was created by casting a valid `LoggerFn` to `*mut ()`.
+/// The caller must ensure:
+/// - Pointer originated from a valid function pointer cast
+/// - Fnuction pointer has 'static lifetime (guaranteed for all fn pointers)
+/// - Proper synchronization (handled by atomic ops)
+#[cfg(not(feature = "static"))]
+#[inline(always)]
+fn ptr_to_logger_fn(ptr: *mut ()) -> LoggerFn {
+    // SAFETY: `ptr` was created from `LoggerFn` in `set_logger` or `add_logger`. Function pointers
+    // are 'static. Atomics ensure cross-thread visibility.
+    unsafe { core::mem::transmute::<*mut (), LoggerFn>(ptr) }
+}
+
+/// Converts a raw pointer back to an `ExtensionFn`.
+///
+/// # Safety
+///
+/// Safe only when `ptr` was created by casting a valid `ExtensionFn` to `*mut ()`.
+/// The caller must ensure:
+/// - Pointer originated from a valid function pointer cast
+/// - Fnuction pointer has 'static lifetime (guaranteed for all fn pointers)
+/// - Proper synchronization (handled by atomic ops)
+#[cfg(all(feature = "extension", not(feature = "static")))]
+#[inline(always)]
+fn ptr_to_extension_fn(ptr: *mut ()) -> ExtensionFn {
+    // SAFETY: `ptr` was created from `ExtensionFn` in `set_extension` or `add_extension`.
+    // Function pointers are 'static. Atomics ensure cross-thread visibility.
+    unsafe { core::mem::transmute::<*mut (), ExtensionFn>(ptr) }
+}
+
+/// Gets the first logger, auto-initializing a default stdout logger if needed (`std` feature).
+/// Returns `None` if not set and `std` feature isn't enabled.
+#[cfg(not(feature = "static"))]
+#[inline(always)]
+fn get_logger() -> Option<LoggerFn> {
+    let ptr = LOGGERS[0].load(core::sync::atomic::Ordering::Acquire);
+
+    if ptr.is_null() {
+        #[cfg(feature = "std")]
+        {
+            let _ = LOGGERS[0].compare_exchange(
+                core::ptr::null_mut(),
+                stdout::default_logger as LoggerFn as *mut (),
+                core::sync::atomic::Ordering::AcqRel,
+                core::sync::atomic::Ordering::Acquire,
+            );
+
+            // Reload after initialization
+            let new_ptr = LOGGERS[0].load(core::sync::atomic::Ordering::Acquire);
+
+            return Some(ptr_to_logger_fn(new_ptr));
+        }
+
+        #[cfg(not(feature = "std"))]
+        return None;
+    }
+
+    Some(ptr_to_logger_fn(ptr))
+}
+
+/// Gets the first extension function if one is set, otherwise returns None.
+#[cfg(all(feature = "extension", not(feature = "static")))]
+#[inline(always)]
+fn get_extension() -> Option<ExtensionFn> {
+    let ptr = EXTENSIONS[0].load(core::sync::atomic::Ordering::Acquire);
+
+    if ptr.is_null() {
+        return None;
+    }
+
+    Some(ptr_to_extension_fn(ptr))
+}
+
+/// Logs a message at the specified level.
+///
+/// This is the underlying macro used by [`trace!`], [`debug!`], [`info!`], [`warn!`] and [`error!`].
+/// While you mostly use those level-specific macros, `log!` can be useful when you want to specify
+/// the log level dynamically or crate your own logging abstractions.
+///
+/// # Example
+///
+/// ```
+/// use loggery::{Level, log};
+///
+/// let level = if cfg!(debug_assertions) {
+///     Level::Debug
+/// } else {
+///     Level::Info
+/// };
+///
+/// log!(level, "This is a log with dynamically set level")
+/// ```
+///
+/// # Lazy arguments
+///
+/// The level filters are checked before the message is built, so the arguments (and the
+/// `key = value` values) of a log filtered out by the level aren't evaluated at all: their side
+/// effects don't happen and formatting them costs nothing. This applies to the runtime level, the
+/// module levels and the level mask, not only to the `min_level_*` features. The only exception is
+/// `set_recent_capacity`, which needs the message of the filtered logs it keeps.
+///
+/// ```
+/// use loggery::{debug, Level};
+/// use std::cell::Cell;
+///
+/// let evaluated = Cell::new(0);
+/// let state = || {
+///     evaluated.set(evaluated.get() + 1);
+///     [1, 2, 3]
+/// };
+///
+/// # #[cfg(feature = "runtime_level")]
+/// loggery::set_min_level(Level::Info);
+///
+/// debug!("state = {:?}", state());
+/// debug!(target: "dump", state:? = state(); "State");
+///
+/// // Both filtered out, `state()` wasn't called
+/// # #[cfg(feature = "runtime_level")]
+/// assert_eq!(evaluated.get(), 0);
+/// ```
+///
+/// # `log` crate compatibility
+///
+/// The macros accept the same syntax as the macros of the [`log`](https://docs.rs/log) crate, so
+/// migrating is mostly a matter of replacing `use log::...` with `use loggery::...`:
+///
+/// - `info!(target: "http", "message")`: the target (a `&'static str`) is stored in
+///   `Metadata::target` instead of the module path, which is the default target, so module levels
+///   set with `set_module_level("http", ...)` apply to it. It's ignored without the `metadata`
+///   feature.
+/// - `info!(key = value, other:? = value; "message")`: the key-values are appended to the message
+///   as ` key=value`, formatted with `Display` (or `Debug` with `:?`, `Display` with `:%`). Values
+///   that are empty or contain whitespace are quoted: ` reason="timed out"`. With the `kv`
+///   feature, they're stored in `Payload::fields` instead, see below.
+///
+/// ```
+/// use loggery::{error, info, log, warn, Level, Payload};
+/// use std::cell::RefCell;
+///
+/// thread_local! {
+///     static LAST: RefCell<String> = RefCell::new(String::new());
+/// }
+///
+/// fn my_logger(payload: Payload) {
+///     LAST.with(|last| *last.borrow_mut() = payload.args.to_string());
+/// }
+///
+/// fn main() {
+/// #   #[cfg(not(feature = "static"))]
+///     loggery::set_logger(my_logger);
+///
+///     let path = "/index.html";
+///
+///     info!("Serving {} on port {}", path, 8080);
+///     info!(target: "http", "GET {}", path);
+///     log!(Level::Info, "Dynamic level");
+///     log!(target: "http", Level::Info, "Dynamic level with a target");
+///
+///     warn!(target: "http", status = 404, path = path; "Not found");
+///
+/// #   #[cfg(all(not(feature = "static"), not(feature = "kv"), not(any(
+/// #       feature = "min_level_error", feature = "min_level_off"))))]
+///     LAST.with(|last| assert_eq!(*last.borrow(), "Not found status=404 path=/index.html"));
+///
+///     error!(user_id = 42, reason:? = "timeout", detail = "timed out"; "Request {} failed", 7);
+///
+/// #   #[cfg(all(not(feature = "static"), not(feature = "kv"), not(feature = "min_level_off")))]
+///     LAST.with(|last| {
+///         assert_eq!(
+///             *last.borrow(),
+///             "Request 7 failed user_id=42 reason=\"timeout\" detail=\"timed out\""
+///         )
+///     });
+/// }
+/// ```
+///
+/// # Structured fields
+///
+/// With the `kv` feature, the `key = value` fields aren't part of the message: they're stored in
+/// `Payload::fields`, so loggers and extensions can render them as they see fit (e.g., the JSON
+/// extensions put them in a `fields` object). The default logger and the text extensions still
+/// append them after the message as ` key=value`.
+///
+/// Logs without fields have an empty `fields` slice, and the fields are formatted lazily (only if
+/// the logger renders them), so there's no cost for logs that don't use them.
+///
+/// ```
+/// use loggery::{info, Payload};
+/// use std::cell::RefCell;
+///
+/// thread_local! {
+///     static FIELDS: RefCell<Vec<String>> = RefCell::new(Vec::new());
+/// }
+///
+/// fn my_logger(payload: Payload) {
+/// #   #[cfg(feature = "kv")]
+///     FIELDS.with(|fields| {
+///         *fields.borrow_mut() = payload
+///             .fields
+///             .iter()
+///             .map(|(key, value)| format!("{}: {}", key, value))
+///             .collect();
+///     });
+/// }
+///
+/// fn main() {
+/// #   #[cfg(not(feature = "static"))]
+///     loggery::set_logger(my_logger);
+///
+///     info!(user_id = 42, request = "abc"; "Logged in");
+///
+/// #   #[cfg(all(feature = "kv", not(feature = "static"), not(feature = "min_level_off")))]
+///     FIELDS.with(|fields| assert_eq!(*fields.borrow(), ["user_id: 42", "request: abc"]));
+/// }
+/// ```
+#[cfg(feature = "metadata")]
+#[macro_export]
+macro_rules! log {
+    (target: $target:expr, $level:expr, $($key:tt $(:$capture:tt)? = $value:expr),+; $($arg:tt)+) => {{
+        let level: $crate::Level = $level;
+        let target: &'static str = $target;
+        let enabled = $crate::__private::enabled(level, target);
+
+        if enabled || $crate::__private::needs_filtered_payload(level) {
+            match &[$(($crate::__key!($key), $crate::__value!($($capture)? $value))),+] {
+                fields => {
+                    let _ = $crate::__private::log_checked(
+                        $crate::Payload::__with_fields(
+                            level,
+                            format_args!(
+                                "{}{}",
+                                format_args!($($arg)+),
+                                $crate::__private::message_suffix(fields)
+                            ),
+                            fields,
+                            $crate::Metadata::__with_target(
+                                target,
+                                module_path!(),
+                                file!(),
+                                line!(),
+                            ),
+                        ),
+                        enabled,
+                    );
+                }
+            }
+        }
+    }};
+    (target: $target:expr, $level:expr, $($arg:tt)*) => {{
+        let level: $crate::Level = $level;
+        let target: &'static str = $target;
+        let enabled = $crate::__private::enabled(level, target);
+
+        if enabled || $crate::__private::needs_filtered_payload(level) {
+            let _ = $crate::__private::log_checked(
+                $crate::Payload::__new(
+                    level,
+                    format_args!($($arg)*),
+                    $crate::Metadata::__with_target(target, module_path!(), file!(), line!()),
+                ),
+                enabled,
+            );
+        }
+    }};
+    ($level:expr, $($arg:tt)*) => {
+        $crate::log!(target: module_path!(), $level, $($arg)*)
+    };
+}
+
+/// Logs a message at the specified level.
+///
+/// This is the underlying macro used by [`trace!`], [`debug!`], [`info!`], [`warn!`] and [`error!`].
+/// While you mostly use those level-specific macros, `log!` can be useful when you want to specify
+/// the log level dynamically or crate your own logging abstractions.
+///
+/// # Example
+///
+/// ```
+/// use loggery::{Level, log};
+///
+/// let level = if cfg!(debug_assertions) {
+///     Level::Debug
+/// } else {
+///     Level::Info
+/// };
+///
+/// log!(level, "This is a log with dynamically set level")
+/// ```
+///
+/// # Lazy arguments
+///
+/// The level filters are checked before the message is built, so the arguments (and the
+/// `key = value` values) of a log filtered out by the level aren't evaluated at all: their side
+/// effects don't happen and formatting them costs nothing. This applies to the runtime level, the
+/// module levels and the level mask, not only to the `min_level_*` features. The only exception is
+/// `set_recent_capacity`, which needs the message of the filtered logs it keeps.
+///
+/// ```
+/// use loggery::{debug, Level};
+/// use std::cell::Cell;
+///
+/// let evaluated = Cell::new(0);
+/// let state = || {
+///     evaluated.set(evaluated.get() + 1);
+///     [1, 2, 3]
+/// };
+///
+/// # #[cfg(feature = "runtime_level")]
+/// loggery::set_min_level(Level::Info);
+///
+/// debug!("state = {:?}", state());
+/// debug!(target: "dump", state:? = state(); "State");
+///
+/// // Both filtered out, `state()` wasn't called
+/// # #[cfg(feature = "runtime_level")]
+/// assert_eq!(evaluated.get(), 0);
+/// ```
+///
+/// # `log` crate compatibility
+///
+/// The macros accept the same syntax as the macros of the [`log`](https://docs.rs/log) crate, so
+/// migrating is mostly a matter of replacing `use log::...` with `use loggery::...`:
+///
+/// - `info!(target: "http", "message")`: the target (a `&'static str`) is stored in
+///   `Metadata::target` instead of the module path, which is the default target, so module levels
+///   set with `set_module_level("http", ...)` apply to it. It's ignored without the `metadata`
+///   feature.
+/// - `info!(key = value, other:? = value; "message")`: the key-values are appended to the message
+///   as ` key=value`, formatted with `Display` (or `Debug` with `:?`, `Display` with `:%`). Values
+///   that are empty or contain whitespace are quoted: ` reason="timed out"`. With the `kv`
+///   feature, they're stored in `Payload::fields` instead, see below.
+///
+/// ```
+/// use loggery::{error, info, log, warn, Level, Payload};
+/// use std::cell::RefCell;
+///
+/// thread_local! {
+///     static LAST: RefCell<String> = RefCell::new(String::new());
+/// }
+///
+/// fn my_logger(payload: Payload) {
+///     LAST.with(|last| *last.borrow_mut() = payload.args.to_string());
+/// }
+///
+/// fn main() {
+/// #   #[cfg(not(feature = "static"))]
+///     loggery::set_logger(my_logger);
+///
+///     let path = "/index.html";
+///
+///     info!("Serving {} on port {}", path, 8080);
+///     info!(target: "http", "GET {}", path);
+///     log!(Level::Info, "Dynamic level");
+///     log!(target: "http", Level::Info, "Dynamic level with a target");
+///
+///     warn!(target: "http", status = 404, path = path; "Not found");
+///
+/// #   #[cfg(all(not(feature = "static"), not(feature = "kv"), not(any(
+/// #       feature = "min_level_error", feature = "min_level_off"))))]
+///     LAST.with(|last| assert_eq!(*last.borrow(), "Not found status=404 path=/index.html"));
+///
+///     error!(user_id = 42, reason:? = "timeout", detail = "timed out"; "Request {} failed", 7);
+///
+/// #   #[cfg(all(not(feature = "static"), not(feature = "kv"), not(feature = "min_level_off")))]
+///     LAST.with(|last| {
+///         assert_eq!(
+///             *last.borrow(),
+///             "Request 7 failed user_id=42 reason=\"timeout\" detail=\"timed out\""
+///         )
+///     });
+/// }
+/// ```
+///
+/// # Structured fields
+///
+/// With the `kv` feature, the `key = value` fields aren't part of the message: they're stored in
+/// `Payload::fields`, so loggers and extensions can render them as they see fit (e.g., the JSON
+/// extensions put them in a `fields` object). The default logger and the text extensions still
+/// append them after the message as ` key=value`.
+///
+/// Logs without fields have an empty `fields` slice, and the fields are formatted lazily (only if
+/// the logger renders them), so there's no cost for logs that don't use them.
+///
+/// ```
+/// use loggery::{info, Payload};
+/// use std::cell::RefCell;
+///
+/// thread_local! {
+///     static FIELDS: RefCell<Vec<String>> = RefCell::new(Vec::new());
+/// }
+///
+/// fn my_logger(payload: Payload) {
+/// #   #[cfg(feature = "kv")]
+///     FIELDS.with(|fields| {
+///         *fields.borrow_mut() = payload
+///             .fields
+///             .iter()
+///             .map(|(key, value)| format!("{}: {}", key, value))
+///             .collect();
+///     });
+/// }
+///
+/// fn main() {
+/// #   #[cfg(not(feature = "static"))]
+///     loggery::set_logger(my_logger);
+///
+///     info!(user_id = 42, request = "abc"; "Logged in");
+///
+/// #   #[cfg(all(feature = "kv", not(feature = "static"), not(feature = "min_level_off")))]
+///     FIELDS.with(|fields| assert_eq!(*fields.borrow(), ["user_id: 42", "request: abc"]));
+/// }
+/// ```
+#[cfg(not(feature = "metadata"))]
+#[macro_export]
+macro_rules! log {
+    (target: $target:expr, $($rest:tt)+) => {{
+        // Without the metadata, there's nowhere to store the target
+        let _: &'static str = $target;
+        $crate::log!($($rest)+)
+    }};
+    ($level:expr, $($key:tt $(:$capture:tt)? = $value:expr),+; $($arg:tt)+) => {{
+        let level: $crate::Level = $level;
+        let enabled = $crate::__private::enabled(level, "");
+
+        if enabled || $crate::__private::needs_filtered_payload(level) {
+            match &[$(($crate::__key!($key), $crate::__value!($($capture)? $value))),+] {
+                fields => {
+                    let _ = $crate::__private::log_checked(
+                        $crate::Payload::__with_fields(
+                            level,
+                            format_args!(
+                                "{}{}",
+                                format_args!($($arg)+),
+                                $crate::__private::message_suffix(fields)
+                            ),
+                            fields,
+                        ),
+                        enabled,
+                    );
+                }
+            }
+        }
+    }};
+    ($level:expr, $($arg:tt)*) => {{
+        let level: $crate::Level = $level;
+        let enabled = $crate::__private::enabled(level, "");
+
+        if enabled || $crate::__private::needs_filtered_payload(level) {
+            let _ = $crate::__private::log_checked(
+                $crate::Payload::__new(level, format_args!($($arg)*)),
+                enabled,
+            );
+        }
+    }};
+}
+
+/// Renders a key of the `key = value` syntax of [`log!`].
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __key {
+    ($key:ident) => {
+        stringify!($key)
+    };
+    ($key:literal) => {
+        $key
+    };
+}
+
+/// Captures a value of the `key = value` syntax of [`log!`], with `Display` by default, or with
+/// `Debug` (`key:? = value`) or `Display` (`key:% = value`) explicitly.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __value {
+    (? $value:expr) => {
+        format_args!("{:?}", $value)
+    };
+    (% $value:expr) => {
+        format_args!("{}", $value)
+    };
+    ($value:expr) => {
+        format_args!("{}", $value)
+    };
+}
+
+/// Implementation details of the macros, not part of the public API.
+#[doc(hidden)]
+pub mod __private {
+    pub use crate::fields::{message_suffix, KeyValues};
+    pub use crate::metrics::{count_drop, DropReason};
+
+    use crate::Level;
+
+    /// Returns `true` if the macros have to build the payload of a log at `level` filtered out by
+    /// the level filters: if it's kept by `set_recent_capacity`.
+    ///
+    /// Otherwise, it's counted as a level drop, since `log()` never sees it.
+    #[inline(always)]
+    pub fn needs_filtered_payload(level: Level) -> bool {
+        if !crate::level_enabled_at_compile_time(level) {
+            return false;
+        }
+
+        #[cfg(feature = "std")]
+        if crate::get_recent_capacity() > 0 {
+            return true;
+        }
+
+        count_drop(DropReason::Level);
+
+        false
+    }
+
+    /// Logs a payload whose level filters the macros have already checked with [`enabled`], so
+    /// the module levels are only looked up once per log.
+    #[inline(always)]
+    pub fn log_checked(payload: crate::Payload, is_level_enabled: bool) -> bool {
+        crate::log_checked(payload, is_level_enabled)
+    }
+
+    /// Logs `msg` verbatim, for `log_str!` and [`log_str`](crate::log_str()).
+    #[inline(always)]
+    pub fn log_str(
+        level: Level,
+        msg: &str,
+        #[cfg(feature = "metadata")] meta: crate::Metadata,
+    ) -> bool {
+        crate::log(crate::Payload::__new(
+            level,
+            format_args!("{}", msg),
+            #[cfg(feature = "metadata")]
+            meta,
+        ))
+    }
+
+    /// Returns `true` if a log at `level` from `target` passes the level filters, as checked by
+    /// `log()`. The target only matters with the `module_level` feature.
+    #[inline(always)]
+    pub fn enabled(level: Level, target: &str) -> bool {
+        #[cfg(feature = "module_level")]
+        {
+            crate::is_enabled_for(level, target)
+        }
+
+        #[cfg(not(feature = "module_level"))]
+        {
+            let _ = target;
+
+            crate::is_enabled(level)
+        }
+    }
+
+    /// Displays an error followed by its sources, as `top: cause1: cause2`, for `error_chain!`.
+    #[cfg(feature = "std")]
+    pub struct ErrorChain<'a>(pub &'a dyn std::error::Error);
+
+    #[cfg(feature = "std")]
+    impl core::fmt::Display for ErrorChain<'_> {
+        fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+            write!(f, "{}", self.0)?;
+
+            let mut source = self.0.source();
+
+            while let Some(error) = source {
+                write!(f, ": {}", error)?;
+                source = error.source();
+            }
+
+            Ok(())
+        }
+    }
+}
+
+/// Returns `true` if a log at the given level from the calling module would pass the level
+/// filters, the equivalent of the `log_enabled!` macro of the `log` crate.
+///
+/// It makes the same check as the level macros: the `min_level_*` features, the runtime level
+/// set with [`set_min_level`], the level mask (`level_mask` feature) and, unlike [`is_enabled`],
+/// the module levels set with `set_module_level` (`module_level` feature), matched against the
+/// module path of the call, or the given target. It's always `false` with the `min_level_off`
+/// feature.
+///
+/// Use it to skip diagnostic work that's only needed for a log, beyond evaluating the arguments
+/// (which the macros already skip for filtered logs), e.g., collecting statistics to log.
+///
+/// # Example
+///
+/// ```
+/// use loggery::{debug, log_enabled, Level};
+///
+/// # #[cfg(feature = "runtime_level")]
+/// loggery::set_min_level(Level::Info);
+///
+/// if log_enabled!(Level::Debug) {
+///     let sizes: Vec<usize> = (0..3).map(|i| i * 512).collect();
+///     debug!("Buffer sizes: {:?}", sizes);
+/// }
+///
+/// // Below the runtime level
+/// # #[cfg(feature = "runtime_level")]
+/// assert!(!log_enabled!(Level::Debug));
+/// # #[cfg(not(any(feature = "min_level_warn", feature = "min_level_error",
+/// #     feature = "min_level_off")))]
+/// assert!(log_enabled!(Level::Info));
+/// assert!(!log_enabled!(Level::Off));
+///
+/// // Nothing is enabled with `min_level_off`
+/// # #[cfg(feature = "min_level_off")]
+/// assert!(Level::iter().all(|level| !log_enabled!(level)));
+/// ```
+///
+/// With the `module_level` feature, a target can be given like with the level macros:
+///
+/// ```
+/// # #[cfg(feature = "module_level")]
+/// # {
+/// use loggery::{log_enabled, Level};
+///
+/// loggery::set_min_level(Level::Info);
+/// loggery::set_module_level("net", Level::Debug);
+///
+/// # #[cfg(not(any(feature = "min_level_info", feature = "min_level_warn",
+/// #     feature = "min_level_error", feature = "min_level_off")))]
+/// assert!(log_enabled!(target: "net::tls", Level::Debug));
+/// assert!(!log_enabled!(target: "db", Level::Debug));
+/// # }
+/// ```
+#[macro_export]
+macro_rules! log_enabled {
+    (target: $target:expr, $level:expr) => {
+        $crate::__private::enabled($level, $target)
+    };
+    ($level:expr) => {
+        $crate::log_enabled!(target: module_path!(), $level)
+    };
+}
+
+/// Returns `true` if the given level passes the compile-time level filter (`min_level_*`
+/// features), as a constant expression.
+///
+/// It's a shorthand for [`level_enabled_at_compile_time`], so it works in `const` items and
+/// `const fn`s, and costs nothing at runtime: use it to leave out whole data structures or blocks
+/// only needed by logs that are removed at compile time.
+///
+/// It only knows about the `min_level_*` features. A level it reports as enabled can still be
+/// filtered at runtime, by [`set_min_level`], a module level or the level mask, which
+/// [`log_enabled!`] (and [`is_enabled`]) take into account, at the cost of runtime checks.
+///
+/// # Example
+///
+/// ```
+/// use loggery::{level_enabled, Level};
+///
+/// const DEBUG_ON: bool = level_enabled!(Level::Debug);
+///
+/// // Only allocated when debug logs exist in the binary
+/// const HISTORY_LEN: usize = if DEBUG_ON { 64 } else { 0 };
+///
+/// struct Connection {
+///     history: [u8; HISTORY_LEN],
+/// }
+///
+/// let connection = Connection { history: [0; HISTORY_LEN] };
+///
+/// let removed = cfg!(any(feature = "min_level_info", feature = "min_level_warn",
+///     feature = "min_level_error", feature = "min_level_off"));
+///
+/// assert_eq!(DEBUG_ON, !removed);
+/// assert_eq!(connection.history.len(), if removed { 0 } else { 64 });
+/// ```
+#[macro_export]
+macro_rules! level_enabled {
+    ($level:expr) => {
+        $crate::level_enabled_at_compile_time($level)
+    };
+}
+
+/// Logs a message at a level given as a raw `u8`, e.g., parsed from external data.
+///
+/// Valid raw levels are the [`Level`] discriminants (`0` = [`Level::Trace`] to `4` =
+/// [`Level::Error`]). Anything else is handled by the [`InvalidLevelPolicy`] set with
+/// [`set_invalid_level_policy`]: clamped to [`Level::Error`] (default) or dropped.
+///
+/// # Example
+///
+/// ```
+/// use loggery::{InvalidLevelPolicy, try_log_level};
+///
+/// let raw_level = 42; // Out of range!
+///
+/// // Logged as `Level::Error`
+/// try_log_level!(raw_level, "Message from a remote device");
+///
+/// loggery::set_invalid_level_policy(InvalidLevelPolicy::Drop);
+///
+/// // Dropped
+/// try_log_level!(raw_level, "Message from a remote device");
+///
+/// # #[cfg(feature = "std")]
+/// assert_eq!(loggery::invalid_level_count(), 1);
+/// ```
+#[macro_export]
+macro_rules! try_log_level {
+    ($raw:expr, $($arg:tt)*) => {
+        if let Some(level) = $crate::resolve_level($raw) {
+            $crate::log!(level, $($arg)*);
+        }
+    };
+}
+
+/// Logs a message at the `trace` level.
+///
+/// # Example
+///
+/// ```
+/// use loggery::trace;
+///
+/// trace!("Entering function...");
+/// ```
+///
+/// # Compile-time filtering
+///
+/// If feature `min_level_debug` or higher is enabled, this compiles to nothing in release builds
+/// with optimizations.
+#[macro_export]
+macro_rules! trace {
+    (target: $target:expr, $($arg:tt)+) => {
+        $crate::log!(target: $target, $crate::Level::Trace, $($arg)+);
+    };
+    ($($arg:tt)*) => {
+        $crate::log!($crate::Level::Trace, $($arg)*);
+    };
+}
+
+/// Logs a message at the `debug` level.
+///
+/// # Example
+///
+/// ```
+/// use loggery::debug;
+///
+/// let x = 69;
+///
+/// debug!("Variable `x` is {}", x);
+/// ```
+///
+/// # Compile-time filtering
+///
+/// If feature `min_level_info` or higher is enabled, this compiles to nothing in release builds
+/// with optimizations.
+#[macro_export]
+macro_rules! debug {
+    (target: $target:expr, $($arg:tt)+) => {
+        $crate::log!(target: $target, $crate::Level::Debug, $($arg)+);
+    };
+    ($($arg:tt)*) => {
+        $crate::log!($crate::Level::Debug, $($arg)*);
+    };
+}
+
+/// Logs a message at the `debug` level in debug builds only, like [`debug_assert!`].
+///
+/// In builds with debug assertions, it's the same as [`debug!`] (including the level filters). In
+/// builds without (usually release builds), the call is removed entirely: the arguments aren't
+/// evaluated, whatever the `min_level_*` features and the runtime level.
+///
+/// [`debug!`] is filtered by level, so it's still in release builds unless a `min_level_*` feature
+/// removes it, and can be enabled at runtime. Use `ddebug!` for logs that are only meant for
+/// development, e.g., dumping a large state on every iteration.
+///
+/// # Example
+///
+/// ```
+/// use loggery::ddebug;
+/// use std::cell::Cell;
+///
+/// let evaluated = Cell::new(0);
+/// let state = || {
+///     evaluated.set(evaluated.get() + 1);
+///     [1, 2, 3]
+/// };
+///
+/// ddebug!("state = {:?}", state());
+///
+/// // The arguments are only evaluated in builds with debug assertions
+/// assert_eq!(evaluated.get(), if cfg!(debug_assertions) { 1 } else { 0 });
+/// ```
+#[macro_export]
+macro_rules! ddebug {
+    ($($arg:tt)*) => {
+        if ::core::cfg!(debug_assertions) {
+            $crate::debug!($($arg)*);
+        }
+    };
+}
+
+/// Logs a message at the `info` level.
+///
+/// # Example
+///
+/// ```
+/// use loggery::info;
+///
+/// const PORT: u16 = 8080;
+///
+/// info!("Server is started on port {}", PORT);
+/// ```
+///
+/// # Compile-time filtering
+///
+/// If feature `min_level_warn` or higher is enabled, this compiles to nothing in release builds
+/// with optimizations.
+#[macro_export]
+macro_rules! info {
+    (target: $target:expr, $($arg:tt)+) => {
+        $crate::log!(target: $target, $crate::Level::Info, $($arg)+);
+    };
+    ($($arg:tt)*) => {
+        $crate::log!($crate::Level::Info, $($arg)*);
+    };
+}
+
+/// Logs a message at the `warn` level.
+///
+/// # Example
+///
+/// ```
+/// use loggery::warn;
+///
+/// warn!("Configuration file not found, using defaults");
+/// ```
+///
+/// # Compile-time filtering
+///
+/// If feature `min_level_error` or higher is enabled, this compiles to nothing in release builds
+/// with optimizations.
+#[macro_export]
+macro_rules! warn {
+    (target: $target:expr, $($arg:tt)+) => {
+        $crate::log!(target: $target, $crate::Level::Warn, $($arg)+);
+    };
+    ($($arg:tt)*) => {
+        $crate::log!($crate::Level::Warn, $($arg)*);
+    };
+}
+
+/// Logs a message at the `error` level.
+///
+/// # Example
+///
+/// ```
+/// use loggery::error;
+///
+/// error!("Failed to connect to database!");
+/// ```
+///
+/// # Compile-time filtering
+///
+/// If feature `min_level_off` or higher is enabled, this compiles to nothing in release builds
+/// with optimizations.
+#[macro_export]
+macro_rules! error {
+    (target: $target:expr, $($arg:tt)+) => {
+        $crate::log!(target: $target, $crate::Level::Error, $($arg)+);
+    };
+    ($($arg:tt)*) => {
+        $crate::log!($crate::Level::Error, $($arg)*);
+    };
+}
+
+/// Logs an error and the chain of its sources at the `error` level, as `top: cause1: cause2`
+/// (`std` feature).
+///
+/// Takes a `&dyn std::error::Error` (or any reference coercing to it) and walks its
+/// [`source`](std::error::Error::source) chain, so the causes don't have to be looped over by
+/// hand. The chain is rendered like the arguments of [`error!`], only if the log passes the level
+/// filters. It accepts a `target:` like the level macros.
+///
+/// # Example
+///
+/// ```
+/// use loggery::{error_chain, Payload};
+/// use std::{cell::RefCell, fmt};
+///
+/// #[derive(Debug)]
+/// struct ConfigError(std::io::Error);
+///
+/// impl fmt::Display for ConfigError {
+///     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+///         f.write_str("failed to load the configuration")
+///     }
+/// }
+///
+/// impl std::error::Error for ConfigError {
+///     fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+///         Some(&self.0)
+///     }
+/// }
+///
+/// thread_local! {
+///     static LINES: RefCell<Vec<String>> = RefCell::new(Vec::new());
+/// }
+///
+/// fn my_logger(payload: Payload) {
+///     LINES.with(|lines| lines.borrow_mut().push(payload.args.to_string()));
+/// }
+///
+/// fn main() {
+/// #   #[cfg(not(feature = "static"))]
+///     loggery::set_logger(my_logger);
+///
+///     let not_found = std::io::Error::new(std::io::ErrorKind::NotFound, "config.toml not found");
+///     let error = ConfigError(not_found);
+///
+///     error_chain!(&error);
+///
+/// #   #[cfg(all(not(feature = "static"), not(feature = "min_level_off")))]
+///     LINES.with(|lines| {
+///         assert_eq!(
+///             *lines.borrow(),
+///             ["failed to load the configuration: config.toml not found"]
+///         );
+///     });
+/// }
+/// ```
+///
+/// # Compile-time filtering
+///
+/// If feature `min_level_off` is enabled, this compiles to nothing in release builds with
+/// optimizations.
+#[cfg(feature = "std")]
+#[macro_export]
+macro_rules! error_chain {
+    (target: $target:expr, $err:expr) => {
+        $crate::error!(target: $target, "{}", $crate::__private::ErrorChain($err));
+    };
+    ($err:expr) => {
+        $crate::error!("{}", $crate::__private::ErrorChain($err));
+    };
+}
+
+/// Logs a message at the specified level through a [`Dispatcher`], instead of the global logger.
+///
+/// Accepts the same syntax as [`log!`] after the dispatcher, including `key = value` fields, and
+/// evaluates to the `bool` returned by [`Dispatcher::log`]. See [`Dispatcher`] for an example.
+///
+/// Like [`log!`], the arguments are only evaluated if the dispatcher's level lets the log through.
+#[cfg(feature = "metadata")]
+#[macro_export]
+macro_rules! log_to {
+    ($dispatcher:expr, $level:expr, $($key:tt $(:$capture:tt)? = $value:expr),+; $($arg:tt)+) => {{
+        let dispatcher = &$dispatcher;
+        let level: $crate::Level = $level;
+
+        dispatcher.is_enabled(level)
+            && match &[$(($crate::__key!($key), $crate::__value!($($capture)? $value))),+] {
+                fields => dispatcher.log($crate::Payload::__with_fields(
+                    level,
+                    format_args!(
+                        "{}{}",
+                        format_args!($($arg)+),
+                        $crate::__private::message_suffix(fields)
+                    ),
+                    fields,
+                    $crate::Metadata::__new(module_path!(), file!(), line!()),
+                )),
+            }
+    }};
+    ($dispatcher:expr, $level:expr, $($arg:tt)*) => {{
+        let dispatcher = &$dispatcher;
+        let level: $crate::Level = $level;
+
+        dispatcher.is_enabled(level)
+            && dispatcher.log($crate::Payload::__new(
+                level,
+                format_args!($($arg)*),
+                $crate::Metadata::__new(module_path!(), file!(), line!()),
+            ))
+    }};
+}
+
+/// Logs a message at the specified level through a [`Dispatcher`], instead of the global logger.
+///
+/// Accepts the same syntax as [`log!`] after the dispatcher, including `key = value` fields, and
+/// evaluates to the `bool` returned by [`Dispatcher::log`]. See [`Dispatcher`] for an example.
+///
+/// Like [`log!`], the arguments are only evaluated if the dispatcher's level lets the log through.
+#[cfg(not(feature = "metadata"))]
+#[macro_export]
+macro_rules! log_to {
+    ($dispatcher:expr, $level:expr, $($key:tt $(:$capture:tt)? = $value:expr),+; $($arg:tt)+) => {{
+        let dispatcher = &$dispatcher;
+        let level: $crate::Level = $level;
+
+        dispatcher.is_enabled(level)
+            && match &[$(($crate::__key!($key), $crate::__value!($($capture)? $value))),+] {
+                fields => dispatcher.log($crate::Payload::__with_fields(
+                    level,
+                    format_args!(
+                        "{}{}",
+                        format_args!($($arg)+),
+                        $crate::__private::message_suffix(fields)
+                    ),
+                    fields,
+                )),
+            }
+    }};
+    ($dispatcher:expr, $level:expr, $($arg:tt)*) => {{
+        let dispatcher = &$dispatcher;
+        let level: $crate::Level = $level;
+
+        dispatcher.is_enabled(level)
+            && dispatcher.log($crate::Payload::__new(level, format_args!($($arg)*)))
+    }};
+}
+
+/// Logs a message at the `trace` level through a [`Dispatcher`], see [`log_to!`].
+#[macro_export]
+macro_rules! trace_to {
+    ($dispatcher:expr, $($arg:tt)*) => {
+        $crate::log_to!($dispatcher, $crate::Level::Trace, $($arg)*)
+    };
+}
+
+/// Logs a message at the `debug` level through a [`Dispatcher`], see [`log_to!`].
+#[macro_export]
+macro_rules! debug_to {
+    ($dispatcher:expr, $($arg:tt)*) => {
+        $crate::log_to!($dispatcher, $crate::Level::Debug, $($arg)*)
+    };
+}
+
+/// Logs a message at the `info` level through a [`Dispatcher`], see [`log_to!`].
+#[macro_export]
+macro_rules! info_to {
+    ($dispatcher:expr, $($arg:tt)*) => {
+        $crate::log_to!($dispatcher, $crate::Level::Info, $($arg)*)
+    };
+}
+
+/// Logs a message at the `warn` level through a [`Dispatcher`], see [`log_to!`].
+#[macro_export]
+macro_rules! warn_to {
+    ($dispatcher:expr, $($arg:tt)*) => {
+        $crate::log_to!($dispatcher, $crate::Level::Warn, $($arg)*)
+    };
+}
+
+/// Logs a message at the `error` level through a [`Dispatcher`], see [`log_to!`].
+#[macro_export]
+macro_rules! error_to {
+    ($dispatcher:expr, $($arg:tt)*) => {
+        $crate::log_to!($dispatcher, $crate::Level::Error, $($arg)*)
+    };
+}
+
+/// Logs an already rendered message at the specified level.
+///
+/// The message is logged verbatim: unlike the format string of [`log!`], it can be any `&str`
+/// expression (e.g., a cached message) and braces in it aren't interpreted.
+///
+/// The message isn't copied, formatted or allocated: it's borrowed by the [`Payload`] and written
+/// with a single `write_str` call, without going through the format string machinery of
+/// [`log!`]. Metadata is captured as usual. See [`log_str()`] for the function form.
+///
+/// # Example
+///
+/// ```
+/// use loggery::{log_str, Level, Payload};
+/// use std::cell::RefCell;
+///
+/// thread_local! {
+///     static LAST: RefCell<String> = RefCell::new(String::new());
+/// }
+///
+/// fn my_logger(payload: Payload) {
+///     LAST.with(|last| *last.borrow_mut() = payload.args.to_string());
+/// }
+///
+/// fn main() {
+/// #   #[cfg(not(feature = "static"))]
+/// #   {
+///     loggery::set_logger(my_logger);
+///
+///     let cached = String::from("Cache hit rate: {} {ratio} 100%");
+///
+///     log_str!(Level::Info, &cached);
+///
+/// #   #[cfg(not(feature = "min_level_off"))]
+///     LAST.with(|last| assert_eq!(*last.borrow(), "Cache hit rate: {} {ratio} 100%"));
+/// #   }
+/// }
+/// ```
+#[cfg(feature = "metadata")]
+#[macro_export]
+macro_rules! log_str {
+    ($level:expr, $msg:expr) => {{
+        let _ = $crate::__private::log_str(
+            $level,
+            $msg,
+            $crate::Metadata::__new(module_path!(), file!(), line!()),
+        );
+    }};
+}
+
+/// Logs an already rendered message at the specified level.
+///
+/// The message is logged verbatim: unlike the format string of [`log!`], it can be any `&str`
+/// expression (e.g., a cached message) and braces in it aren't interpreted.
+///
+/// The message isn't copied, formatted or allocated: it's borrowed by the [`Payload`] and written
+/// with a single `write_str` call, without going through the format string machinery of
+/// [`log!`]. See [`log_str()`] for the function form.
+#[cfg(not(feature = "metadata"))]
+#[macro_export]
+macro_rules! log_str {
+    ($level:expr, $msg:expr) => {{
+        let _ = $crate::__private::log_str($level, $msg);
+    }};
+}
+
+/// Logs an already rendered message at the `trace` level, see [`log_str!`].
+///
+/// # Example
+///
+/// ```
+/// use loggery::trace_str;
+///
+/// let message = String::from("Rendered elsewhere");
+///
+/// trace_str!(&message);
+/// ```
+#[macro_export]
+macro_rules! trace_str {
+    ($msg:expr) => {
+        $crate::log_str!($crate::Level::Trace, $msg);
+    };
+}
+
+/// Logs an already rendered message at the `debug` level, see [`log_str!`].
+///
+/// # Example
+///
+/// ```
+/// use loggery::debug_str;
+///
+/// let message = String::from("Rendered elsewhere");
+///
+/// debug_str!(&message);
+/// ```
+#[macro_export]
+macro_rules! debug_str {
+    ($msg:expr) => {
+        $crate::log_str!($crate::Level::Debug, $msg);
+    };
+}
+
+/// Logs an already rendered message at the `info` level, see [`log_str!`].
+///
+/// # Example
+///
+/// ```
+/// use loggery::info_str;
+///
+/// let message = String::from("Rendered elsewhere");
+///
+/// info_str!(&message);
+/// ```
+#[macro_export]
+macro_rules! info_str {
+    ($msg:expr) => {
+        $crate::log_str!($crate::Level::Info, $msg);
+    };
+}
+
+/// Logs an already rendered message at the `warn` level, see [`log_str!`].
+///
+/// # Example
+///
+/// ```
+/// use loggery::warn_str;
+///
+/// let message = String::from("Rendered elsewhere");
+///
+/// warn_str!(&message);
+/// ```
+#[macro_export]
+macro_rules! warn_str {
+    ($msg:expr) => {
+        $crate::log_str!($crate::Level::Warn, $msg);
+    };
+}
+
+/// Logs an already rendered message at the `error` level, see [`log_str!`].
+///
+/// # Example
+///
+/// ```
+/// use loggery::error_str;
+///
+/// let message = String::from("Rendered elsewhere");
+///
+/// error_str!(&message);
+/// ```
+#[macro_export]
+macro_rules! error_str {
+    ($msg:expr) => {
+        $crate::log_str!($crate::Level::Error, $msg);
     };
 }
 
-/// Logs a message at the specified level.
+/// Logs a message at the specified level, at most once per call site.
 ///
-/// This is the underlying macro used by [`trace!`], [`debug!`], [`info!`], [`warn!`] and [`error!`].
-/// While you mostly use those level-specific macros, `log!` can be useful when you want to specify
-/// the log level dynamically or crate your own logging abstractions.
+/// The first time the call site is reached, the message is logged like with [`log!`]. Every later
+/// pass (from any thread) does nothing, which is handy to report a condition in a hot loop without
+/// flooding the logs. Each expansion of the macro has its own flag, so two `log_once!` calls are
+/// independent even with the same message.
+///
+/// The flag is a `static` [`AtomicBool`](core::sync::atomic::AtomicBool), so it's thread-safe and
+/// works in `no_std`, but it needs atomic swaps, which some targets (e.g., `thumbv6m-none-eabi`)
+/// don't have.
+///
+/// > **Note:** A pass counts even if the log is filtered by the level, so a log filtered on its
+/// > first pass is never emitted.
 ///
 /// # Example
 ///
 /// ```
-/// use loggery::{Level, log};
+/// use loggery::{log_once, Level, Payload};
+/// use std::sync::atomic::{AtomicUsize, Ordering};
 ///
-/// let level = if cfg!(debug_assertions) {
-///     Level::Debug
-/// } else {
-///     Level::Info
-/// };
+/// static LOGS: AtomicUsize = AtomicUsize::new(0);
 ///
-/// log!(level, "This is a log with dynamically set level")
+/// fn my_logger(_: Payload) {
+///     LOGS.fetch_add(1, Ordering::Relaxed);
+/// }
+///
+/// fn main() {
+/// #   #[cfg(not(feature = "static"))]
+/// #   {
+///     loggery::set_logger(my_logger);
+///
+///     for i in 0..10 {
+///         log_once!(Level::Warn, "Slow path taken at iteration {}", i);
+///     }
+///
+/// #   #[cfg(not(any(feature = "min_level_error", feature = "min_level_off")))]
+///     assert_eq!(LOGS.load(Ordering::Relaxed), 1);
+/// #   }
+/// }
 /// ```
-#[cfg(not(feature = "metadata"))]
 #[macro_export]
-macro_rules! log {
-    ($level:expr, $($arg:tt)*) => {
-        $crate::log($crate::Payload {
-            level: $level,
-            args: format_args!($($arg)*),
-        })
-    };
+macro_rules! log_once {
+    ($level:expr, $($arg:tt)+) => {{
+        static FIRED: ::core::sync::atomic::AtomicBool =
+            ::core::sync::atomic::AtomicBool::new(false);
+
+        if !FIRED.load(::core::sync::atomic::Ordering::Relaxed)
+            && !FIRED.swap(true, ::core::sync::atomic::Ordering::Relaxed)
+        {
+            $crate::log!($level, $($arg)+);
+        } else {
+            $crate::__private::count_drop($crate::__private::DropReason::Once);
+        }
+    }};
 }
 
-/// Logs a message at the `trace` level.
+/// Logs a message at the `warn` level, at most once per call site, see [`log_once!`].
 ///
 /// # Example
 ///
 /// ```
-/// use loggery::trace;
+/// use loggery::warn_once;
 ///
-/// trace!("Entering function...");
+/// for _ in 0..3 {
+///     warn_once!("Config file not found, using defaults");
+/// }
 /// ```
+#[macro_export]
+macro_rules! warn_once {
+    ($($arg:tt)+) => {
+        $crate::log_once!($crate::Level::Warn, $($arg)+)
+    };
+}
+
+/// Logs a message at the `error` level, at most once per call site, see [`log_once!`].
 ///
-/// # Compile-time filtering
+/// # Example
 ///
-/// If feature `min_level_debug` or higher is enabled, this compiles to nothing in release builds
-/// with optimizations.
+/// ```
+/// use loggery::error_once;
+///
+/// for _ in 0..3 {
+///     error_once!("Sensor {} isn't responding", 7);
+/// }
+/// ```
 #[macro_export]
-macro_rules! trace {
-    ($($arg:tt)*) => {
-        $crate::log!($crate::Level::Trace, $($arg)*);
+macro_rules! error_once {
+    ($($arg:tt)+) => {
+        $crate::log_once!($crate::Level::Error, $($arg)+)
     };
 }
 
-/// Logs a message at the `debug` level.
+/// Logs the error of a `Result` at the `error` level and returns the `Result` unchanged.
+///
+/// The expression is evaluated exactly once. If it's an `Err(e)`, `e` is logged with its
+/// [`Display`](core::fmt::Display) implementation (like `error!("{}", e)`), an `Ok` is passed
+/// through without logging. Since the `Result` is returned as is, it combines with `?` to log and
+/// propagate an error in one expression: `let x = log_err!(do_thing())?;`.
 ///
 /// # Example
 ///
 /// ```
-/// use loggery::debug;
+/// use loggery::{log_err, Payload};
+/// use std::cell::RefCell;
 ///
-/// let x = 69;
+/// thread_local! {
+///     static LAST: RefCell<String> = RefCell::new(String::new());
+/// }
 ///
-/// debug!("Variable `x` is {}", x);
-/// ```
+/// fn my_logger(payload: Payload) {
+///     LAST.with(|last| *last.borrow_mut() = payload.args.to_string());
+/// }
 ///
-/// # Compile-time filtering
+/// fn parse_port(text: &str) -> Result<u16, std::num::ParseIntError> {
+///     let port = log_err!(text.parse::<u16>())?;
 ///
-/// If feature `min_level_info` or higher is enabled, this compiles to nothing in release builds
-/// with optimizations.
+///     Ok(port)
+/// }
+///
+/// fn main() {
+/// #   #[cfg(not(feature = "static"))]
+///     loggery::set_logger(my_logger);
+///
+///     assert_eq!(parse_port("8080"), Ok(8080));
+///     assert!(parse_port("http").is_err());
+///
+/// #   #[cfg(all(not(feature = "static"), not(feature = "min_level_off")))]
+///     LAST.with(|last| assert_eq!(*last.borrow(), "invalid digit found in string"));
+/// }
+/// ```
 #[macro_export]
-macro_rules! debug {
-    ($($arg:tt)*) => {
-        $crate::log!($crate::Level::Debug, $($arg)*);
+macro_rules! log_err {
+    ($result:expr $(,)?) => {
+        match $result {
+            result => {
+                if let ::core::result::Result::Err(error) = &result {
+                    $crate::error!("{}", error);
+                }
+
+                result
+            }
+        }
     };
 }
 
-/// Logs a message at the `info` level.
+/// Logs a message at the specified level on the 1st, `n + 1`th, `2n + 1`th, ... pass of the call
+/// site.
+///
+/// Samples high-frequency events instead of logging every one of them. Each expansion of the
+/// macro has its own `static` [`AtomicUsize`](core::sync::atomic::AtomicUsize) counter,
+/// incremented atomically on every pass (from any thread), whether or not the log passes the
+/// runtime level filters. An `n` of `0` is treated as `1`, logging every pass.
+///
+/// If the level is removed at compile time (`min_level_*` features), the counter isn't touched
+/// and the whole call compiles to nothing in release builds with optimizations (as long as the
+/// level is a constant).
+///
+/// Like [`log_once!`], it needs atomic read-modify-write operations, which some targets (e.g.,
+/// `thumbv6m-none-eabi`) don't have.
+///
+/// > **Note:** The counter is internal to the call site and can't be referenced from the message.
+/// > To show how many events a log stands for, mention `n` in the message.
 ///
 /// # Example
 ///
 /// ```
-/// use loggery::info;
+/// use loggery::{log_every_n, Level, Payload};
+/// use std::sync::atomic::{AtomicUsize, Ordering};
 ///
-/// const PORT: u16 = 8080;
+/// static LOGS: AtomicUsize = AtomicUsize::new(0);
 ///
-/// info!("Server is started on port {}", PORT);
-/// ```
+/// fn my_logger(_: Payload) {
+///     LOGS.fetch_add(1, Ordering::Relaxed);
+/// }
 ///
-/// # Compile-time filtering
+/// fn main() {
+/// #   #[cfg(not(feature = "static"))]
+/// #   {
+///     loggery::set_logger(my_logger);
 ///
-/// If feature `min_level_warn` or higher is enabled, this compiles to nothing in release builds
-/// with optimizations.
+///     for i in 0..250 {
+///         // Logs for `i` = 0, 100 and 200
+///         log_every_n!(100, Level::Warn, "Packet {} dropped (1 in 100 logged)", i);
+///     }
+///
+/// #   #[cfg(not(any(feature = "min_level_error", feature = "min_level_off")))]
+///     assert_eq!(LOGS.load(Ordering::Relaxed), 3);
+/// #   }
+/// }
+/// ```
 #[macro_export]
-macro_rules! info {
-    ($($arg:tt)*) => {
-        $crate::log!($crate::Level::Info, $($arg)*);
-    };
+macro_rules! log_every_n {
+    ($n:expr, $level:expr, $($arg:tt)+) => {{
+        static COUNT: ::core::sync::atomic::AtomicUsize =
+            ::core::sync::atomic::AtomicUsize::new(0);
+
+        let level: $crate::Level = $level;
+
+        if $crate::level_enabled_at_compile_time(level) {
+            let n: usize = $n;
+            let count = COUNT.fetch_add(1, ::core::sync::atomic::Ordering::Relaxed);
+
+            if count % ::core::cmp::max(n, 1) == 0 {
+                $crate::log!(level, $($arg)+);
+            } else {
+                $crate::__private::count_drop($crate::__private::DropReason::Sampling);
+            }
+        }
+    }};
 }
 
-/// Logs a message at the `warn` level.
+/// Logs an expression, its type and its [`Debug`](core::fmt::Debug) value at the `debug` level.
+///
+/// Logs `expression: TypeName = value`, which is handy to find out what a generic parameter
+/// actually is. The expression is only borrowed, so it's still usable after the log.
 ///
 /// # Example
 ///
 /// ```
-/// use loggery::warn;
+/// use loggery::{type_dbg, Payload};
+/// use std::cell::RefCell;
 ///
-/// warn!("Configuration file not found, using defaults");
+/// thread_local! {
+///     static LAST: RefCell<String> = RefCell::new(String::new());
+/// }
+///
+/// fn my_logger(payload: Payload) {
+///     LAST.with(|last| *last.borrow_mut() = payload.args.to_string());
+/// }
+///
+/// fn main() {
+/// #   #[cfg(not(feature = "static"))]
+/// #   {
+///     loggery::set_logger(my_logger);
+///
+///     let x: u16 = 42;
+///
+///     type_dbg!(x);
+///
+/// #   #[cfg(not(any(feature = "min_level_info", feature = "min_level_warn",
+/// #       feature = "min_level_error", feature = "min_level_off")))]
+///     LAST.with(|last| assert_eq!(*last.borrow(), "x: u16 = 42"));
+/// #   }
+/// }
 /// ```
 ///
-/// # Compile-time filtering
+/// # Note
 ///
-/// If feature `min_level_error` or higher is enabled, this compiles to nothing in release builds
-/// with optimizations.
+/// The type name comes from [`core::any::type_name`], whose output isn't guaranteed to be stable:
+/// it may differ between compiler versions (e.g., `alloc::vec::Vec<u8>` or `Vec<u8>`), so it's
+/// meant for debugging, not for parsing.
 #[macro_export]
-macro_rules! warn {
-    ($($arg:tt)*) => {
-        $crate::log!($crate::Level::Warn, $($arg)*);
+macro_rules! type_dbg {
+    ($value:expr $(,)?) => {
+        match &$value {
+            value => $crate::log!(
+                $crate::Level::Debug,
+                "{}: {} = {:?}",
+                stringify!($value),
+                $crate::__type_name_of(value),
+                value
+            ),
+        }
     };
 }
 
-/// Logs a message at the `error` level.
+/// Opens a scope whose label prefixes every log emitted while the returned [`ScopeGuard`] is
+/// alive, like a lightweight `tracing` span.
+///
+/// The default logger and the built-in text extensions write the labels of the active scopes
+/// (outermost first) between the level and the message: `[ INFO] [request] [db] message`. Custom
+/// loggers can get them with [`for_each_scope`].
+///
+/// With the `std` feature, scopes are tracked per thread, and the guard closes its scope (and any
+/// scope opened after it) when dropped. Without it, there's no thread-local storage: only the
+/// innermost scope is tracked, globally, and dropping the guard restores the previous one, so
+/// guards must be dropped in the reverse order of their creation.
+///
+/// The label must be a constant `&'static str` expression, like a string literal.
 ///
 /// # Example
 ///
 /// ```
-/// use loggery::error;
+/// use loggery::info;
 ///
-/// error!("Failed to connect to database!");
-/// ```
+/// fn handle_request() {
+///     let _scope = loggery::scope!("request handling");
 ///
-/// # Compile-time filtering
+///     // [ INFO] [request handling] Parsing the headers
+///     info!("Parsing the headers");
+/// }
 ///
-/// If feature `min_level_off` or higher is enabled, this compiles to nothing in release builds
-/// with optimizations.
+/// fn main() {
+/// #   #[cfg(feature = "std")]
+/// #   {
+///     use loggery::{Destination, Level, StreamRouting};
+///
+///     // Send the default logger's output to a file to check it
+///     let path = std::env::temp_dir().join("loggery_scope_doctest.log");
+///     let _ = std::fs::remove_file(&path);
+///     let path: &'static str = Box::leak(path.to_string_lossy().into_owned().into_boxed_str());
+///
+///     loggery::set_stream_routing(StreamRouting::new().route(Level::Info, Destination::File(path)));
+///
+///     handle_request();
+///
+///     // [ INFO] Done
+///     info!("Done");
+///
+/// #   #[cfg(all(any(not(feature = "static"), feature = "static_default"),
+/// #       not(feature = "min_level_off"), not(feature = "thread_meta"),
+/// #       not(feature = "cpu_meta")))]
+/// #   {
+///     let contents = std::fs::read_to_string(path).unwrap();
+///
+///     assert!(contents.contains("[ INFO] [request handling] Parsing the headers\n"));
+///     assert!(contents.ends_with("[ INFO] Done\n"));
+/// #   }
+/// #   }
+/// }
+/// ```
 #[macro_export]
-macro_rules! error {
-    ($($arg:tt)*) => {
-        $crate::log!($crate::Level::Error, $($arg)*);
-    };
+macro_rules! scope {
+    ($label:expr $(,)?) => {{
+        static LABEL: &str = $label;
+
+        $crate::ScopeGuard::__enter(&LABEL)
+    }};
+}
+
+/// Returns the type name of the value behind a reference, used by the [`type_dbg!`] macro.
+#[doc(hidden)]
+#[inline(always)]
+pub fn __type_name_of<T: ?Sized>(_: &T) -> &'static str {
+    core::any::type_name::<T>()
 }
 
 /// Built-in extension utilities for common logging tasks.
@@ -833,10 +3950,84 @@ pub mod extensions {
     #[cfg(feature = "std")]
     extern crate std;
 
+    #[cfg(feature = "std")]
+    mod async_logger;
+    #[cfg(feature = "std")]
+    mod broadcast;
+    #[cfg(feature = "std")]
+    mod dedup;
+    #[cfg(feature = "std")]
+    mod ecs;
+    #[cfg(feature = "std")]
+    mod file;
+    #[cfg(feature = "std")]
+    mod frame;
+    #[cfg(feature = "gzip")]
+    mod gzip;
+    #[cfg(feature = "std")]
+    mod hash_chain;
+    #[cfg(feature = "std")]
+    mod json;
+    #[cfg(feature = "std")]
+    mod logfmt;
+    #[cfg(feature = "tracing_appender")]
+    mod non_blocking;
+    #[cfg(feature = "std")]
+    mod redact;
+    #[cfg(all(feature = "anyhow", feature = "std"))]
+    mod report;
+    #[cfg(feature = "std")]
+    mod rotating;
+    #[cfg(all(feature = "shm", unix))]
+    mod shm;
+    #[cfg(feature = "std")]
+    mod syslog;
+
+    #[cfg(feature = "std")]
+    pub use async_logger::{AsyncLogger, OverflowPolicy};
+    #[cfg(feature = "std")]
+    pub use broadcast::BroadcastSink;
+    #[cfg(feature = "std")]
+    pub use dedup::DedupLogger;
+    #[cfg(feature = "std")]
+    pub use ecs::format_ecs;
+    #[cfg(feature = "std")]
+    pub use file::FileLogger;
+    #[cfg(feature = "std")]
+    pub use frame::{
+        decode_frame, encode_frame, frame_len, DecodeError, OwnedMetadata, OwnedRecord,
+    };
+    #[cfg(feature = "gzip")]
+    pub use gzip::GzipFileSink;
+    #[cfg(feature = "std")]
+    pub use hash_chain::HashChainSink;
+    #[cfg(feature = "std")]
+    pub use json::{save_json_to_file, to_json, write_json};
+    #[cfg(feature = "std")]
+    pub use logfmt::to_logfmt;
+    #[cfg(feature = "tracing_appender")]
+    pub use non_blocking::NonBlockingSink;
+    #[cfg(feature = "std")]
+    pub use redact::RedactingLogger;
+    #[cfg(all(feature = "anyhow", feature = "std"))]
+    pub use report::log_report;
+    #[cfg(feature = "std")]
+    pub use rotating::RotatingFileLogger;
+    #[cfg(all(feature = "shm", unix))]
+    pub use shm::{ShmReader, ShmSink};
+    #[cfg(feature = "std")]
+    pub use syslog::to_syslog;
+
     /// Appends a log entry to a file (`std` feature)
     ///
     /// The file at the `path` is opened in append mode.
     ///
+    /// # Performance
+    ///
+    /// The file is opened and closed on every call, which is convenient for occasional logs but
+    /// costs two extra syscalls per log. Under load, use a [`FileLogger`] instead, which keeps the
+    /// file open.
+    ///
     /// # Example
     ///
     /// ```no_run
@@ -858,6 +4049,12 @@ pub mod extensions {
     /// # Format
     ///
     /// Logs are written in the format: `[LEVEL] message`
+    ///
+    /// With the `timestamp` feature, the time is prepended in the format set with
+    /// `set_timestamp_format`: `timestamp [LEVEL] message`
+    ///
+    /// A formatter set with [`set_global_formatter`](crate::set_global_formatter) replaces this
+    /// format.
     #[cfg(feature = "std")]
     #[inline]
     pub fn save_to_file(payload: &Payload, path: &str) -> std::io::Result<()> {
@@ -868,27 +4065,338 @@ pub mod extensions {
             .append(true)
             .open(path)?;
 
-        writeln!(file, "[{}] {}", payload.level.as_str(), payload.args)
+        writeln!(file, "{}", crate::format::Line::timestamped(payload))
     }
 }
 
-#[cfg(any(
-    all(feature = "std", not(feature = "static")),
-    feature = "static_default",
-))]
+#[cfg(feature = "std")]
 mod stdout {
     extern crate std;
 
     use crate::Payload;
+    use std::{io::Write, vec::Vec};
+
+    /// The default logger, writing logs in the `[LEVEL] message` format to stdout or stderr.
+    /// (`std` feature)
+    ///
+    /// It's the logger used until [`set_logger`](crate::set_logger) is called, and the one of the
+    /// `static_default` feature. Each log is written to the destination of its level (see
+    /// [`set_stream_routing`](crate::set_stream_routing)), with the formatter of that route if it
+    /// has one, otherwise in the default format or the one set with
+    /// [`set_format`](crate::set_format) or [`set_global_formatter`](crate::set_global_formatter).
+    ///
+    /// It's a [`LoggerFn`](crate::LoggerFn), so custom loggers can wrap it: do their own work, then
+    /// delegate to it, without reimplementing the format.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use loggery::{error, Level, Payload};
+    /// use std::sync::atomic::{AtomicUsize, Ordering};
+    ///
+    /// static ERRORS: AtomicUsize = AtomicUsize::new(0);
+    ///
+    /// fn counting_logger(payload: Payload) {
+    ///     if payload.level == Level::Error {
+    ///         ERRORS.fetch_add(1, Ordering::Relaxed);
+    ///     }
+    ///
+    ///     // Written as `[ERROR] Disk full`
+    ///     loggery::default_logger(payload);
+    /// }
+    ///
+    /// fn main() {
+    /// #   #[cfg(not(feature = "static"))]
+    ///     loggery::set_logger(counting_logger);
+    ///
+    ///     error!("Disk full");
+    ///
+    /// #   #[cfg(all(not(feature = "static"), not(feature = "min_level_off")))]
+    ///     assert_eq!(ERRORS.load(Ordering::Relaxed), 1);
+    /// }
+    /// ```
+    #[inline(always)]
+    pub fn default_logger(payload: Payload) {
+        let routing = crate::get_stream_routing();
+        let formatter_fn = routing.formatter(payload.level);
+
+        match routing.destination(payload.level) {
+            crate::Destination::Stdout => {
+                let stdout = std::io::stdout();
+                let _ = write_line(&mut stdout.lock(), &payload, formatter_fn);
+            }
+            crate::Destination::Stderr => {
+                let stderr = std::io::stderr();
+                let _ = write_line(&mut stderr.lock(), &payload, formatter_fn);
+            }
+            crate::Destination::File(path) => {
+                if let Some(file) = crate::routing::open_file(path) {
+                    let mut line = Vec::new();
+
+                    if write_line(&mut line, &payload, formatter_fn).is_ok() {
+                        let _ = (&*file).write_all(&line);
+                    }
+                }
+            }
+            crate::Destination::Drop => {}
+        }
+    }
 
-    /// Default stdout logger (`std` feature).
+    /// Writes a log with the formatter of its route, or in the default format (or the one set
+    /// with [`crate::set_format`]).
+    ///
+    /// A single trailing newline of the rendered log is trimmed, so a message already ending with
+    /// `\n` doesn't leave a blank line.
     #[inline(always)]
-    pub(super) fn logger_fn(payload: Payload) {
-        use std::io::Write as _;
+    fn write_line(
+        out: &mut impl std::io::Write,
+        payload: &Payload,
+        formatter_fn: Option<crate::FormatterFn>,
+    ) -> std::io::Result<()> {
+        let mut line = TrimNewline {
+            out: &mut *out,
+            pending_newline: false,
+        };
+
+        match formatter_fn {
+            Some(formatter_fn) => {
+                write!(line, "{}", crate::format::Formatted(formatter_fn, payload))?
+            }
+            None => write!(line, "{}", crate::format::DefaultLoggerLine(payload))?,
+        }
+
+        out.write_all(b"\n")
+    }
+
+    /// Holds back the last newline written, it's only passed on if more is written after it.
+    struct TrimNewline<'a, W: std::io::Write> {
+        out: &'a mut W,
+        pending_newline: bool,
+    }
+
+    impl<W: std::io::Write> std::io::Write for TrimNewline<'_, W> {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            if buf.is_empty() {
+                return Ok(0);
+            }
+
+            if self.pending_newline {
+                self.out.write_all(b"\n")?;
+                self.pending_newline = false;
+            }
+
+            match buf.split_last() {
+                Some((b'\n', rest)) => {
+                    self.out.write_all(rest)?;
+                    self.pending_newline = true;
+                }
+                _ => self.out.write_all(buf)?,
+            }
+
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            self.out.flush()
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+mod aliases;
+#[cfg(feature = "catch_panic")]
+mod catch_panic;
+#[cfg(feature = "cpu_meta")]
+mod cpu;
+mod diagnostics;
+mod dispatcher;
+#[cfg(feature = "emission_stats")]
+mod emission_stats;
+mod fields;
+#[cfg(feature = "filter")]
+mod filter;
+mod flush;
+#[cfg(feature = "std")]
+mod format;
+#[cfg(feature = "std")]
+mod gap_markers;
+#[cfg(feature = "std")]
+mod generation;
+#[cfg(feature = "std")]
+mod labels;
+#[cfg(feature = "level_mask")]
+mod level_mask;
+mod metrics;
+#[cfg(feature = "std")]
+mod panic_hook;
+#[cfg(feature = "std")]
+mod panicking;
+#[cfg(feature = "std")]
+mod recent;
+#[cfg(feature = "std")]
+mod routing;
+mod scope;
+#[cfg(feature = "seq")]
+mod seq;
+#[cfg(all(feature = "std", any(unix, windows)))]
+mod shutdown;
+#[cfg(feature = "std")]
+mod shutdown_mode;
+#[cfg(feature = "target_route")]
+mod target_route;
+#[cfg(feature = "thread_meta")]
+mod thread;
+#[cfg(feature = "std")]
+mod thread_sink;
+#[cfg(feature = "timestamp")]
+mod timestamp;
+mod truncate;
+
+pub mod embedded;
+#[cfg(feature = "testing")]
+pub mod testing;
 
-        let stdout = std::io::stdout();
-        let mut handle = stdout.lock();
-        let _ = writeln!(handle, "[{}] {}", payload.level.as_str(), payload.args);
+#[cfg(feature = "std")]
+pub use aliases::{register_level_alias, MAX_LEVEL_ALIASES};
+#[cfg(feature = "cpu_meta")]
+pub use cpu::current_cpu;
+#[cfg(feature = "std")]
+pub use diagnostics::warn_config_issues;
+pub use diagnostics::{diagnose_config, log_startup_banner, ConfigDiagnostics, ConfigIssue};
+pub use dispatcher::Dispatcher;
+#[cfg(feature = "emission_stats")]
+pub use emission_stats::{emission_stats, reset_emission_stats};
+#[cfg(feature = "filter")]
+pub use filter::{clear_filter, set_filter, FilterFn};
+pub use flush::{
+    flush, get_flush_policy, is_always_flush, set_always_flush, set_flush, set_flush_policy,
+    FlushFn, FlushPolicy,
+};
+#[cfg(feature = "std")]
+pub use format::{
+    clear_format, clear_global_formatter, colored, get_format, get_global_formatter,
+    multiline_fields, set_format, set_global_formatter, FormatterFn,
+};
+#[cfg(feature = "std")]
+pub use gap_markers::{is_seq_gap_markers, set_seq_gap_markers};
+#[cfg(feature = "std")]
+pub use generation::{
+    bump_generation, current_generation, is_show_generation, set_show_generation,
+};
+#[cfg(feature = "std")]
+pub use labels::{
+    get_level_align, get_level_label, is_compact_level_labels, set_compact_level_labels,
+    set_level_align, set_level_labels, LevelAlign,
+};
+#[cfg(feature = "level_mask")]
+pub use level_mask::{get_level_mask, set_level_mask, LevelSet};
+#[cfg(feature = "metrics")]
+pub use metrics::{drop_breakdown, level_counts, reset_level_counts, DropReason};
+#[cfg(feature = "std")]
+pub use panic_hook::{install_panic_hook, install_panic_hook_chained};
+#[cfg(feature = "std")]
+pub use panicking::{get_panicking_policy, set_panicking_policy, PanickingPolicy};
+#[cfg(feature = "std")]
+pub use recent::{dump_recent, get_recent_capacity, set_recent_capacity};
+#[cfg(feature = "std")]
+pub use routing::{
+    get_stream_routing, set_stderr_threshold, set_stream_routing, Destination, StreamRouting,
+};
+pub use scope::{for_each_scope, ScopeGuard};
+#[cfg(all(feature = "std", any(unix, windows)))]
+pub use shutdown::install_atexit_flush;
+#[cfg(feature = "std")]
+pub use shutdown_mode::{enter_shutdown_mode, is_shutdown_mode, suppressed_log_count};
+#[cfg(feature = "std")]
+pub use stdout::default_logger;
+#[cfg(feature = "target_route")]
+pub use target_route::{add_target_route, clear_target_routes, MAX_TARGET_ROUTES};
+#[cfg(feature = "thread_meta")]
+pub use thread::current_thread_id;
+#[cfg(feature = "std")]
+pub use thread_sink::{set_thread_sink, with_thread_sink, ThreadSinkGuard};
+
+pub use truncate::{get_max_message_len, set_max_message_len, truncate_str};
+
+#[cfg(feature = "timestamp")]
+pub use timestamp::{
+    get_timestamp_format, set_clock, set_timestamp_format, ClockFn, DisplayTimestamp,
+    TimestampFormat,
+};
+
+/// Minimal spin lock for the global tables that don't fit in a single atomic.
+///
+/// Requires compare-and-swap atomics, so features using it aren't available on targets like
+/// `thumbv6m-none-eabi`. The lock is only held while reading or updating a table, never while
+/// calling the logger or extensions.
+#[cfg(any(
+    feature = "std",
+    feature = "module_level",
+    feature = "seq",
+    feature = "target_route"
+))]
+mod spin {
+    use core::sync::atomic::{AtomicBool, Ordering};
+
+    pub(crate) struct Mutex<T> {
+        locked: AtomicBool,
+        value: core::cell::UnsafeCell<T>,
+    }
+
+    // SAFETY: Access to `value` is serialized by `locked`.
+    unsafe impl<T: Send> Sync for Mutex<T> {}
+
+    impl<T> Mutex<T> {
+        pub(crate) const fn new(value: T) -> Self {
+            Self {
+                locked: AtomicBool::new(false),
+                value: core::cell::UnsafeCell::new(value),
+            }
+        }
+
+        #[inline]
+        pub(crate) fn lock(&self) -> MutexGuard<'_, T> {
+            while self
+                .locked
+                .compare_exchange_weak(false, true, Ordering::Acquire, Ordering::Relaxed)
+                .is_err()
+            {
+                while self.locked.load(Ordering::Relaxed) {
+                    core::hint::spin_loop();
+                }
+            }
+
+            MutexGuard { mutex: self }
+        }
+    }
+
+    pub(crate) struct MutexGuard<'a, T> {
+        mutex: &'a Mutex<T>,
+    }
+
+    impl<T> core::ops::Deref for MutexGuard<'_, T> {
+        type Target = T;
+
+        #[inline]
+        fn deref(&self) -> &T {
+            // SAFETY: The guard holds the lock.
+            unsafe { &*self.mutex.value.get() }
+        }
+    }
+
+    impl<T> core::ops::DerefMut for MutexGuard<'_, T> {
+        #[inline]
+        fn deref_mut(&mut self) -> &mut T {
+            // SAFETY: The guard holds the lock.
+            unsafe { &mut *self.mutex.value.get() }
+        }
+    }
+
+    impl<T> Drop for MutexGuard<'_, T> {
+        #[inline]
+        fn drop(&mut self) {
+            self.mutex.locked.store(false, Ordering::Release);
+        }
     }
 }
 
@@ -901,6 +4409,6 @@ mod static_impl_std {
     /// Default logger implementation for when the `std` and `static` features are enabled.
     #[no_mangle]
     pub extern "Rust" fn __loggery_log_impl(payload: Payload) {
-        crate::stdout::logger_fn(payload);
+        crate::stdout::default_logger(payload);
     }
 }
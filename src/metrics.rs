@@ -0,0 +1,204 @@
+#[cfg(feature = "metrics")]
+use core::sync::atomic::{AtomicUsize, Ordering};
+
+/// Empty counter, used to initialize [`DROPS`] and [`EMITTED`].
+#[cfg(feature = "metrics")]
+#[allow(clippy::declare_interior_mutable_const)]
+const NO_DROPS: AtomicUsize = AtomicUsize::new(0);
+/// Number of dropped logs per reason storage, indexed by reason.
+#[cfg(feature = "metrics")]
+static DROPS: [AtomicUsize; DropReason::COUNT] = [NO_DROPS; DropReason::COUNT];
+/// Number of emitted logs per level storage, indexed by level.
+#[cfg(feature = "metrics")]
+static EMITTED: [AtomicUsize; 5] = [NO_DROPS; 5];
+
+/// Why a log was dropped, see `drop_breakdown`. (`metrics` feature)
+///
+/// Logs removed at compile time by the `min_level_*` features aren't counted: they don't exist in
+/// the binary.
+#[cfg_attr(not(feature = "metrics"), allow(dead_code))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum DropReason {
+    /// Filtered by the runtime level filters: `set_min_level`, a module level or the level mask.
+    Level = 0,
+    /// A repeated pass of a [`log_once!`](crate::log_once) call site.
+    Once = 1,
+    /// A pass skipped by [`log_every_n!`](crate::log_every_n).
+    Sampling = 2,
+    /// Suppressed by `enter_shutdown_mode`, or not written by an `AsyncLogger` shut down with
+    /// `shutdown_timeout` (`std` feature).
+    Shutdown = 3,
+    /// Emitted while the thread was panicking, with the `PanickingPolicy::Drop` policy (`std`
+    /// feature).
+    Panicking = 4,
+    /// An invalid raw level with the [`InvalidLevelPolicy::Drop`](crate::InvalidLevelPolicy)
+    /// policy.
+    InvalidLevel = 5,
+    /// Not delivered to a subscriber of a `BroadcastSink` whose channel was full, or dropped by an
+    /// `AsyncLogger` whose queue was full (`extension` feature). Counted once per subscriber that
+    /// missed it.
+    FullChannel = 6,
+    /// Dropped by the filter set with `set_filter` (`filter` feature).
+    Filter = 7,
+}
+
+impl DropReason {
+    /// The number of reasons, i.e., the length of `drop_breakdown`.
+    pub const COUNT: usize = 8;
+
+    /// All the reasons, in the order of `drop_breakdown`.
+    pub const ALL: [DropReason; DropReason::COUNT] = [
+        DropReason::Level,
+        DropReason::Once,
+        DropReason::Sampling,
+        DropReason::Shutdown,
+        DropReason::Panicking,
+        DropReason::InvalidLevel,
+        DropReason::FullChannel,
+        DropReason::Filter,
+    ];
+}
+
+/// Returns how many logs were dropped for each [`DropReason`], indexed by `reason as usize`.
+/// (`metrics` feature)
+///
+/// The counters wrap around and can miss concurrent drops on targets without compare-and-swap
+/// atomics, like the ones of [`level_counts`] (see [Counters](level_counts#counters)).
+///
+/// # Example
+///
+/// ```
+/// use loggery::{debug, log_every_n, DropReason, Level};
+///
+/// # #[cfg(feature = "runtime_level")]
+/// loggery::set_min_level(Level::Info);
+///
+/// debug!("Filtered by the level");
+///
+/// for i in 0..10 {
+///     log_every_n!(5, Level::Warn, "Event {}", i);
+/// }
+///
+/// let drops = loggery::drop_breakdown();
+///
+/// # #[cfg(all(feature = "runtime_level", not(any(feature = "min_level_info",
+/// #     feature = "min_level_warn", feature = "min_level_error", feature = "min_level_off"))))]
+/// assert_eq!(drops[DropReason::Level as usize], 1);
+/// # #[cfg(not(any(feature = "min_level_error", feature = "min_level_off")))]
+/// assert_eq!(drops[DropReason::Sampling as usize], 8);
+/// assert_eq!(drops[DropReason::Shutdown as usize], 0);
+/// ```
+#[cfg(feature = "metrics")]
+pub fn drop_breakdown() -> [u64; DropReason::COUNT] {
+    let mut breakdown = [0; DropReason::COUNT];
+
+    for (count, drops) in breakdown.iter_mut().zip(DROPS.iter()) {
+        *count = drops.load(Ordering::Relaxed) as u64;
+    }
+
+    breakdown
+}
+
+/// Returns how many logs of each level were emitted, indexed by `level as usize`, from
+/// [`Level::Trace`](crate::Level::Trace) to [`Level::Error`](crate::Level::Error). (`metrics`
+/// feature)
+///
+/// A log is counted once it passes the filters (levels, shutdown mode, `set_filter`), right before
+/// it's passed to the extensions and the loggers. Filtered and dropped logs aren't counted (see
+/// [`drop_breakdown`] for those), and neither are the logs written to stderr while the thread is
+/// panicking. It's cheap enough for health endpoints: one relaxed atomic increment per log.
+///
+/// # Counters
+///
+/// The counters are `usize` atomics, since 64-bit atomics aren't available on every target: they
+/// wrap around to zero after `usize::MAX` logs, i.e., about 4 billion logs of a level on 32-bit
+/// targets. Read and reset them periodically if that many logs are expected.
+///
+/// On targets without compare-and-swap atomics (e.g., `thumbv6m-none-eabi`), a count is a plain
+/// atomic load and store instead of an atomic increment: logs counted concurrently (e.g., from an
+/// interrupt handler) can be missed.
+///
+/// # Example
+///
+/// ```
+/// use loggery::{error, info, warn, Level};
+///
+/// # #[cfg(feature = "runtime_level")]
+/// loggery::set_min_level(Level::Warn);
+///
+/// info!("Filtered by the level");
+/// warn!("Disk almost full");
+/// error!("Disk full");
+/// error!("Write failed");
+///
+/// let counts = loggery::level_counts();
+///
+/// # #[cfg(not(any(feature = "min_level_error", feature = "min_level_off")))]
+/// # {
+/// assert_eq!(counts[Level::Error as usize], 2);
+/// assert_eq!(counts[Level::Warn as usize], 1);
+/// # #[cfg(feature = "runtime_level")]
+/// assert_eq!(counts[Level::Info as usize], 0);
+/// # }
+///
+/// loggery::reset_level_counts();
+///
+/// assert_eq!(loggery::level_counts(), [0; 5]);
+/// ```
+#[cfg(feature = "metrics")]
+pub fn level_counts() -> [u64; 5] {
+    let mut counts = [0; 5];
+
+    for (count, emitted) in counts.iter_mut().zip(EMITTED.iter()) {
+        *count = emitted.load(Ordering::Relaxed) as u64;
+    }
+
+    counts
+}
+
+/// Resets the counts of [`level_counts`] to zero. (`metrics` feature)
+#[cfg(feature = "metrics")]
+pub fn reset_level_counts() {
+    for emitted in EMITTED.iter() {
+        emitted.store(0, Ordering::Relaxed);
+    }
+}
+
+/// Counts an emitted log.
+#[cfg(feature = "metrics")]
+#[inline(always)]
+pub(crate) fn count_emitted(level: crate::Level) {
+    if let Some(emitted) = EMITTED.get(level as usize) {
+        increment(emitted);
+    }
+}
+
+/// Adds one to a counter, with a load and a store on targets without compare-and-swap atomics.
+#[cfg(feature = "metrics")]
+#[inline(always)]
+fn increment(counter: &AtomicUsize) {
+    #[cfg(not(loggery_no_atomic_cas))]
+    counter.fetch_add(1, Ordering::Relaxed);
+
+    #[cfg(loggery_no_atomic_cas)]
+    counter.store(
+        counter.load(Ordering::Relaxed).wrapping_add(1),
+        Ordering::Relaxed,
+    );
+}
+
+/// Counts a dropped log (with the `metrics` feature), and records the gap for
+/// `set_seq_gap_markers` (with the `std` feature).
+#[doc(hidden)]
+#[inline(always)]
+pub fn count_drop(reason: DropReason) {
+    #[cfg(feature = "metrics")]
+    increment(&DROPS[reason as usize]);
+
+    #[cfg(feature = "std")]
+    crate::gap_markers::record(reason);
+
+    #[cfg(not(any(feature = "metrics", feature = "std")))]
+    let _ = reason;
+}
@@ -0,0 +1,95 @@
+extern crate std;
+
+use core::any::Any;
+use std::{boxed::Box, panic::Location, string::String};
+
+/// Replaces the panic hook with one logging panics at the `error` level, as
+/// `panic at <file>:<line>:<column>: <message>`. (`std` feature)
+///
+/// The default hook (writing `thread '...' panicked at ...` to stderr) is dropped, so panics
+/// only show up in the logs. Use [`install_panic_hook_chained`] to keep it (or any hook set
+/// before). The hook is global: it's called for the panics of every thread, and replaces the one
+/// set by any other crate. Calling this again replaces the hook again.
+///
+/// # Panicking policy
+///
+/// The hook runs while the thread is already panicking, so the log goes through the policy set
+/// with `set_panicking_policy`. With the default [`PanickingPolicy::Fallback`], it's written to
+/// stderr as `[ERROR] panic at ...` instead of reaching the loggers. Set
+/// [`PanickingPolicy::Normal`] to pass it to the loggers and extensions, which then must not
+/// panic: a panic in a panic hook aborts the process.
+///
+/// [`PanickingPolicy::Fallback`]: crate::PanickingPolicy::Fallback
+/// [`PanickingPolicy::Normal`]: crate::PanickingPolicy::Normal
+///
+/// # Example
+///
+/// ```
+/// use loggery::{Payload, PanickingPolicy};
+/// use std::cell::RefCell;
+///
+/// thread_local! {
+///     static LINES: RefCell<Vec<String>> = RefCell::new(Vec::new());
+/// }
+///
+/// fn my_logger(payload: Payload) {
+///     LINES.with(|lines| lines.borrow_mut().push(payload.args.to_string()));
+/// }
+///
+/// fn main() {
+/// #   #[cfg(not(feature = "static"))]
+///     loggery::set_logger(my_logger);
+///     loggery::set_panicking_policy(PanickingPolicy::Normal);
+///     loggery::install_panic_hook();
+///
+///     let result = std::panic::catch_unwind(|| panic!("Invalid state: {}", 42));
+///
+///     assert!(result.is_err());
+///
+/// #   #[cfg(all(not(feature = "static"), not(feature = "min_level_off")))]
+///     LINES.with(|lines| {
+///         let lines = lines.borrow();
+///
+///         assert!(lines[0].starts_with("panic at "));
+///         assert!(lines[0].ends_with(": Invalid state: 42"));
+///     });
+/// }
+/// ```
+pub fn install_panic_hook() {
+    std::panic::set_hook(Box::new(|info| log_panic(info.payload(), info.location())));
+}
+
+/// Wraps the current panic hook (the default one, unless another was set) with one logging panics
+/// like [`install_panic_hook`], then calling the previous hook. (`std` feature)
+///
+/// With the default hook, panics are both logged and reported on stderr as usual. Calling this
+/// several times chains the hooks, logging each panic several times.
+pub fn install_panic_hook_chained() {
+    let previous = std::panic::take_hook();
+
+    std::panic::set_hook(Box::new(move |info| {
+        log_panic(info.payload(), info.location());
+
+        previous(info);
+    }));
+}
+
+/// Logs a panic with its message and location.
+fn log_panic(payload: &(dyn Any + Send), location: Option<&Location<'_>>) {
+    let message = if let Some(message) = payload.downcast_ref::<&str>() {
+        message
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message.as_str()
+    } else {
+        "Box<dyn Any>"
+    };
+
+    match location {
+        Some(location) => {
+            crate::error!("panic at {}: {}", location, message);
+        }
+        None => {
+            crate::error!("panic at an unknown location: {}", message);
+        }
+    }
+}
@@ -0,0 +1,115 @@
+extern crate std;
+
+use crate::Payload;
+
+use core::sync::atomic::{AtomicU8, Ordering};
+
+/// Policy for logs emitted while the thread is panicking storage.
+static PANICKING_POLICY: AtomicU8 = AtomicU8::new(PanickingPolicy::Fallback as u8);
+
+/// What to do with a log emitted while the thread is unwinding from a panic, e.g., from a `Drop`
+/// implementation. (`std` feature)
+///
+/// A panic during unwinding aborts the process, so a logger that can panic (or that takes a lock
+/// poisoned by the panic) would turn a recoverable panic into an abort.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum PanickingPolicy {
+    /// Write the log straight to stderr as `[LEVEL] message` (with the `key=value` fields of the
+    /// `kv` feature), bypassing the extensions and the loggers (default).
+    Fallback = 0,
+    /// Pass the log to the extensions and the loggers as usual.
+    Normal = 1,
+    /// Drop the log.
+    Drop = 2,
+}
+
+/// Sets what happens to logs emitted while the thread is panicking. (`std` feature)
+///
+/// Defaults to [`PanickingPolicy::Fallback`]: the log is written to stderr without allocating,
+/// taking locks (other than the reentrant lock of stderr) or calling any extension or logger.
+/// Only the message itself is rendered, so its arguments must not panic. The timestamp, scopes
+/// and the formats set with `set_global_formatter` or `set_format` aren't used.
+///
+/// The log still goes through the level filters first.
+///
+/// # Example
+///
+/// ```
+/// use loggery::{error, Payload};
+///
+/// fn fragile_logger(_: Payload) {
+///     panic!("The logger failed");
+/// }
+///
+/// struct Connection;
+///
+/// impl Drop for Connection {
+///     fn drop(&mut self) {
+///         // Written to stderr instead of calling `fragile_logger`, which would abort
+///         error!("Connection dropped while panicking");
+///     }
+/// }
+///
+/// fn main() {
+/// #   #[cfg(not(feature = "static"))]
+///     loggery::set_logger(fragile_logger);
+///
+/// #   std::panic::set_hook(Box::new(|_| {}));
+///     let result = std::panic::catch_unwind(|| {
+///         let _connection = Connection;
+///
+///         panic!("Request failed");
+///     });
+///
+///     assert!(result.is_err());
+/// }
+/// ```
+#[inline]
+pub fn set_panicking_policy(policy: PanickingPolicy) {
+    PANICKING_POLICY.store(policy as u8, Ordering::Relaxed);
+}
+
+/// Returns the policy set with [`set_panicking_policy`]. (`std` feature)
+#[inline]
+pub fn get_panicking_policy() -> PanickingPolicy {
+    match PANICKING_POLICY.load(Ordering::Relaxed) {
+        1 => PanickingPolicy::Normal,
+        2 => PanickingPolicy::Drop,
+        _ => PanickingPolicy::Fallback,
+    }
+}
+
+/// Handles the log with the policy if the thread is panicking, returning whether it was written.
+///
+/// Returns `None` if the log must be dispatched as usual.
+#[inline(always)]
+pub(crate) fn intercept(payload: &Payload) -> Option<bool> {
+    if !std::thread::panicking() {
+        return None;
+    }
+
+    match get_panicking_policy() {
+        PanickingPolicy::Normal => None,
+        PanickingPolicy::Drop => {
+            crate::metrics::count_drop(crate::metrics::DropReason::Panicking);
+
+            Some(false)
+        }
+        PanickingPolicy::Fallback => {
+            use std::io::Write as _;
+
+            let stderr = std::io::stderr();
+            let mut stderr = stderr.lock();
+
+            let _ = write!(stderr, "[{}] {}", payload.level.as_str(), payload.args);
+
+            #[cfg(feature = "kv")]
+            let _ = write!(stderr, "{}", crate::fields::KeyValues(payload.fields));
+
+            let _ = stderr.write_all(b"\n");
+
+            Some(true)
+        }
+    }
+}
@@ -0,0 +1,167 @@
+extern crate std;
+
+use crate::{Level, Payload};
+
+use core::sync::atomic::{AtomicUsize, Ordering};
+use std::{collections::VecDeque, string::String};
+
+/// Maximum number of filtered logs kept, `0` when disabled.
+static RECENT_CAPACITY: AtomicUsize = AtomicUsize::new(0);
+/// Most recent filtered logs, oldest first.
+static RECENT: crate::spin::Mutex<Option<VecDeque<Recent>>> = crate::spin::Mutex::new(None);
+
+/// A filtered log, with its message already rendered.
+struct Recent {
+    level: Level,
+    message: String,
+    #[cfg(feature = "metadata")]
+    meta: crate::Metadata,
+    #[cfg(feature = "timestamp")]
+    timestamp: u64,
+}
+
+/// Keeps the `capacity` most recent logs filtered out by the runtime level, so they can be
+/// emitted after the fact with [`dump_recent`]. (`std` feature)
+///
+/// This gives the verbose context of a failure without logging verbosely all the time: log at
+/// `Info` normally, and dump the recent `Debug`/`Trace` logs when an error is detected.
+///
+/// The buffer is a ring: once `capacity` logs are kept, each new one evicts the oldest. It's
+/// disabled by default (a `capacity` of `0`), and setting a smaller capacity drops the oldest
+/// logs.
+///
+/// Only logs below the active level are kept (filtered by [`set_min_level`](crate::set_min_level),
+/// a module level or the level mask), logs removed at compile time by the `min_level_*` features
+/// never reach the buffer. Kept logs are rendered right away, so every filtered log costs its
+/// formatting (but no I/O) while the buffer is enabled.
+///
+/// # Example
+///
+/// ```
+/// use loggery::{debug, error, Level, Payload};
+/// use std::cell::RefCell;
+///
+/// thread_local! {
+///     static LOGGED: RefCell<Vec<String>> = RefCell::new(Vec::new());
+/// }
+///
+/// fn my_logger(payload: Payload) {
+///     LOGGED.with(|logged| logged.borrow_mut().push(payload.args.to_string()));
+/// }
+///
+/// fn main() {
+/// #   #[cfg(not(feature = "static"))]
+///     loggery::set_logger(my_logger);
+/// #   #[cfg(feature = "runtime_level")]
+///     loggery::set_min_level(Level::Info);
+///     loggery::set_recent_capacity(2);
+///
+///     debug!("Opening the file");
+///     debug!("Reading 512 bytes");
+///     debug!("Parsing the header");
+///
+///     error!("Invalid header");
+///     loggery::dump_recent();
+///
+/// #   #[cfg(all(feature = "runtime_level", not(feature = "static"), not(any(
+/// #       feature = "min_level_info", feature = "min_level_warn", feature = "min_level_error",
+/// #       feature = "min_level_off"))))]
+///     LOGGED.with(|logged| {
+///         // Only the 2 most recent debug logs were kept
+///         assert_eq!(
+///             *logged.borrow(),
+///             ["Invalid header", "Reading 512 bytes", "Parsing the header"]
+///         );
+///     });
+/// }
+/// ```
+#[inline]
+pub fn set_recent_capacity(capacity: usize) {
+    RECENT_CAPACITY.store(capacity, Ordering::Relaxed);
+
+    if let Some(recent) = RECENT.lock().as_mut() {
+        while recent.len() > capacity {
+            recent.pop_front();
+        }
+    }
+}
+
+/// Returns the capacity set with [`set_recent_capacity`] (`0` when disabled). (`std` feature)
+#[inline]
+pub fn get_recent_capacity() -> usize {
+    RECENT_CAPACITY.load(Ordering::Relaxed)
+}
+
+/// Emits the logs kept by [`set_recent_capacity`], oldest first, and empties the buffer.
+/// (`std` feature)
+///
+/// The logs are passed to the extension and the loggers with their original level (and
+/// metadata), bypassing the level filters that removed them in the first place.
+pub fn dump_recent() {
+    let recent = match RECENT.lock().take() {
+        Some(recent) => recent,
+        None => return,
+    };
+
+    for log in recent {
+        replay(&log, format_args!("{}", log.message));
+    }
+}
+
+/// Passes a kept log to the extension and the loggers.
+fn replay(log: &Recent, args: core::fmt::Arguments) {
+    #[cfg_attr(not(feature = "timestamp"), allow(unused_mut))]
+    let mut payload = Payload::__new(
+        log.level,
+        args,
+        #[cfg(feature = "metadata")]
+        log.meta,
+    );
+
+    #[cfg(feature = "timestamp")]
+    {
+        payload.timestamp = log.timestamp;
+    }
+
+    crate::emit(payload);
+}
+
+/// Keeps a filtered log if the buffer is enabled.
+#[inline(always)]
+pub(crate) fn record(payload: &Payload) {
+    let capacity = RECENT_CAPACITY.load(Ordering::Relaxed);
+
+    if capacity == 0 {
+        return;
+    }
+
+    // Rendered before locking, since rendering can log. The fields can't be kept, so they're
+    // appended to the message.
+    #[cfg_attr(not(feature = "kv"), allow(unused_mut))]
+    let mut message = std::format!("{}", payload.args);
+
+    #[cfg(feature = "kv")]
+    {
+        use core::fmt::Write as _;
+
+        let _ = write!(message, "{}", crate::__private::KeyValues(payload.fields));
+    }
+
+    let log = Recent {
+        level: payload.level,
+        message,
+        #[cfg(feature = "metadata")]
+        meta: payload.meta,
+        #[cfg(feature = "timestamp")]
+        timestamp: payload.timestamp,
+    };
+
+    let mut recent = RECENT.lock();
+    let recent = recent.get_or_insert_with(VecDeque::new);
+
+    while recent.len() >= capacity {
+        recent.pop_front();
+    }
+
+    recent.push_back(log);
+}
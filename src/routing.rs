@@ -0,0 +1,328 @@
+extern crate std;
+
+use crate::{FormatterFn, Level};
+
+use std::{fs::File, sync::Arc, vec::Vec};
+
+/// Per-level destinations storage.
+static STREAM_ROUTING: crate::spin::Mutex<StreamRouting> =
+    crate::spin::Mutex::new(StreamRouting::new());
+
+/// Files opened for [`Destination::File`] routes, by path.
+static OPEN_FILES: crate::spin::Mutex<Vec<(&'static str, Arc<File>)>> =
+    crate::spin::Mutex::new(Vec::new());
+
+/// Where the default logger writes a log. (`std` feature)
+///
+/// Every log is written followed by a single newline. A message that already ends with a newline
+/// (e.g., pre-formatted text) doesn't leave a blank line after it, only one trailing `\n` is
+/// trimmed though, further ones are kept.
+///
+/// # Example
+///
+/// ```
+/// use loggery::{info, Destination, Level, StreamRouting};
+///
+/// let path = std::env::temp_dir().join("loggery_destination_doctest.log");
+/// let _ = std::fs::remove_file(&path);
+/// let path: &'static str = Box::leak(path.to_string_lossy().into_owned().into_boxed_str());
+///
+/// loggery::set_stream_routing(StreamRouting::new().route(Level::Info, Destination::File(path)));
+///
+/// info!("Report:\n  3 sensors ready\n");
+/// info!("Done");
+///
+/// # #[cfg(all(any(not(feature = "static"), feature = "static_default"), not(any(
+/// #     feature = "min_level_warn", feature = "min_level_error", feature = "min_level_off")),
+/// #     not(feature = "thread_meta"), not(feature = "cpu_meta")))]
+/// # {
+/// let contents = std::fs::read_to_string(path).unwrap();
+/// let mut lines = contents.lines();
+///
+/// assert!(lines.next().unwrap().ends_with("[ INFO] Report:"));
+/// assert_eq!(lines.next(), Some("  3 sensors ready"));
+/// assert!(lines.next().unwrap().ends_with("[ INFO] Done"));
+/// assert_eq!(lines.next(), None);
+///
+/// // Rotate the file, setting the routing again reopens it
+/// let rotated = format!("{}.1", path);
+/// std::fs::rename(path, &rotated).unwrap();
+///
+/// loggery::set_stream_routing(StreamRouting::new().route(Level::Info, Destination::File(path)));
+///
+/// info!("Reopened");
+///
+/// assert!(std::fs::read_to_string(path).unwrap().ends_with("[ INFO] Reopened\n"));
+/// # let _ = std::fs::remove_file(&rotated);
+/// # }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Destination {
+    /// The standard output.
+    Stdout,
+    /// The standard error.
+    Stderr,
+    /// A file at the given path, opened in append mode.
+    ///
+    /// The file is opened on the first log routed to it and kept open, so later logs cost a
+    /// single write. Each log is rendered first and written with one call, so lines from several
+    /// threads don't interleave. [`set_stream_routing`] closes the files opened so far, call it
+    /// again to reopen a file that was rotated or deleted in the meantime. If the file can't be
+    /// opened, the log is discarded and opening is tried again on the next one.
+    File(&'static str),
+    /// Nowhere, the log is discarded.
+    Drop,
+}
+
+/// Maps every [`Level`] to the [`Destination`] the default logger writes it to. (`std` feature)
+///
+/// By default, [`Level::Warn`] and [`Level::Error`] go to [`Destination::Stderr`] (so they aren't
+/// swallowed when stdout is redirected to a data pipe) and the other levels go to
+/// [`Destination::Stdout`].
+///
+/// # Example
+///
+/// ```
+/// use loggery::{debug, warn, Destination, Level, StreamRouting};
+///
+/// let path = std::env::temp_dir().join("loggery_stream_routing_doctest.log");
+/// let _ = std::fs::remove_file(&path);
+///
+/// // `Destination::File` needs a `&'static str`
+/// let path: &'static str = Box::leak(path.to_string_lossy().into_owned().into_boxed_str());
+///
+/// let routing = StreamRouting::new()
+///     .route(Level::Debug, Destination::Drop)
+///     .route(Level::Warn, Destination::File(path))
+///     .route(Level::Error, Destination::Stderr);
+///
+/// loggery::set_stream_routing(routing);
+///
+/// assert_eq!(loggery::get_stream_routing().destination(Level::Debug), Destination::Drop);
+/// assert_eq!(loggery::get_stream_routing().destination(Level::Error), Destination::Stderr);
+///
+/// debug!("Dropped");
+/// warn!("Saved to the file");
+///
+/// # #[cfg(all(any(not(feature = "static"), feature = "static_default"), not(feature = "min_level_off"),
+/// #     not(feature = "thread_meta"), not(feature = "cpu_meta")))]
+/// assert!(std::fs::read_to_string(path)
+///     .unwrap()
+///     .ends_with("[ WARN] Saved to the file\n"));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StreamRouting {
+    destinations: [Destination; 5],
+    /// Addresses of the route formatters (`0` for none), function pointers can't be used in
+    /// `const fn` on older compilers.
+    formatters: [usize; 5],
+}
+
+impl StreamRouting {
+    /// Creates the default routing, [`Level::Warn`] and [`Level::Error`] go to
+    /// [`Destination::Stderr`] and the other levels go to [`Destination::Stdout`].
+    #[inline]
+    pub const fn new() -> Self {
+        Self::stderr_from(Level::Warn)
+    }
+
+    /// Creates a routing where levels from `threshold` up go to [`Destination::Stderr`] and the
+    /// levels below it go to [`Destination::Stdout`].
+    ///
+    /// A `threshold` of [`Level::Off`] sends every level to [`Destination::Stdout`].
+    #[inline]
+    pub const fn stderr_from(threshold: Level) -> Self {
+        let mut destinations = [Destination::Stdout; 5];
+        let mut index = threshold as usize;
+
+        while index < destinations.len() {
+            destinations[index] = Destination::Stderr;
+            index += 1;
+        }
+
+        Self {
+            destinations,
+            formatters: [0; 5],
+        }
+    }
+
+    /// Routes logs at `level` to `destination`, in the format of the default logger.
+    ///
+    /// This replaces the whole route of `level`, including a formatter set with
+    /// [`route_with_format`](Self::route_with_format).
+    ///
+    /// Routing [`Level::Off`] does nothing, since nothing is logged at that level.
+    #[inline]
+    pub fn route(mut self, level: Level, destination: Destination) -> Self {
+        if let Some(slot) = self.destinations.get_mut(level as usize) {
+            *slot = destination;
+            self.formatters[level as usize] = 0;
+        }
+
+        self
+    }
+
+    /// Routes logs at `level` to `destination`, formatted by `formatter_fn`.
+    ///
+    /// The formatter takes precedence over the format of the default logger for this route only,
+    /// including a formatter set with [`set_global_formatter`](crate::set_global_formatter), so
+    /// each destination can have its own format, e.g., [`colored`](crate::colored) for a terminal
+    /// and JSON for a file. The default logger adds the trailing newline.
+    ///
+    /// Routing [`Level::Off`] does nothing, since nothing is logged at that level.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use loggery::{error, info, Destination, Level, Payload, StreamRouting};
+    /// use std::fmt;
+    ///
+    /// fn alert_format(payload: &Payload, out: &mut dyn fmt::Write) -> fmt::Result {
+    ///     write!(out, "ALERT: {}", payload.args)
+    /// }
+    ///
+    /// let path = std::env::temp_dir().join("loggery_route_with_format_doctest.log");
+    /// let _ = std::fs::remove_file(&path);
+    /// let path: &'static str = Box::leak(path.to_string_lossy().into_owned().into_boxed_str());
+    ///
+    /// loggery::set_stream_routing(
+    ///     StreamRouting::new()
+    ///         .route_with_format(Level::Info, Destination::Stdout, loggery::colored)
+    ///         .route_with_format(Level::Error, Destination::File(path), alert_format),
+    /// );
+    ///
+    /// // Green `[ INFO]` label on stdout
+    /// info!("Backup started");
+    /// error!("Backup failed");
+    ///
+    /// # #[cfg(all(any(not(feature = "static"), feature = "static_default"),
+    /// #     not(feature = "min_level_off")))]
+    /// assert_eq!(std::fs::read_to_string(path).unwrap(), "ALERT: Backup failed\n");
+    /// ```
+    #[inline]
+    pub fn route_with_format(
+        mut self,
+        level: Level,
+        destination: Destination,
+        formatter_fn: FormatterFn,
+    ) -> Self {
+        if let Some(slot) = self.destinations.get_mut(level as usize) {
+            *slot = destination;
+            self.formatters[level as usize] = formatter_fn as usize;
+        }
+
+        self
+    }
+
+    /// Returns the destination of logs at `level` ([`Destination::Drop`] for [`Level::Off`]).
+    #[inline]
+    pub fn destination(&self, level: Level) -> Destination {
+        self.destinations
+            .get(level as usize)
+            .copied()
+            .unwrap_or(Destination::Drop)
+    }
+
+    /// Returns the formatter of the route of `level`, if it was set with
+    /// [`route_with_format`](Self::route_with_format).
+    #[inline]
+    pub fn formatter(&self, level: Level) -> Option<FormatterFn> {
+        match self.formatters.get(level as usize).copied() {
+            None | Some(0) => None,
+            // SAFETY: non-zero addresses were created from a `FormatterFn` in
+            // `route_with_format`, function pointers are 'static.
+            Some(addr) => Some(unsafe { core::mem::transmute::<usize, FormatterFn>(addr) }),
+        }
+    }
+}
+
+impl Default for StreamRouting {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Sets where the default logger writes each level. (`std` feature)
+///
+/// This only affects the default logger, a logger set with `set_logger` (or a custom
+/// `__loggery_log_impl`) decides on its own where logs go.
+///
+/// The files opened for [`Destination::File`] routes are closed, the next log routed to a file
+/// opens it again.
+///
+/// See [`StreamRouting`] for an example.
+#[inline]
+pub fn set_stream_routing(routing: StreamRouting) {
+    *STREAM_ROUTING.lock() = routing;
+
+    // Closed after unlocking, like every other I/O
+    let files = core::mem::take(&mut *OPEN_FILES.lock());
+    drop(files);
+}
+
+/// Sets the level from which the default logger writes to stderr instead of stdout.
+/// (`std` feature)
+///
+/// Levels from `threshold` up go to stderr, the levels below it go to stdout. Defaults to
+/// [`Level::Warn`]. Use [`Level::Off`] to send everything to stdout.
+///
+/// This replaces the whole routing, including [`Destination::File`] and [`Destination::Drop`]
+/// routes, it's a shorthand for `set_stream_routing(StreamRouting::stderr_from(threshold))`.
+///
+/// # Locking
+///
+/// Each log only locks the stream it's written to, so logs going to stdout and to stderr don't
+/// wait for each other. As a consequence, when both streams end up in the same place (e.g., with
+/// `2>&1`), the order of lines *across* the two streams isn't guaranteed: stdout is line-buffered
+/// by the standard library while stderr isn't, and two threads can write to them concurrently. The
+/// order within each stream is always preserved.
+///
+/// # Example
+///
+/// ```
+/// use loggery::{Destination, Level};
+///
+/// loggery::set_stderr_threshold(Level::Error);
+///
+/// let routing = loggery::get_stream_routing();
+///
+/// assert_eq!(routing.destination(Level::Warn), Destination::Stdout);
+/// assert_eq!(routing.destination(Level::Error), Destination::Stderr);
+/// ```
+#[inline]
+pub fn set_stderr_threshold(threshold: Level) {
+    set_stream_routing(StreamRouting::stderr_from(threshold));
+}
+
+/// Returns the current routing of the default logger. (`std` feature)
+#[inline]
+pub fn get_stream_routing() -> StreamRouting {
+    *STREAM_ROUTING.lock()
+}
+
+/// Returns the file of a [`Destination::File`] route, opening it in append mode if it isn't
+/// already open.
+pub(crate) fn open_file(path: &'static str) -> Option<Arc<File>> {
+    if let Some((_, file)) = OPEN_FILES.lock().iter().find(|(open, _)| *open == path) {
+        return Some(Arc::clone(file));
+    }
+
+    // Opened without holding the lock, another thread may open it meanwhile
+    let file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .ok()?;
+
+    let mut files = OPEN_FILES.lock();
+
+    if let Some((_, open)) = files.iter().find(|(open, _)| *open == path) {
+        return Some(Arc::clone(open));
+    }
+
+    let file = Arc::new(file);
+    files.push((path, Arc::clone(&file)));
+
+    Some(file)
+}
@@ -0,0 +1,129 @@
+#[cfg(feature = "std")]
+extern crate std;
+
+use core::marker::PhantomData;
+
+#[cfg(feature = "std")]
+std::thread_local! {
+    /// Labels of the active scopes of the thread, outermost first.
+    static SCOPES: core::cell::RefCell<std::vec::Vec<&'static str>> =
+        core::cell::RefCell::new(std::vec::Vec::new());
+}
+
+/// Label of the innermost active scope, without thread-local storage.
+#[cfg(not(feature = "std"))]
+static SCOPE: core::sync::atomic::AtomicPtr<&'static str> =
+    core::sync::atomic::AtomicPtr::new(core::ptr::null_mut());
+
+/// Guard of a scope opened with [`scope!`](crate::scope), closing it when dropped.
+///
+/// It can't be sent to another thread, since scopes are tracked per thread.
+#[must_use = "the scope is closed as soon as the guard is dropped"]
+pub struct ScopeGuard {
+    #[cfg(feature = "std")]
+    depth: usize,
+    #[cfg(not(feature = "std"))]
+    previous: *mut &'static str,
+    _not_send: PhantomData<*const ()>,
+}
+
+impl ScopeGuard {
+    /// Opens a scope, used by the [`scope!`](crate::scope) macro.
+    #[doc(hidden)]
+    #[inline]
+    pub fn __enter(label: &'static &'static str) -> Self {
+        #[cfg(feature = "std")]
+        {
+            let depth = SCOPES
+                .try_with(|scopes| {
+                    let mut scopes = scopes.borrow_mut();
+                    scopes.push(*label);
+
+                    scopes.len() - 1
+                })
+                .unwrap_or(0);
+
+            Self {
+                depth,
+                _not_send: PhantomData,
+            }
+        }
+
+        #[cfg(not(feature = "std"))]
+        {
+            let previous = SCOPE.load(core::sync::atomic::Ordering::Acquire);
+
+            SCOPE.store(
+                label as *const &'static str as *mut &'static str,
+                core::sync::atomic::Ordering::Release,
+            );
+
+            Self {
+                previous,
+                _not_send: PhantomData,
+            }
+        }
+    }
+}
+
+impl Drop for ScopeGuard {
+    fn drop(&mut self) {
+        #[cfg(feature = "std")]
+        let _ = SCOPES.try_with(|scopes| scopes.borrow_mut().truncate(self.depth));
+
+        #[cfg(not(feature = "std"))]
+        SCOPE.store(self.previous, core::sync::atomic::Ordering::Release);
+    }
+}
+
+/// Calls `f` with the label of every active scope, outermost first.
+///
+/// Used by the default format, and available to custom loggers to render the scopes their own
+/// way. Without the `std` feature, only the innermost scope is tracked (globally, not per thread).
+///
+/// `f` must not open scopes itself.
+///
+/// # Example
+///
+/// ```
+/// let _request = loggery::scope!("request");
+/// let _db = loggery::scope!("db");
+///
+/// let mut scopes = Vec::new();
+/// loggery::for_each_scope(|label| scopes.push(label));
+///
+/// # #[cfg(feature = "std")]
+/// assert_eq!(scopes, ["request", "db"]);
+/// ```
+#[inline]
+pub fn for_each_scope(mut f: impl FnMut(&'static str)) {
+    #[cfg(feature = "std")]
+    let _ = SCOPES.try_with(|scopes| {
+        for label in scopes.borrow().iter() {
+            f(label);
+        }
+    });
+
+    #[cfg(not(feature = "std"))]
+    {
+        let ptr = SCOPE.load(core::sync::atomic::Ordering::Acquire);
+
+        if !ptr.is_null() {
+            // SAFETY: `ptr` was created from a `&'static &'static str` in `ScopeGuard::__enter`.
+            f(unsafe { *ptr });
+        }
+    }
+}
+
+/// Writes the active scopes as ` [outer] [inner]`.
+pub(crate) struct Scopes;
+
+impl core::fmt::Display for Scopes {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let mut result = Ok(());
+
+        for_each_scope(|label| result = result.and_then(|_| write!(f, " [{}]", label)));
+
+        result
+    }
+}
@@ -0,0 +1,14 @@
+/// Sequence number of the next emitted log storage.
+///
+/// Behind a lock since 64-bit atomics aren't available on every target.
+static NEXT_SEQ: crate::spin::Mutex<u64> = crate::spin::Mutex::new(1);
+
+/// Returns the sequence number of a log about to be emitted, one more than the previous one.
+#[inline(always)]
+pub(crate) fn next() -> u64 {
+    let mut next_seq = NEXT_SEQ.lock();
+    let seq = *next_seq;
+    *next_seq = seq.wrapping_add(1);
+
+    seq
+}
@@ -0,0 +1,59 @@
+extern crate std;
+
+use core::sync::atomic::{AtomicBool, Ordering};
+
+/// Whether the exit handler has already been registered.
+static ATEXIT_INSTALLED: AtomicBool = AtomicBool::new(false);
+
+extern "C" {
+    fn atexit(callback: extern "C" fn()) -> std::os::raw::c_int;
+}
+
+/// Registers a handler that flushes the built-in sinks when the program exits. (`std` feature)
+///
+/// The handler calls [`flush`](crate::flush) on a normal return from `main` and on
+/// [`std::process::exit`], so buffered log output isn't lost when a shutdown path forgets to
+/// flush. A panic of the function set with [`set_flush`](crate::set_flush) or of a logger is
+/// caught by the handler, since it can't unwind into the C runtime: the panic message is printed as
+/// usual, the rest of the flush is skipped and the process exits normally. The handler is
+/// registered with the C runtime's `atexit`, so it only has to be installed once; calling this
+/// again does nothing.
+///
+/// Returns `true` if the handler was registered by this call.
+///
+/// # Limitations
+///
+/// Like every `atexit` handler, it does **not** run when the process is terminated by
+/// [`std::process::abort`], `_exit`, a signal (e.g., `SIGKILL`) or a panic with `panic = "abort"`.
+///
+/// # Example
+///
+/// ```
+/// assert!(loggery::install_atexit_flush());
+///
+/// // Already installed
+/// assert!(!loggery::install_atexit_flush());
+/// ```
+pub fn install_atexit_flush() -> bool {
+    if ATEXIT_INSTALLED.swap(true, Ordering::AcqRel) {
+        return false;
+    }
+
+    // SAFETY: `flush_at_exit` is a plain `extern "C"` function that catches the panics of the
+    // flush, so it never unwinds into the C runtime.
+    let result = unsafe { atexit(flush_at_exit) };
+
+    if result != 0 {
+        ATEXIT_INSTALLED.store(false, Ordering::Release);
+
+        return false;
+    }
+
+    true
+}
+
+/// Flushes the built-in sinks and the loggers. Called from the C runtime, so a panic of the
+/// flush is caught here: unwinding out of an `extern "C"` function is undefined behavior.
+extern "C" fn flush_at_exit() {
+    let _ = std::panic::catch_unwind(crate::flush);
+}
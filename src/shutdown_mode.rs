@@ -0,0 +1,79 @@
+use crate::Level;
+
+use core::sync::atomic::{AtomicU8, AtomicUsize, Ordering};
+
+/// Most severe suppressed level plus one, `0` when not in shutdown mode.
+static SUPPRESS_UP_TO: AtomicU8 = AtomicU8::new(0);
+/// Number of logs suppressed by the shutdown mode.
+static SUPPRESSED_COUNT: AtomicUsize = AtomicUsize::new(0);
+
+/// Enters shutdown mode: from now on, logs at or below `suppress_at_or_below` are dropped (and
+/// counted by [`suppressed_log_count`]) until the process exits. (`std` feature)
+///
+/// Meant for graceful shutdowns, where expected churn (e.g., "connection closed" warnings) would
+/// only be noise. Logs above the given level still pass, so a real problem during the shutdown
+/// is still visible. Calling it again replaces the level, and [`Level::Off`] suppresses every log.
+///
+/// Suppressed logs are dropped after the level filters, so they only count if they would have
+/// been logged otherwise. [`is_enabled`](crate::is_enabled) doesn't take the shutdown mode into
+/// account.
+///
+/// # Example
+///
+/// ```
+/// use loggery::{error, warn, Level, Payload};
+/// use std::sync::atomic::{AtomicUsize, Ordering};
+///
+/// static LOGGED: AtomicUsize = AtomicUsize::new(0);
+///
+/// fn my_logger(_: Payload) {
+///     LOGGED.fetch_add(1, Ordering::Relaxed);
+/// }
+///
+/// fn main() {
+/// #   #[cfg(not(feature = "static"))]
+///     loggery::set_logger(my_logger);
+///
+///     loggery::enter_shutdown_mode(Level::Warn);
+///
+///     assert!(loggery::is_shutdown_mode());
+///
+///     warn!("Connection closed");
+///     error!("Failed to save the state");
+///
+/// #   #[cfg(all(not(feature = "static"), not(feature = "min_level_off")))]
+/// #   {
+///     // Only the error was logged
+///     assert_eq!(LOGGED.load(Ordering::Relaxed), 1);
+///     assert_eq!(loggery::suppressed_log_count(), 1);
+/// #   }
+/// }
+/// ```
+#[inline]
+pub fn enter_shutdown_mode(suppress_at_or_below: Level) {
+    SUPPRESS_UP_TO.store(suppress_at_or_below as u8 + 1, Ordering::Relaxed);
+}
+
+/// Returns `true` if [`enter_shutdown_mode`] was called. (`std` feature)
+#[inline]
+pub fn is_shutdown_mode() -> bool {
+    SUPPRESS_UP_TO.load(Ordering::Relaxed) != 0
+}
+
+/// Returns how many logs were dropped by the shutdown mode. (`std` feature)
+#[inline]
+pub fn suppressed_log_count() -> usize {
+    SUPPRESSED_COUNT.load(Ordering::Relaxed)
+}
+
+/// Returns `true` (and counts the log) if a log at `level` is suppressed by the shutdown mode.
+#[inline(always)]
+pub(crate) fn suppresses(level: Level) -> bool {
+    if (level as u8) < SUPPRESS_UP_TO.load(Ordering::Relaxed) {
+        SUPPRESSED_COUNT.fetch_add(1, Ordering::Relaxed);
+
+        return true;
+    }
+
+    false
+}
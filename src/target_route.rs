@@ -0,0 +1,139 @@
+use crate::LoggerFn;
+
+/// Target routes storage, in registration order.
+static TARGET_ROUTES: crate::spin::Mutex<[Option<(&'static str, LoggerFn)>; MAX_TARGET_ROUTES]> =
+    crate::spin::Mutex::new([None; MAX_TARGET_ROUTES]);
+
+/// Maximum number of routes that can be registered with [`add_target_route`].
+/// (`target_route` feature)
+pub const MAX_TARGET_ROUTES: usize = 8;
+
+/// Sends the logs whose target matches `pattern` to `sink`, instead of the loggers.
+/// (`target_route` feature)
+///
+/// The target of a log is its module path, unless set with `target:` (e.g.,
+/// `info!(target: "net", ...)`). The `pattern` is a glob matched against the whole target:
+///
+/// - `*` matches any sequence of characters, including `::` and the empty sequence, so
+///   `"net::*"` matches `net::http` and `net::http::client`, but not `net` itself, nor `network`.
+/// - Any other character matches itself, there's no other wildcard nor escaping.
+/// - A pattern without `*` only matches the exact target.
+///
+/// The routes are tried in the order they were added and the **first match wins**: the log is
+/// only passed to the sink of that route. Logs matching no route go to the loggers (set with
+/// `set_logger` and `add_logger`, or the default logger) as usual. The extensions see every log,
+/// routed or not.
+///
+/// Routing happens after the level filters, the filter and the other checks: a route decides where
+/// an emitted log goes, not whether it's emitted. The routes are checked (under a lock) for every
+/// log, but the sink is called after releasing it, so it can log itself.
+///
+/// Adding a route with an already registered pattern replaces its sink. Returns `false` if the
+/// route couldn't be added because [`MAX_TARGET_ROUTES`] routes are already registered.
+///
+/// # Example
+///
+/// ```
+/// use loggery::{info, Payload};
+/// use std::sync::atomic::{AtomicUsize, Ordering};
+///
+/// static NET_LOGS: AtomicUsize = AtomicUsize::new(0);
+/// static OTHER_LOGS: AtomicUsize = AtomicUsize::new(0);
+///
+/// fn net_sink(_: Payload) {
+///     NET_LOGS.fetch_add(1, Ordering::Relaxed);
+/// }
+///
+/// fn my_logger(_: Payload) {
+///     OTHER_LOGS.fetch_add(1, Ordering::Relaxed);
+/// }
+///
+/// fn main() {
+/// #   #[cfg(not(feature = "static"))]
+///     loggery::set_logger(my_logger);
+///     assert!(loggery::add_target_route("net::*", net_sink));
+///
+///     info!(target: "net::http", "GET /index.html");
+///     info!(target: "net", "Not matched, the pattern needs a submodule");
+///     info!(target: "db::pool", "Connected");
+///
+/// #   #[cfg(not(any(feature = "min_level_warn", feature = "min_level_error",
+/// #       feature = "min_level_off")))]
+/// #   {
+///     assert_eq!(NET_LOGS.load(Ordering::Relaxed), 1);
+/// #   #[cfg(not(feature = "static"))]
+///     assert_eq!(OTHER_LOGS.load(Ordering::Relaxed), 2);
+/// #   }
+/// }
+/// ```
+pub fn add_target_route(pattern: &'static str, sink: LoggerFn) -> bool {
+    let mut routes = TARGET_ROUTES.lock();
+
+    if let Some(route) = routes
+        .iter_mut()
+        .flatten()
+        .find(|(route_pattern, _)| *route_pattern == pattern)
+    {
+        route.1 = sink;
+
+        return true;
+    }
+
+    match routes.iter_mut().find(|route| route.is_none()) {
+        Some(slot) => {
+            *slot = Some((pattern, sink));
+
+            true
+        }
+        None => false,
+    }
+}
+
+/// Removes every route added with [`add_target_route`], so all logs go to the loggers again.
+/// (`target_route` feature)
+pub fn clear_target_routes() {
+    *TARGET_ROUTES.lock() = [None; MAX_TARGET_ROUTES];
+}
+
+/// Returns the sink of the first route matching `target`, if any.
+#[inline(always)]
+pub(crate) fn find(target: &str) -> Option<LoggerFn> {
+    let routes = TARGET_ROUTES.lock();
+
+    routes
+        .iter()
+        .flatten()
+        .find(|(pattern, _)| glob_matches(pattern.as_bytes(), target.as_bytes()))
+        .map(|(_, sink)| *sink)
+}
+
+/// Returns `true` if `text` matches the glob `pattern`, where `*` matches any sequence.
+fn glob_matches(pattern: &[u8], text: &[u8]) -> bool {
+    let (mut p, mut t) = (0, 0);
+    // Position of the last `*` in the pattern, and of the text it was tried at
+    let mut star: Option<(usize, usize)> = None;
+
+    while t < text.len() {
+        match pattern.get(p) {
+            Some(b'*') => {
+                star = Some((p, t));
+                p += 1;
+            }
+            Some(&c) if c == text[t] => {
+                p += 1;
+                t += 1;
+            }
+            _ => match star {
+                // Let the last `*` match one more character, and try again from there
+                Some((star_p, star_t)) => {
+                    star = Some((star_p, star_t + 1));
+                    p = star_p + 1;
+                    t = star_t + 1;
+                }
+                None => return false,
+            },
+        }
+    }
+
+    pattern[p..].iter().all(|&c| c == b'*')
+}
@@ -0,0 +1,425 @@
+//! Helpers for asserting on logs in tests. (`testing` feature)
+//!
+//! [`CaptureLogger`] captures everything that's logged, [`CaptureBuffer`] is the building block to
+//! capture logs in a custom logger or extension.
+
+extern crate std;
+
+use crate::{Level, Payload};
+
+use core::sync::atomic::{AtomicPtr, Ordering};
+use std::{boxed::Box, collections::VecDeque, string::String, sync::Mutex, vec::Vec};
+
+/// The buffer of [`CaptureLogger`], created on first use.
+static GLOBAL_BUFFER: AtomicPtr<CaptureBuffer> = AtomicPtr::new(core::ptr::null_mut());
+
+/// A captured log, with its message already rendered.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Record {
+    /// The severity level of the log.
+    pub level: Level,
+    /// The rendered message.
+    pub message: String,
+    #[cfg(feature = "metadata")]
+    /// The module path where the log was generated (requires `metadata` feature).
+    pub module_path: &'static str,
+    #[cfg(feature = "kv")]
+    /// The `key = value` fields of the log, with their values rendered (requires `kv` feature).
+    pub fields: Vec<(String, String)>,
+}
+
+/// An in-memory store of captured logs.
+///
+/// By default, the buffer is unbounded, which is fine for short tests. For long runs (e.g.,
+/// fuzzing), cap it with [`with_capacity`](CaptureBuffer::with_capacity) or
+/// [`set_capacity`](CaptureBuffer::set_capacity): once full, the oldest records are evicted to
+/// make room for new ones (like a ring buffer), and [`dropped`](CaptureBuffer::dropped) counts
+/// them.
+///
+/// # Example
+///
+/// ```
+/// use loggery::{info, testing::CaptureBuffer, Payload};
+///
+/// thread_local! {
+///     static CAPTURE: CaptureBuffer = CaptureBuffer::with_capacity(2);
+/// }
+///
+/// fn my_logger(payload: Payload) {
+///     CAPTURE.with(|capture| capture.push(&payload));
+/// }
+///
+/// fn main() {
+/// #   #[cfg(not(feature = "static"))]
+///     loggery::set_logger(my_logger);
+///
+///     for i in 0..5 {
+///         info!("Iteration {}", i);
+///     }
+///
+/// #   #[cfg(all(not(feature = "static"), not(feature = "min_level_off")))]
+///     CAPTURE.with(|capture| {
+///         let messages: Vec<String> = capture.records().into_iter().map(|r| r.message).collect();
+///
+///         // The 3 oldest records were evicted
+///         assert_eq!(messages, ["Iteration 3", "Iteration 4"]);
+///         assert_eq!(capture.dropped(), 3);
+///     });
+/// }
+/// ```
+pub struct CaptureBuffer {
+    state: Mutex<State>,
+}
+
+struct State {
+    records: VecDeque<Record>,
+    capacity: Option<usize>,
+    dropped: usize,
+}
+
+impl CaptureBuffer {
+    /// Creates an empty, unbounded buffer.
+    pub fn new() -> Self {
+        Self::from_capacity(None)
+    }
+
+    /// Creates an empty buffer holding at most `capacity` records.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self::from_capacity(Some(capacity))
+    }
+
+    fn from_capacity(capacity: Option<usize>) -> Self {
+        Self {
+            state: Mutex::new(State {
+                records: VecDeque::new(),
+                capacity,
+                dropped: 0,
+            }),
+        }
+    }
+
+    /// Sets the maximum number of records (`None` for unbounded), evicting the oldest records if
+    /// there are already more.
+    pub fn set_capacity(&self, capacity: Option<usize>) {
+        let mut state = self.lock();
+
+        state.capacity = capacity;
+        state.evict(0);
+    }
+
+    /// Captures a log, evicting the oldest record if the buffer is full.
+    pub fn push(&self, payload: &Payload) {
+        use core::fmt::Write as _;
+
+        let mut message = String::new();
+        let _ = write!(message, "{}", payload.args);
+
+        let record = Record {
+            level: payload.level,
+            message,
+            #[cfg(feature = "metadata")]
+            module_path: payload.meta.module_path,
+            #[cfg(feature = "kv")]
+            fields: payload
+                .fields
+                .iter()
+                .map(|(key, value)| (String::from(*key), std::format!("{}", value)))
+                .collect(),
+        };
+
+        let mut state = self.lock();
+
+        if state.capacity == Some(0) {
+            state.dropped += 1;
+
+            return;
+        }
+
+        state.evict(1);
+        state.records.push_back(record);
+    }
+
+    /// Returns a copy of the captured records, oldest first.
+    pub fn records(&self) -> Vec<Record> {
+        self.lock().records.iter().cloned().collect()
+    }
+
+    /// Renders the captured records as a table aligned in columns, oldest first, e.g., to print
+    /// them when a test fails.
+    ///
+    /// The table has a header row, a separator row and a row per record, with these columns,
+    /// separated by ` | `:
+    ///
+    /// - `LEVEL`: the level name, e.g., `INFO`.
+    /// - `MODULE`: the module path of the log (only with the `metadata` feature).
+    /// - `MESSAGE`: the message, followed by the ` key=value` fields with the `kv` feature. Line
+    ///   breaks are written as `\n`, so each record stays on one row.
+    ///
+    /// The columns are padded to their widest cell, except the last one. Every row ends with a
+    /// newline.
+    ///
+    /// ```text
+    /// LEVEL | MODULE      | MESSAGE
+    /// ------+-------------+------------------
+    /// INFO  | my_app      | Connecting
+    /// ERROR | my_app::net | Connection refused
+    /// ```
+    ///
+    /// For the global buffer of [`CaptureLogger`], use `CaptureLogger::buffer().to_table()`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use loggery::{error, info, testing::CaptureBuffer, Payload};
+    ///
+    /// thread_local! {
+    ///     static CAPTURE: CaptureBuffer = CaptureBuffer::new();
+    /// }
+    ///
+    /// fn my_logger(payload: Payload) {
+    ///     CAPTURE.with(|capture| capture.push(&payload));
+    /// }
+    ///
+    /// fn main() {
+    /// #   #[cfg(not(feature = "static"))]
+    ///     loggery::set_logger(my_logger);
+    ///
+    ///     info!("Connecting");
+    ///     error!("Connection refused");
+    ///
+    /// #   #[cfg(all(feature = "metadata", not(feature = "kv"), not(feature = "static"),
+    /// #       not(feature = "min_level_off")))]
+    ///     CAPTURE.with(|capture| {
+    ///         assert_eq!(
+    ///             capture.to_table(),
+    ///             "LEVEL | MODULE   | MESSAGE\n\
+    ///              ------+----------+-------------------\n\
+    ///              INFO  | rust_out | Connecting\n\
+    ///              ERROR | rust_out | Connection refused\n"
+    ///         );
+    ///     });
+    /// }
+    /// ```
+    pub fn to_table(&self) -> String {
+        let state = self.lock();
+
+        let rows: Vec<Vec<String>> = core::iter::once(header())
+            .chain(state.records.iter().map(cells))
+            .collect();
+
+        let mut widths = [0; COLUMNS];
+
+        for row in &rows {
+            for (width, cell) in widths.iter_mut().zip(row) {
+                *width = (*width).max(cell.chars().count());
+            }
+        }
+
+        let mut table = String::new();
+
+        for (index, row) in rows.iter().enumerate() {
+            push_row(&mut table, row, &widths, " | ");
+
+            if index == 0 {
+                let dashes: Vec<String> = widths.iter().map(|width| "-".repeat(*width)).collect();
+
+                push_row(&mut table, &dashes, &widths, "-+-");
+            }
+        }
+
+        table
+    }
+
+    /// Returns the number of records evicted because the buffer was full.
+    pub fn dropped(&self) -> usize {
+        self.lock().dropped
+    }
+
+    /// Removes all records and resets the dropped count.
+    pub fn clear(&self) {
+        let mut state = self.lock();
+
+        state.records.clear();
+        state.dropped = 0;
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl Default for CaptureBuffer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The number of columns of [`CaptureBuffer::to_table`].
+#[cfg(feature = "metadata")]
+const COLUMNS: usize = 3;
+/// The number of columns of [`CaptureBuffer::to_table`].
+#[cfg(not(feature = "metadata"))]
+const COLUMNS: usize = 2;
+
+/// Returns the header row of [`CaptureBuffer::to_table`].
+fn header() -> Vec<String> {
+    let mut row = Vec::with_capacity(COLUMNS);
+
+    row.push("LEVEL".into());
+    #[cfg(feature = "metadata")]
+    row.push("MODULE".into());
+    row.push("MESSAGE".into());
+
+    row
+}
+
+/// Returns the row of a record in [`CaptureBuffer::to_table`].
+fn cells(record: &Record) -> Vec<String> {
+    #[cfg_attr(not(feature = "kv"), allow(unused_mut))]
+    let mut message = record.message.replace('\n', "\\n");
+
+    #[cfg(feature = "kv")]
+    for (key, value) in &record.fields {
+        message.push(' ');
+        message.push_str(key);
+        message.push('=');
+        message.push_str(&value.replace('\n', "\\n"));
+    }
+
+    let mut row = Vec::with_capacity(COLUMNS);
+
+    row.push(record.level.name().into());
+    #[cfg(feature = "metadata")]
+    row.push(record.module_path.into());
+    row.push(message);
+
+    row
+}
+
+/// Appends a row to a table, padding every cell but the last one to the width of its column.
+fn push_row(table: &mut String, row: &[String], widths: &[usize], separator: &str) {
+    for (index, (cell, width)) in row.iter().zip(widths).enumerate() {
+        if index > 0 {
+            table.push_str(separator);
+        }
+
+        table.push_str(cell);
+
+        if index + 1 < row.len() {
+            let padding = width - cell.chars().count();
+
+            table.extend(core::iter::repeat(' ').take(padding));
+        }
+    }
+
+    table.push('\n');
+}
+
+impl State {
+    /// Evicts the oldest records until `room` more records fit.
+    fn evict(&mut self, room: usize) {
+        if let Some(capacity) = self.capacity {
+            while !self.records.is_empty() && self.records.len() + room > capacity {
+                self.records.pop_front();
+                self.dropped += 1;
+            }
+        }
+    }
+}
+
+/// A logger storing every log in a global [`CaptureBuffer`], to assert on the logs of the code
+/// under test.
+///
+/// [`CaptureLogger::log`] is a [`LoggerFn`](crate::LoggerFn), so it's installed like any other
+/// logger (with [`install`](CaptureLogger::install), or with `set_logger` / `add_logger` to keep
+/// other loggers), then [`records`](CaptureLogger::records) returns what was logged.
+///
+/// # Example
+///
+/// ```
+/// use loggery::{error, info, testing::CaptureLogger, Level};
+///
+/// fn main() {
+/// #   #[cfg(not(feature = "static"))]
+///     CaptureLogger::install();
+///
+///     info!("Connecting");
+///     error!("Connection refused");
+///
+/// #   #[cfg(all(not(feature = "static"), not(feature = "min_level_off")))]
+/// #   {
+///     let records = CaptureLogger::records();
+///
+///     assert_eq!(records.len(), 2);
+///     assert_eq!(records[1].level, Level::Error);
+///     assert_eq!(records[1].message, "Connection refused");
+/// #   }
+///
+///     CaptureLogger::clear();
+///
+///     assert!(CaptureLogger::records().is_empty());
+/// }
+/// ```
+///
+/// # Parallel tests
+///
+/// The logger and the buffer are global, while `cargo test` runs tests in parallel threads: a test
+/// can see the logs of another one, or have its logs cleared by it. Run the tests capturing logs
+/// one at a time, e.g., by locking a shared `Mutex` at their start (or with `--test-threads=1`),
+/// and [`clear`](CaptureLogger::clear) the buffer at their start.
+pub struct CaptureLogger;
+
+impl CaptureLogger {
+    /// Sets [`CaptureLogger::log`] as the only logger and clears the captured records.
+    /// (NOT `static` feature)
+    #[cfg(not(feature = "static"))]
+    pub fn install() {
+        Self::clear();
+        crate::set_logger(Self::log);
+    }
+
+    /// Captures a log, the logger function to install with `set_logger` or `add_logger`.
+    pub fn log(payload: Payload) {
+        Self::buffer().push(&payload);
+    }
+
+    /// Returns a copy of the captured records, oldest first.
+    pub fn records() -> Vec<Record> {
+        Self::buffer().records()
+    }
+
+    /// Removes all captured records.
+    pub fn clear() {
+        Self::buffer().clear();
+    }
+
+    /// Returns the global buffer, e.g., to cap it with
+    /// [`set_capacity`](CaptureBuffer::set_capacity).
+    pub fn buffer() -> &'static CaptureBuffer {
+        let ptr = GLOBAL_BUFFER.load(Ordering::Acquire);
+
+        if !ptr.is_null() {
+            // SAFETY: `ptr` was leaked from a `Box` below and is never freed.
+            return unsafe { &*ptr };
+        }
+
+        let new = Box::into_raw(Box::new(CaptureBuffer::new()));
+
+        match GLOBAL_BUFFER.compare_exchange(
+            core::ptr::null_mut(),
+            new,
+            Ordering::AcqRel,
+            Ordering::Acquire,
+        ) {
+            // SAFETY: `new` was just leaked from a `Box` and is never freed.
+            Ok(_) => unsafe { &*new },
+            Err(existing) => {
+                // SAFETY: `new` lost the race and was never shared.
+                drop(unsafe { Box::from_raw(new) });
+
+                // SAFETY: `existing` was leaked from a `Box` and is never freed.
+                unsafe { &*existing }
+            }
+        }
+    }
+}
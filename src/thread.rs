@@ -0,0 +1,55 @@
+extern crate std;
+
+use core::sync::atomic::{AtomicUsize, Ordering};
+
+/// ID of the next thread that logs.
+static NEXT_THREAD_ID: AtomicUsize = AtomicUsize::new(1);
+
+std::thread_local! {
+    /// ID of the thread, assigned when it first logs.
+    static THREAD_ID: u64 = NEXT_THREAD_ID.fetch_add(1, Ordering::Relaxed) as u64;
+}
+
+/// Returns the ID of the current thread, as stored in
+/// [`Metadata::thread_id`](crate::Metadata::thread_id). (`thread_meta` feature)
+///
+/// IDs are small numbers assigned in the order threads first log (or call this function),
+/// starting at `1`. Unlike [`std::thread::ThreadId`], they can be stored in the `Copy`
+/// [`Metadata`](crate::Metadata) and printed as is. `0` is returned while the thread is being
+/// destroyed.
+///
+/// # Example
+///
+/// ```
+/// let id = loggery::current_thread_id();
+///
+/// assert_ne!(id, 0);
+/// assert_eq!(loggery::current_thread_id(), id);
+///
+/// let other = std::thread::spawn(loggery::current_thread_id).join().unwrap();
+///
+/// assert_ne!(other, id);
+/// ```
+#[inline]
+pub fn current_thread_id() -> u64 {
+    THREAD_ID.try_with(|id| *id).unwrap_or(0)
+}
+
+/// Writes the thread that logged as its name, or `thread-ID` if it's unnamed.
+///
+/// Thread names aren't `'static`, so they aren't stored in the metadata: the name is read from
+/// the current thread, which is the thread that logged unless the log is rendered elsewhere
+/// (e.g., replayed by `dump_recent`), in which case only the ID is written.
+pub(crate) struct ThreadLabel(pub(crate) u64);
+
+impl core::fmt::Display for ThreadLabel {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        if self.0 == current_thread_id() {
+            if let Some(name) = std::thread::current().name() {
+                return f.write_str(name);
+            }
+        }
+
+        write!(f, "thread-{}", self.0)
+    }
+}
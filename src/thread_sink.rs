@@ -0,0 +1,142 @@
+extern crate std;
+
+use core::{any::Any, cell::RefCell, marker::PhantomData};
+use std::{boxed::Box, vec::Vec};
+
+std::thread_local! {
+    /// Sinks of the thread, at most one per type.
+    static THREAD_SINKS: RefCell<Vec<Box<dyn Any>>> = RefCell::new(Vec::new());
+}
+
+/// Stores `sink` as the current thread's sink of type `T`, until the returned guard is dropped.
+/// (`std` feature)
+///
+/// Loggers are plain `fn` pointers ([`LoggerFn`](crate::LoggerFn)), they can't capture state. For
+/// request-scoped sinks (e.g., a log file per request), keep the state in the thread handling the
+/// request instead: set it with `set_thread_sink` when the request starts, and reach it from the
+/// logger with [`with_thread_sink`], without boxing the logger itself.
+///
+/// # Lifetime
+///
+/// - The sink belongs to the thread that set it: loggers called from other threads don't see it,
+///   so logs emitted there (or after moving the work to another thread) fall back to whatever the
+///   logger does without a sink.
+/// - It's dropped with the guard (or when the thread exits, if the guard is leaked), so a sink
+///   flushing on drop (e.g., a `BufWriter`) is flushed when the request ends.
+/// - There's one sink per type and thread: setting another sink of the same type replaces it, and
+///   dropping the guard brings the previous one back, so they can be nested. Guards should be
+///   dropped in reverse order, as they are with scopes.
+///
+/// # Example
+///
+/// ```
+/// use loggery::{info, Payload};
+///
+/// // One buffer per request, filled by a plain `fn` logger
+/// struct RequestLog {
+///     lines: Vec<String>,
+/// }
+///
+/// fn request_logger(payload: Payload) {
+///     loggery::with_thread_sink(|log: &mut RequestLog| log.lines.push(payload.args.to_string()));
+/// }
+///
+/// fn handle_request(id: u32) -> Vec<String> {
+///     let _guard = loggery::set_thread_sink(RequestLog { lines: Vec::new() });
+///
+///     info!("Request {} started", id);
+///     info!("Request {} done", id);
+///
+///     loggery::with_thread_sink(|log: &mut RequestLog| log.lines.clone()).unwrap()
+/// }
+///
+/// fn main() {
+/// #   #[cfg(not(feature = "static"))]
+///     loggery::set_logger(request_logger);
+///
+///     let handles: Vec<_> = (1..=2)
+///         .map(|id| std::thread::spawn(move || handle_request(id)))
+///         .collect();
+///
+///     let logs: Vec<_> = handles.into_iter().map(|handle| handle.join().unwrap()).collect();
+///
+/// #   #[cfg(all(not(feature = "static"), not(any(feature = "min_level_warn",
+/// #       feature = "min_level_error", feature = "min_level_off"))))]
+/// #   {
+///     assert_eq!(logs[0], ["Request 1 started", "Request 1 done"]);
+///     assert_eq!(logs[1], ["Request 2 started", "Request 2 done"]);
+/// #   }
+///
+///     // The sinks were dropped with the guards
+///     assert!(loggery::with_thread_sink(|_: &mut RequestLog| ()).is_none());
+/// }
+/// ```
+pub fn set_thread_sink<T: 'static>(sink: T) -> ThreadSinkGuard<T> {
+    let previous = THREAD_SINKS
+        .try_with(|sinks| {
+            let mut sinks = sinks.try_borrow_mut().ok()?;
+            let previous = take::<T>(&mut sinks);
+            sinks.push(Box::new(sink));
+
+            previous
+        })
+        .ok()
+        .flatten();
+
+    ThreadSinkGuard {
+        previous,
+        _not_send: PhantomData,
+    }
+}
+
+/// Calls `f` with the current thread's sink of type `T`, set with [`set_thread_sink`].
+/// (`std` feature)
+///
+/// Returns `None` without calling `f` if the thread has no sink of that type, or if it's already
+/// being used (e.g., when `f` logs and the logger calls `with_thread_sink` again).
+pub fn with_thread_sink<T: 'static, R>(f: impl FnOnce(&mut T) -> R) -> Option<R> {
+    THREAD_SINKS
+        .try_with(|sinks| {
+            let mut sinks = sinks.try_borrow_mut().ok()?;
+            let sink = sinks.iter_mut().find_map(|sink| sink.downcast_mut::<T>())?;
+
+            Some(f(sink))
+        })
+        .ok()
+        .flatten()
+}
+
+/// Removes the sink of type `T` from `sinks`.
+fn take<T: 'static>(sinks: &mut Vec<Box<dyn Any>>) -> Option<Box<dyn Any>> {
+    let index = sinks.iter().position(|sink| sink.is::<T>())?;
+
+    Some(sinks.swap_remove(index))
+}
+
+/// Guard of a sink set with [`set_thread_sink`], dropping it (and bringing the previous sink of
+/// the same type back, if any) when dropped. (`std` feature)
+///
+/// It can't be sent to another thread, since sinks are stored per thread.
+#[must_use = "the sink is dropped as soon as the guard is dropped"]
+pub struct ThreadSinkGuard<T: 'static> {
+    previous: Option<Box<dyn Any>>,
+    _not_send: PhantomData<*const T>,
+}
+
+impl<T: 'static> Drop for ThreadSinkGuard<T> {
+    fn drop(&mut self) {
+        let previous = self.previous.take();
+
+        let _ = THREAD_SINKS.try_with(|sinks| {
+            if let Ok(mut sinks) = sinks.try_borrow_mut() {
+                // Dropped after releasing the borrow, in case dropping the sink logs
+                let sink = take::<T>(&mut sinks);
+                sinks.extend(previous);
+
+                sink
+            } else {
+                None
+            }
+        });
+    }
+}
@@ -0,0 +1,260 @@
+#[cfg(feature = "std")]
+extern crate std;
+
+use core::sync::atomic::{AtomicBool, AtomicPtr, AtomicU32, AtomicU8, Ordering};
+
+/// Timestamp rendering format storage.
+static TIMESTAMP_FORMAT: AtomicU8 = AtomicU8::new(TimestampFormat::Rfc3339 as u8);
+/// Clock function pointer storage.
+static CLOCK_FN: AtomicPtr<()> = AtomicPtr::new(core::ptr::null_mut());
+/// Timestamp of the first captured log, used by [`TimestampFormat::RelativeSecs`].
+///
+/// Split in two halves since 64-bit atomics aren't available on every target.
+static START_HIGH: AtomicU32 = AtomicU32::new(0);
+static START_LOW: AtomicU32 = AtomicU32::new(0);
+static START_SET: AtomicBool = AtomicBool::new(false);
+
+/// Function type for a custom clock, returning milliseconds since the UNIX epoch.
+/// (`timestamp` feature)
+pub type ClockFn = fn() -> u64;
+
+/// The ways a log timestamp can be rendered. (`timestamp` feature)
+///
+/// | Format         | Example                    | Description                              |
+/// |----------------|----------------------------|------------------------------------------|
+/// | `Rfc3339`      | `2023-11-14T22:13:20.123Z` | UTC date and time with milliseconds      |
+/// | `EpochMillis`  | `1700000000123`            | Milliseconds since the UNIX epoch        |
+/// | `EpochSecs`    | `1700000000`               | Whole seconds since the UNIX epoch       |
+/// | `RelativeSecs` | `12.345`                   | Seconds since the first log was captured |
+///
+/// # Example
+///
+/// ```
+/// use loggery::TimestampFormat;
+///
+/// let millis = 1_700_000_000_123;
+///
+/// assert_eq!(
+///     TimestampFormat::Rfc3339.display(millis).to_string(),
+///     "2023-11-14T22:13:20.123Z"
+/// );
+/// assert_eq!(
+///     TimestampFormat::EpochMillis.display(millis).to_string(),
+///     "1700000000123"
+/// );
+/// assert_eq!(
+///     TimestampFormat::EpochSecs.display(millis).to_string(),
+///     "1700000000"
+/// );
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum TimestampFormat {
+    /// RFC 3339 UTC date and time with millisecond precision (default).
+    Rfc3339 = 0,
+    /// Milliseconds since the UNIX epoch, as a plain integer.
+    EpochMillis = 1,
+    /// Seconds since the UNIX epoch, as a plain integer.
+    EpochSecs = 2,
+    /// Seconds (with milliseconds) elapsed since the first log was captured.
+    RelativeSecs = 3,
+}
+
+impl TimestampFormat {
+    /// Returns a value that renders `millis` (milliseconds since the UNIX epoch) in this format.
+    #[inline]
+    pub fn display(self, millis: u64) -> DisplayTimestamp {
+        DisplayTimestamp {
+            millis,
+            format: self,
+        }
+    }
+
+    #[inline(always)]
+    fn from_u8(value: u8) -> Self {
+        match value {
+            1 => TimestampFormat::EpochMillis,
+            2 => TimestampFormat::EpochSecs,
+            3 => TimestampFormat::RelativeSecs,
+            _ => TimestampFormat::Rfc3339,
+        }
+    }
+}
+
+/// A timestamp rendered in a [`TimestampFormat`], created by [`TimestampFormat::display`].
+/// (`timestamp` feature)
+#[derive(Debug, Clone, Copy)]
+pub struct DisplayTimestamp {
+    millis: u64,
+    format: TimestampFormat,
+}
+
+impl core::fmt::Display for DisplayTimestamp {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let millis = self.millis;
+
+        match self.format {
+            TimestampFormat::Rfc3339 => {
+                let (year, month, day) = civil_from_days((millis / 86_400_000) as i64);
+                let time = millis % 86_400_000;
+
+                write!(
+                    f,
+                    "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}.{:03}Z",
+                    year,
+                    month,
+                    day,
+                    time / 3_600_000,
+                    time / 60_000 % 60,
+                    time / 1_000 % 60,
+                    time % 1_000
+                )
+            }
+            TimestampFormat::EpochMillis => write!(f, "{}", millis),
+            TimestampFormat::EpochSecs => write!(f, "{}", millis / 1_000),
+            TimestampFormat::RelativeSecs => {
+                let elapsed = millis.saturating_sub(start());
+
+                write!(f, "{}.{:03}", elapsed / 1_000, elapsed % 1_000)
+            }
+        }
+    }
+}
+
+/// Sets how the default logger renders timestamps. (`timestamp` feature)
+///
+/// Defaults to [`TimestampFormat::Rfc3339`].
+///
+/// # Example
+///
+/// ```
+/// use loggery::{TimestampFormat, info};
+///
+/// loggery::set_timestamp_format(TimestampFormat::EpochMillis);
+///
+/// info!("Machine friendly timestamp!");
+/// ```
+///
+/// Output:
+///
+/// ```text
+/// 1700000000123 [ INFO] Machine friendly timestamp!
+/// ```
+#[inline(always)]
+pub fn set_timestamp_format(format: TimestampFormat) {
+    TIMESTAMP_FORMAT.store(format as u8, Ordering::Relaxed);
+}
+
+/// Returns the timestamp format used by the default logger. (`timestamp` feature)
+#[inline(always)]
+pub fn get_timestamp_format() -> TimestampFormat {
+    TimestampFormat::from_u8(TIMESTAMP_FORMAT.load(Ordering::Relaxed))
+}
+
+/// Sets the clock used to capture log timestamps. (`timestamp` feature)
+///
+/// Without the `std` feature there's no system clock, so timestamps are `0` until a clock is set,
+/// e.g., one reading an RTC or a monotonic timer on embedded targets. With the `std` feature, the
+/// system clock is used by default and this function can override it (e.g., for tests).
+///
+/// The clock should return milliseconds since the UNIX epoch for [`TimestampFormat::Rfc3339`] to
+/// make sense. A clock that counts from boot works fine with the other formats.
+///
+/// # Example
+///
+/// ```
+/// use loggery::{info, Payload};
+/// use std::sync::atomic::{AtomicU64, Ordering};
+///
+/// static LAST_TIMESTAMP: AtomicU64 = AtomicU64::new(0);
+///
+/// fn my_logger(payload: Payload) {
+///     LAST_TIMESTAMP.store(payload.timestamp, Ordering::Relaxed);
+/// }
+///
+/// fn main() {
+///     loggery::set_clock(|| 1_700_000_000_123);
+///
+/// #   #[cfg(not(feature = "static"))]
+/// #   {
+///     loggery::set_logger(my_logger);
+///
+///     info!("What time is it?");
+///
+/// #   #[cfg(not(feature = "min_level_off"))]
+///     assert_eq!(LAST_TIMESTAMP.load(Ordering::Relaxed), 1_700_000_000_123);
+/// #   }
+/// }
+/// ```
+#[inline(always)]
+pub fn set_clock(clock_fn: ClockFn) {
+    CLOCK_FN.store(clock_fn as *mut (), Ordering::Release);
+}
+
+/// Returns the current time in milliseconds since the UNIX epoch.
+#[inline]
+pub(crate) fn now() -> u64 {
+    let ptr = CLOCK_FN.load(Ordering::Acquire);
+
+    let millis = if ptr.is_null() {
+        system_now()
+    } else {
+        // SAFETY: `ptr` was created from a `ClockFn` in `set_clock`. Function pointers are
+        // 'static. Atomics ensure cross-thread visibility.
+        unsafe { core::mem::transmute::<*mut (), ClockFn>(ptr)() }
+    };
+
+    // Racing threads may both store their own start, which only differs by a few milliseconds
+    if !START_SET.load(Ordering::Acquire) {
+        START_HIGH.store((millis >> 32) as u32, Ordering::Relaxed);
+        START_LOW.store(millis as u32, Ordering::Relaxed);
+        START_SET.store(true, Ordering::Release);
+    }
+
+    millis
+}
+
+/// Returns the timestamp of the first captured log.
+#[inline]
+fn start() -> u64 {
+    if !START_SET.load(Ordering::Acquire) {
+        return 0;
+    }
+
+    (u64::from(START_HIGH.load(Ordering::Relaxed)) << 32)
+        | u64::from(START_LOW.load(Ordering::Relaxed))
+}
+
+/// Returns the system time (`std` feature).
+#[cfg(feature = "std")]
+#[inline]
+fn system_now() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|duration| duration.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+/// There's no system clock without the `std` feature.
+#[cfg(not(feature = "std"))]
+#[inline]
+fn system_now() -> u64 {
+    0
+}
+
+/// Converts days since the UNIX epoch to a `(year, month, day)` civil date.
+///
+/// See: <https://howardhinnant.github.io/date_algorithms.html#civil_from_days>
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = (if mp < 10 { mp + 3 } else { mp - 9 }) as u32;
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    (year, month, day)
+}
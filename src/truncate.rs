@@ -0,0 +1,132 @@
+use core::sync::atomic::{AtomicUsize, Ordering};
+
+/// Maximum message length in bytes, `usize::MAX` when unlimited.
+static MAX_MESSAGE_LEN: AtomicUsize = AtomicUsize::new(usize::MAX);
+
+/// Appended to a message cut by [`set_max_message_len`].
+#[cfg(feature = "std")]
+pub(crate) const TRUNCATION_MARKER: &str = "…";
+
+/// Limits the length of the messages written by the built-in sinks to `max_len` bytes (`None` for
+/// unlimited, the default).
+///
+/// The limit applies to the message (with its `key = value` fields) as rendered by the default
+/// logger and the built-in text extensions (e.g., `save_to_file`), not to the level label or the
+/// timestamp. A longer message is cut and ends with `…`, so the marker makes it at most
+/// `max_len + 3` bytes.
+///
+/// The message is never cut in the middle of a multi-byte UTF-8 character: the cut moves back to
+/// the previous character boundary (see [`truncate_str`]), so a truncated message is always valid
+/// UTF-8 and can be a few bytes shorter than `max_len`.
+///
+/// # Example
+///
+/// ```
+/// loggery::set_max_message_len(Some(9));
+///
+/// assert_eq!(loggery::get_max_message_len(), Some(9));
+///
+/// # #[cfg(feature = "std")]
+/// # {
+/// use loggery::{error, Destination, Level, StreamRouting};
+///
+/// // Send the default logger's output to a file to check it
+/// let path = std::env::temp_dir().join("loggery_max_message_len_doctest.log");
+/// let _ = std::fs::remove_file(&path);
+/// let path: &'static str = Box::leak(path.to_string_lossy().into_owned().into_boxed_str());
+///
+/// loggery::set_stream_routing(StreamRouting::new().route(Level::Error, Destination::File(path)));
+///
+/// // "é" is 2 bytes long, the 9th and 10th bytes: it would be split, so it's left out
+/// error!("Failed: é!");
+///
+/// # #[cfg(all(any(not(feature = "static"), feature = "static_default"), not(feature = "min_level_off"),
+/// #     not(feature = "thread_meta"), not(feature = "cpu_meta")))]
+/// assert!(std::fs::read_to_string(path)
+///     .unwrap()
+///     .ends_with("[ERROR] Failed: …\n"));
+/// # }
+/// ```
+#[inline]
+pub fn set_max_message_len(max_len: Option<usize>) {
+    MAX_MESSAGE_LEN.store(max_len.unwrap_or(usize::MAX), Ordering::Relaxed);
+}
+
+/// Returns the maximum message length set with [`set_max_message_len`].
+#[inline]
+pub fn get_max_message_len() -> Option<usize> {
+    match MAX_MESSAGE_LEN.load(Ordering::Relaxed) {
+        usize::MAX => None,
+        max_len => Some(max_len),
+    }
+}
+
+/// Returns the longest prefix of `s` that is at most `max_len` bytes long and doesn't split a
+/// UTF-8 character.
+///
+/// If `max_len` falls in the middle of a multi-byte character, that character is left out.
+///
+/// # Example
+///
+/// ```
+/// // "€" is 3 bytes long, at the bytes 2 to 4
+/// assert_eq!(loggery::truncate_str("1 €", 3), "1 ");
+/// assert_eq!(loggery::truncate_str("1 €", 5), "1 €");
+/// assert_eq!(loggery::truncate_str("1 €", 100), "1 €");
+/// ```
+#[inline]
+pub fn truncate_str(s: &str, max_len: usize) -> &str {
+    if s.len() <= max_len {
+        return s;
+    }
+
+    // A char boundary is at most 3 bytes before any index
+    let end = (0..=max_len)
+        .rev()
+        .find(|&index| s.is_char_boundary(index))
+        .unwrap_or(0);
+
+    &s[..end]
+}
+
+/// Writes at most `remaining` bytes to the inner writer, cutting on a char boundary.
+#[cfg(feature = "std")]
+pub(crate) struct Truncate<W> {
+    inner: W,
+    remaining: usize,
+    truncated: bool,
+}
+
+#[cfg(feature = "std")]
+impl<W: core::fmt::Write> Truncate<W> {
+    pub(crate) fn new(inner: W, max_len: usize) -> Self {
+        Self {
+            inner,
+            remaining: max_len,
+            truncated: false,
+        }
+    }
+
+    /// Returns whether anything was cut.
+    pub(crate) fn truncated(&self) -> bool {
+        self.truncated
+    }
+}
+
+#[cfg(feature = "std")]
+impl<W: core::fmt::Write> core::fmt::Write for Truncate<W> {
+    fn write_str(&mut self, s: &str) -> core::fmt::Result {
+        if self.truncated {
+            return Ok(());
+        }
+
+        if s.len() <= self.remaining {
+            self.remaining -= s.len();
+
+            return self.inner.write_str(s);
+        }
+
+        self.truncated = true;
+        self.inner.write_str(truncate_str(s, self.remaining))
+    }
+}